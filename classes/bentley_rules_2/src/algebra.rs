@@ -0,0 +1,408 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use std::ops::{Add, Mul, Neg, Sub};
+
+// Element-wise algebra directly on the compressed rows. Every constructor leaves the columns
+// of a row strictly increasing (`validate` rejects anything else), so A ± B is a two-pointer
+// merge per row, like the merge step of merge sort: O(nnz(A) + nnz(B)) instead of
+// O(rows · cols) through a dense round trip.
+//
+// Results never store zeroes: entries that cancel (A - A) or map to zero are dropped,
+// the same invariant `new` and `from_triplets` keep.
+
+impl<T> CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    /// Union merge: `f(a, b)` wherever either matrix has an entry, the missing side reads 0.
+    pub fn merge_with<F>(&self, other: &Self, f: F) -> Self
+    where
+        F: Fn(T, T) -> T,
+    {
+        self.assert_same_shape(other);
+        let zero = T::default();
+
+        self.build_rows(|row, column_indices, values| {
+            let (cols_a, vals_a) = self.row(row);
+            let (cols_b, vals_b) = other.row(row);
+            let (mut a, mut b) = (0, 0);
+
+            while a < cols_a.len() || b < cols_b.len() {
+                let col_a = cols_a.get(a).copied().unwrap_or(usize::MAX);
+                let col_b = cols_b.get(b).copied().unwrap_or(usize::MAX);
+                let (col, val) = match col_a.cmp(&col_b) {
+                    std::cmp::Ordering::Less => {
+                        a += 1;
+                        (col_a, f(vals_a[a - 1], zero))
+                    }
+                    std::cmp::Ordering::Greater => {
+                        b += 1;
+                        (col_b, f(zero, vals_b[b - 1]))
+                    }
+                    std::cmp::Ordering::Equal => {
+                        a += 1;
+                        b += 1;
+                        (col_a, f(vals_a[a - 1], vals_b[b - 1]))
+                    }
+                };
+                if val != zero {
+                    column_indices.push(col);
+                    values.push(val);
+                }
+            }
+        })
+    }
+
+    /// A ∘ B, the element-wise product. Only columns present in both rows survive, so this
+    /// is an intersection merge.
+    pub fn hadamard(&self, other: &Self) -> Self {
+        self.assert_same_shape(other);
+
+        self.build_rows(|row, column_indices, values| {
+            let (cols_a, vals_a) = self.row(row);
+            let (cols_b, vals_b) = other.row(row);
+            let (mut a, mut b) = (0, 0);
+
+            while a < cols_a.len() && b < cols_b.len() {
+                if cols_a[a] == cols_b[b] {
+                    let val = vals_a[a] * vals_b[b];
+                    if val != T::default() {
+                        column_indices.push(cols_a[a]);
+                        values.push(val);
+                    }
+                }
+                // advance whichever side is behind, both on a match
+                let (col_a, col_b) = (cols_a[a], cols_b[b]);
+                a += (col_a <= col_b) as usize;
+                b += (col_b <= col_a) as usize;
+            }
+        })
+    }
+
+    /// alpha · A
+    pub fn scale(&self, alpha: T) -> Self {
+        self.map(|val| val * alpha)
+    }
+
+    /// D · A for D = diag(d), multiplies row i by d[i].
+    pub fn scale_rows(&self, d: &[T]) -> Self {
+        assert_eq!(d.len(), self.rows(), "d must have one entry per row");
+        self.map_indexed(|row, _, val| d[row] * val)
+    }
+
+    /// A · D for D = diag(d), multiplies column j by d[j].
+    pub fn scale_columns(&self, d: &[T]) -> Self {
+        assert_eq!(d.len(), self.cols(), "d must have one entry per column");
+        self.map_indexed(|_, col, val| val * d[col])
+    }
+
+    /// Applies `f` to every stored value, keeping the sparsity pattern minus new zeroes.
+    /// Zeroes that aren't stored stay zero, so `f(0)` is assumed to be 0.
+    pub fn map<U, F>(&self, f: F) -> CompressedSparseRow<U>
+    where
+        U: Add<Output = U> + Mul<Output = U> + Default + Copy + PartialEq,
+        F: Fn(T) -> U,
+    {
+        self.map_indexed(|_, _, val| f(val))
+    }
+
+    fn map_indexed<U, F>(&self, f: F) -> CompressedSparseRow<U>
+    where
+        U: Add<Output = U> + Mul<Output = U> + Default + Copy + PartialEq,
+        F: Fn(usize, usize, T) -> U,
+    {
+        let mut row_pointers = Vec::with_capacity(self.rows() + 1);
+        let mut column_indices = Vec::with_capacity(self.nnz());
+        let mut values = Vec::with_capacity(self.nnz());
+
+        row_pointers.push(0);
+        for row in 0..self.rows() {
+            let (cols, vals) = self.row(row);
+            for (&col, &val) in cols.iter().zip(vals) {
+                let mapped = f(row, col, val);
+                if mapped != U::default() {
+                    column_indices.push(col);
+                    values.push(mapped);
+                }
+            }
+            row_pointers.push(values.len());
+        }
+
+        CompressedSparseRow::from_validated_parts(self.cols(), row_pointers, column_indices, values)
+    }
+
+    // Builds a matrix of the same shape row by row, `fill` appends the sorted entries of a row.
+    fn build_rows<F>(&self, mut fill: F) -> Self
+    where
+        F: FnMut(usize, &mut Vec<usize>, &mut Vec<T>),
+    {
+        let mut row_pointers = Vec::with_capacity(self.rows() + 1);
+        let mut column_indices = Vec::new();
+        let mut values = Vec::new();
+
+        row_pointers.push(0);
+        for row in 0..self.rows() {
+            fill(row, &mut column_indices, &mut values);
+            row_pointers.push(values.len());
+        }
+
+        Self::from_validated_parts(self.cols(), row_pointers, column_indices, values)
+    }
+
+    fn assert_same_shape(&self, other: &Self) {
+        assert_eq!(
+            (self.rows(), self.cols()),
+            (other.rows(), other.cols()),
+            "matrix shapes differ"
+        );
+    }
+}
+
+impl<T> CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Neg<Output = T> + Default + Copy + PartialOrd,
+{
+    /// |A| element-wise.
+    pub fn abs(&self) -> Self {
+        self.map(|val| if val < T::default() { -val } else { val })
+    }
+}
+
+/// Lossy conversion for norms, `as f64`. Unlike `Into<f64>` it covers i64 and u64, which
+/// round to the nearest f64 above 2^53.
+pub trait AsF64: Copy {
+    fn as_f64(self) -> f64;
+}
+
+macro_rules! impl_as_f64 {
+    ($($t:ty),*) => {
+        $(impl AsF64 for $t {
+            #[inline(always)]
+            fn as_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+impl_as_f64!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+// Norms go through f64 so integer matrices don't overflow while summing.
+impl<T> CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq + AsF64,
+{
+    /// sqrt(Σ a_ij²)
+    pub fn frobenius_norm(&self) -> f64 {
+        self.values()
+            .iter()
+            .map(|&val| val.as_f64() * val.as_f64())
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Largest absolute column sum. Columns are scattered over the rows, so this needs
+    /// one accumulator per column.
+    pub fn norm_1(&self) -> f64 {
+        let mut sums = vec![0.0; self.cols()];
+        for (&col, &val) in self.column_indices().iter().zip(self.values()) {
+            sums[col] += val.as_f64().abs();
+        }
+        sums.into_iter().fold(0.0, f64::max)
+    }
+
+    /// Largest absolute row sum, a single pass over the rows.
+    pub fn norm_inf(&self) -> f64 {
+        (0..self.rows())
+            .map(|row| self.row(row).1.iter().map(|&val| val.as_f64().abs()).sum())
+            .fold(0.0, f64::max)
+    }
+}
+
+impl<T> Add for &CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = CompressedSparseRow<T>;
+
+    fn add(self, other: Self) -> CompressedSparseRow<T> {
+        self.merge_with(other, |a, b| a + b)
+    }
+}
+
+impl<T> Add for CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        &self + &other
+    }
+}
+
+impl<T> Sub for &CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = CompressedSparseRow<T>;
+
+    fn sub(self, other: Self) -> CompressedSparseRow<T> {
+        self.merge_with(other, |a, b| a - b)
+    }
+}
+
+impl<T> Sub for CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        &self - &other
+    }
+}
+
+impl<T> Mul<T> for &CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = CompressedSparseRow<T>;
+
+    fn mul(self, alpha: T) -> CompressedSparseRow<T> {
+        self.scale(alpha)
+    }
+}
+
+impl<T> Mul<T> for CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = Self;
+
+    fn mul(self, alpha: T) -> Self {
+        self.scale(alpha)
+    }
+}
+
+// `alpha * A` needs an impl on the scalar type, which only works for concrete types.
+macro_rules! impl_scalar_mul {
+    ($($t:ty),*) => {
+        $(impl Mul<&CompressedSparseRow<$t>> for $t {
+            type Output = CompressedSparseRow<$t>;
+
+            fn mul(self, matrix: &CompressedSparseRow<$t>) -> CompressedSparseRow<$t> {
+                matrix.scale(self)
+            }
+        }
+
+        impl Mul<CompressedSparseRow<$t>> for $t {
+            type Output = CompressedSparseRow<$t>;
+
+            fn mul(self, matrix: CompressedSparseRow<$t>) -> CompressedSparseRow<$t> {
+                matrix.scale(self)
+            }
+        })*
+    };
+}
+
+impl_scalar_mul!(i32, i64, f32, f64);
+
+impl<T> Neg for &CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Neg<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = CompressedSparseRow<T>;
+
+    fn neg(self) -> CompressedSparseRow<T> {
+        self.map(|val| -val)
+    }
+}
+
+impl<T> Neg for CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Neg<Output = T> + Default + Copy + PartialEq,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        -&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn a() -> CompressedSparseRow<i32> {
+        CompressedSparseRow::new(vec![vec![1, 0, 2, 0], vec![0, 0, 0, 0], vec![0, -3, 4, 5]])
+    }
+
+    fn b() -> CompressedSparseRow<i32> {
+        CompressedSparseRow::new(vec![vec![0, 7, -2, 0], vec![6, 0, 0, 0], vec![0, 3, 1, 0]])
+    }
+
+    fn dense_zip(f: impl Fn(i32, i32) -> i32) -> Vec<Vec<i32>> {
+        a().reconstruct()
+            .iter()
+            .zip(b().reconstruct())
+            .map(|(ra, rb)| ra.iter().zip(rb).map(|(&x, y)| f(x, y)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_add_sub_hadamard_match_dense() {
+        let sum = &a() + &b();
+        assert_eq!(sum.reconstruct(), dense_zip(|x, y| x + y));
+        // 2 + -2 and -3 + 3 cancel and are not stored
+        assert_eq!(sum.nnz(), 5);
+
+        assert_eq!((a() - b()).reconstruct(), dense_zip(|x, y| x - y));
+        assert_eq!((&a() - &a()).nnz(), 0);
+
+        let product = a().hadamard(&b());
+        assert_eq!(product.reconstruct(), dense_zip(|x, y| x * y));
+        assert_eq!(product.column_indices(), &[2, 1, 2]);
+    }
+
+    #[test]
+    fn test_scaling_and_map() {
+        assert_eq!((a() * 3).reconstruct(), (3 * a()).reconstruct());
+        assert_eq!(a().scale(0).nnz(), 0);
+        assert_eq!((-a()).reconstruct(), (a() * -1).reconstruct());
+
+        assert_eq!(
+            a().scale_rows(&[2, 5, -1]).reconstruct(),
+            vec![vec![2, 0, 4, 0], vec![0; 4], vec![0, 3, -4, -5]]
+        );
+        assert_eq!(
+            a().scale_columns(&[1, 10, 0, 2]).reconstruct(),
+            vec![vec![1, 0, 0, 0], vec![0; 4], vec![0, -30, 0, 10]]
+        );
+
+        assert_eq!(a().abs().values(), &[1, 2, 3, 4, 5]);
+        let halves = a().map(|val| val as f64 / 2.0);
+        assert_eq!(halves.get(2, 1), -1.5);
+    }
+
+    #[test]
+    fn test_norms() {
+        let m = a();
+        assert!((m.frobenius_norm() - 55f64.sqrt()).abs() < 1e-12);
+        // column sums 1, 3, 6, 5 and row sums 3, 0, 12
+        assert_eq!(m.norm_1(), 6.0);
+        assert_eq!(m.norm_inf(), 12.0);
+
+        let empty = CompressedSparseRow::<f64>::from_triplets(3, 3, vec![]).unwrap();
+        assert_eq!(empty.norm_1(), 0.0);
+
+        let wide = CompressedSparseRow::<i64>::new(vec![vec![-3_000_000_000, 4_000_000_000]]);
+        assert_eq!(wide.frobenius_norm(), 5e9);
+        assert_eq!(wide.norm_inf(), 7e9);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix shapes differ")]
+    fn test_shape_mismatch_panics() {
+        let _ = &a() + &a().transpose();
+    }
+}
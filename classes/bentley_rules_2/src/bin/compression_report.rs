@@ -0,0 +1,134 @@
+use bentley_rules_2::compressed_sparse_row::CompressedSparseRow;
+use bentley_rules_2::generators::laplacian_2d;
+use bentley_rules_2::graph::{CompressedGraph, Encoding, Graph, PageRankConfig};
+use bentley_rules_2::reorder::{bfs_order, random_shuffle};
+use rand::prelude::*;
+use std::time::Instant;
+
+// Compares plain CSR adjacency against the compressed encodings: memory per edge versus the
+// time of a full neighbour scan, a BFS and a fixed number of PageRank iterations.
+// Compression depends on id locality, so every graph is measured scrambled and BFS-ordered.
+
+const REPS: usize = 5;
+
+// preferential attachment, every new vertex links to `m` endpoints of earlier edges
+fn power_law(n: usize, m: usize, rng: &mut StdRng) -> CompressedSparseRow<f64> {
+    let mut endpoints = vec![0, 1];
+    let mut triplets = vec![(0, 1, 1.0), (1, 0, 1.0)];
+    for v in 2..n {
+        for _ in 0..m {
+            let u = endpoints[rng.gen_range(0..endpoints.len())];
+            triplets.extend([(u, v, 1.0), (v, u, 1.0)]);
+            endpoints.extend([u, v]);
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+fn best_of<F: FnMut()>(mut f: F) -> f64 {
+    (0..REPS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let config = PageRankConfig {
+        tolerance: 0.0,
+        max_iterations: 10,
+        ..PageRankConfig::default()
+    };
+
+    for (name, original) in [
+        ("grid 1000x1000", laplacian_2d(1000, 1000)),
+        ("power law", power_law(1_000_000, 8, &mut rng)),
+    ] {
+        let scrambled = original.permute_symmetric(&random_shuffle(original.rows(), 1));
+        let ordered = scrambled.permute_symmetric(&bfs_order(&scrambled));
+
+        for (order, matrix) in [("scrambled", scrambled), ("bfs order", ordered)] {
+            // symmetric input, so only one direction has to be stored
+            let edges: Vec<_> = (0..matrix.rows())
+                .flat_map(|u| matrix.row(u).0.iter().map(move |&v| (u, v)))
+                .collect();
+            let graph = Graph::undirected(matrix.rows(), &edges);
+            let n = graph.vertex_count();
+            let arcs = graph.arc_count();
+            println!("{}, {} ({} vertices, {} arcs)", name, order, n, arcs);
+            println!(
+                "{:<11} {:>8} {:>10} {:>9} {:>8} {:>9} {:>8} {:>9} {:>8}",
+                "format",
+                "ids b/e",
+                "total b/e",
+                "scan ms",
+                "slower",
+                "bfs ms",
+                "slower",
+                "pr ms",
+                "slower"
+            );
+
+            // ids are the column indices alone, total adds row pointers (offsets + degrees)
+            let word = std::mem::size_of::<usize>();
+            let csr_bytes = (arcs * word, (n + 1 + arcs) * word);
+            let scan = best_of(|| {
+                let sum: usize = (0..n)
+                    .map(|u| graph.neighbors(u).iter().sum::<usize>())
+                    .sum();
+                std::hint::black_box(sum);
+            });
+            let bfs = best_of(|| {
+                std::hint::black_box(graph.bfs(0));
+            });
+            let pagerank = best_of(|| {
+                std::hint::black_box(graph.pagerank_pull(&config));
+            });
+            let print = |label: &str, bytes: (usize, usize), times: (f64, f64, f64)| {
+                println!(
+                    "{:<11} {:>8.2} {:>10.2} {:>9.2} {:>7.2}x {:>9.2} {:>7.2}x {:>9.2} {:>7.2}x",
+                    label,
+                    bytes.0 as f64 / arcs as f64,
+                    bytes.1 as f64 / arcs as f64,
+                    times.0 * 1e3,
+                    times.0 / scan,
+                    times.1 * 1e3,
+                    times.1 / bfs,
+                    times.2 * 1e3,
+                    times.2 / pagerank
+                );
+            };
+            print("csr", csr_bytes, (scan, bfs, pagerank));
+
+            let reference = graph.bfs(0);
+            for (label, encoding) in [
+                ("varint", Encoding::Varint),
+                ("nibble", Encoding::Nibble),
+                ("run-length", Encoding::RunLength),
+            ] {
+                let compressed = CompressedGraph::from_graph(&graph, encoding);
+                assert_eq!(compressed.bfs(0), reference, "{} bfs differs", label);
+
+                let scan = best_of(|| {
+                    let sum: usize = (0..n).map(|u| compressed.neighbors(u).sum::<usize>()).sum();
+                    std::hint::black_box(sum);
+                });
+                let bfs = best_of(|| {
+                    std::hint::black_box(compressed.bfs(0));
+                });
+                let pagerank = best_of(|| {
+                    std::hint::black_box(compressed.pagerank_pull(&config));
+                });
+                print(
+                    label,
+                    (compressed.encoded_bytes(), compressed.bytes()),
+                    (scan, bfs, pagerank),
+                );
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use bentley_rules_2::compressed_sparse_row::{CompressedSparseRow, SparseIndex};
+use rand::prelude::*;
+use std::time::Instant;
+
+// SpMV is memory bound: per non-zero it streams a value and a column index and does two
+// flops. Narrowing the indices shrinks the stream, so the speedup should track the bytes saved.
+
+const ROWS: usize = 2_000_000;
+const PER_ROW: usize = 8;
+const REPS: usize = 20;
+
+fn random_matrix(cols: usize, rng: &mut StdRng) -> CompressedSparseRow<f64> {
+    let triplets = (0..ROWS)
+        .flat_map(|row| (0..PER_ROW).map(move |_| row))
+        .map(|row| (row, rng.gen_range(0..cols), rng.gen_range(-1.0..1.0)))
+        .collect();
+    CompressedSparseRow::from_triplets(ROWS, cols, triplets).unwrap()
+}
+
+fn best_of<F: FnMut()>(mut f: F) -> f64 {
+    (0..REPS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+// (seconds, bytes streamed per SpMV)
+fn measure<I: SparseIndex, P: SparseIndex>(
+    wide: &CompressedSparseRow<f64>,
+    x: &[f64],
+    expected: &[f64],
+) -> (f64, usize) {
+    let a = wide.with_index_types::<I, P>().unwrap();
+    let mut y = vec![0.0; a.rows()];
+    a.spmv(x, &mut y);
+    assert_eq!(y, expected, "narrow indices change the result");
+
+    let seconds = best_of(|| a.spmv(x, &mut y));
+    // x is gathered, count it once as if it stayed in cache
+    let bytes = a.storage_bytes() + std::mem::size_of_val(x) + std::mem::size_of_val(&y[..]);
+    (seconds, bytes)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // a square matrix needs 32-bit columns, a skinny one fits 16 bits
+    for (name, cols) in [("square", ROWS), ("skinny", 60_000)] {
+        let wide = random_matrix(cols, &mut rng);
+        let x: Vec<f64> = (0..cols).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let mut expected = vec![0.0; ROWS];
+        wide.spmv(&x, &mut expected);
+
+        println!("{} ({} x {}, {} nnz)", name, ROWS, cols, wide.nnz());
+        println!(
+            "{:<12} {:>12} {:>10} {:>10} {:>8} {:>9}",
+            "indices", "index B/nnz", "MB moved", "spmv ms", "GB/s", "speedup"
+        );
+
+        let mut variants: Vec<(&str, usize, (f64, usize))> = vec![
+            (
+                "usize/usize",
+                8,
+                measure::<usize, usize>(&wide, &x, &expected),
+            ),
+            ("u32/u64", 4, measure::<u32, u64>(&wide, &x, &expected)),
+            ("u32/u32", 4, measure::<u32, u32>(&wide, &x, &expected)),
+        ];
+        if cols <= u16::MAX as usize + 1 {
+            variants.push(("u16/u32", 2, measure::<u16, u32>(&wide, &x, &expected)));
+        }
+
+        let baseline = variants[0].2 .0;
+        for (label, index_bytes, (seconds, bytes)) in variants {
+            println!(
+                "{:<12} {:>12} {:>10.1} {:>10.3} {:>8.2} {:>8.2}x",
+                label,
+                index_bytes,
+                bytes as f64 / 1e6,
+                seconds * 1e3,
+                bytes as f64 / seconds / 1e9,
+                baseline / seconds
+            );
+        }
+        println!();
+    }
+}
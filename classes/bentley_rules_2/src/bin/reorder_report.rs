@@ -0,0 +1,112 @@
+use bentley_rules_2::compressed_sparse_row::CompressedSparseRow;
+use bentley_rules_2::generators::laplacian_2d;
+use bentley_rules_2::graph::Graph;
+use bentley_rules_2::reorder::{
+    bandwidth, bfs_order, degree_sort, gorder_lite, profile, random_shuffle, reverse_cuthill_mckee,
+    Permutation,
+};
+use rand::prelude::*;
+use std::time::Instant;
+
+// Applies every ordering to a mesh-like and a power-law matrix whose ids were scrambled,
+// then reports bandwidth/profile and how much faster SpMV and BFS run afterwards.
+
+const REPS: usize = 10;
+
+type Ordering = fn(&CompressedSparseRow<f64>) -> Permutation;
+
+// preferential attachment, every new vertex links to `m` endpoints of earlier edges
+fn power_law(n: usize, m: usize, rng: &mut StdRng) -> CompressedSparseRow<f64> {
+    let mut endpoints = vec![0, 1];
+    let mut triplets = vec![(0, 1, 1.0), (1, 0, 1.0)];
+    for v in 2..n {
+        for _ in 0..m {
+            let u = endpoints[rng.gen_range(0..endpoints.len())];
+            triplets.extend([(u, v, 1.0), (v, u, 1.0)]);
+            endpoints.extend([u, v]);
+        }
+    }
+    // parallel edges merge into larger values, that's fine here
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+fn best_of<F: FnMut()>(mut f: F) -> f64 {
+    (0..REPS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let inputs = [
+        ("grid 700x700", laplacian_2d(700, 700)),
+        ("power law", power_law(500_000, 4, &mut rng)),
+    ];
+
+    for (name, original) in inputs {
+        // scramble the natural ordering first, the orderings have to recover it
+        let scrambled = original.permute_symmetric(&random_shuffle(original.rows(), 1));
+        let n = scrambled.rows();
+        let x: Vec<f64> = (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect();
+
+        println!("{} ({} rows, {} nnz)", name, n, scrambled.nnz());
+        println!(
+            "{:<14} {:>10} {:>10} {:>14} {:>10} {:>8} {:>10} {:>8}",
+            "ordering",
+            "order ms",
+            "bandwidth",
+            "profile",
+            "spmv ms",
+            "speedup",
+            "bfs ms",
+            "speedup"
+        );
+
+        let orderings: [(&str, Ordering); 6] = [
+            ("scrambled", |a| Permutation::identity(a.rows())),
+            ("random", |a| random_shuffle(a.rows(), 2)),
+            ("degree sort", |a| degree_sort(a)),
+            ("bfs", |a| bfs_order(a)),
+            ("rcm", |a| reverse_cuthill_mckee(a)),
+            ("gorder-lite", |a| gorder_lite(a, 8)),
+        ];
+
+        let mut baseline = (0.0, 0.0);
+        for (label, ordering) in orderings {
+            let start = Instant::now();
+            let permutation = ordering(&scrambled);
+            let order_seconds = start.elapsed().as_secs_f64();
+
+            let a = scrambled.permute_symmetric(&permutation);
+            let px = permutation.apply(&x);
+            let mut y = vec![0.0; n];
+            let spmv = best_of(|| a.spmv(&px, &mut y));
+
+            let graph = Graph::from_csr(&a);
+            let source = permutation.old_to_new()[0];
+            let bfs = best_of(|| {
+                std::hint::black_box(graph.bfs(source));
+            });
+
+            if label == "scrambled" {
+                baseline = (spmv, bfs);
+            }
+            println!(
+                "{:<14} {:>10.1} {:>10} {:>14} {:>10.3} {:>7.2}x {:>10.3} {:>7.2}x",
+                label,
+                order_seconds * 1e3,
+                bandwidth(&a),
+                profile(&a),
+                spmv * 1e3,
+                baseline.0 / spmv,
+                bfs * 1e3,
+                baseline.1 / bfs
+            );
+        }
+        println!();
+    }
+}
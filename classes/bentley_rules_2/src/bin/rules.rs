@@ -0,0 +1,36 @@
+use bentley_rules_2::rules::catalog;
+
+// Runs every rule of the catalog at its benchmark size and prints the speedup of the
+// optimized version. An argument keeps only the rules whose name contains it:
+//
+//   cargo run --release --bin rules
+//   cargo run --release --bin rules -- loop
+
+const SEED: u64 = 42;
+const REPS: usize = 5;
+
+fn main() {
+    let filter = std::env::args().nth(1).unwrap_or_default();
+
+    println!(
+        "{:<22} {:<15} {:>11} {:>11} {:>8}  what changed",
+        "rule", "category", "naive ms", "opt ms", "speedup"
+    );
+    for rule in catalog().iter().filter(|rule| rule.name.contains(&filter)) {
+        assert!(
+            (rule.agree)(rule.size, SEED),
+            "{}: optimized result differs",
+            rule.name
+        );
+        let (naive, optimized) = (rule.time)(rule.size, SEED, REPS);
+        println!(
+            "{:<22} {:<15} {:>11.3} {:>11.3} {:>7.2}x  {}",
+            rule.name,
+            format!("{:?}", rule.category),
+            naive * 1e3,
+            optimized * 1e3,
+            naive / optimized,
+            rule.summary
+        );
+    }
+}
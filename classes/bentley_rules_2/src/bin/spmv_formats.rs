@@ -0,0 +1,138 @@
+use bentley_rules_2::block_csr::BlockCsr;
+use bentley_rules_2::compressed_sparse_row::CompressedSparseRow;
+use bentley_rules_2::ellpack::Ellpack;
+use bentley_rules_2::generators::{banded, erdos_renyi};
+use bentley_rules_2::sliced_ell::SlicedEll;
+use rand::prelude::*;
+use std::time::Instant;
+use utils::aligned::{page_backing, AlignedVec, Pages};
+
+// Runs SpMV in every storage format on a handful of matrix shapes and reports the winner.
+// There is no universally best format: ELLPACK wins on uniform rows, SELL-C-σ on skewed
+// rows, BSR when non-zeros come in dense blocks.
+//
+// With --huge-pages the x and y vectors sit on 2 MB pages. The matrix arrays are streamed
+// front to back, but every non-zero gathers from a different spot of x, and on 4 KB
+// pages most of those gathers need a page walk.
+
+const ROWS: usize = 200_000;
+const REPS: usize = 20;
+
+type Kernel<'a> = Box<dyn Fn(&[f64], &mut [f64]) + 'a>;
+
+// a few very long rows, the rest short, like the degree distribution of a social graph
+fn power_law(n: usize, rng: &mut StdRng) -> CompressedSparseRow<f64> {
+    let mut triplets = Vec::new();
+    for row in 0..n {
+        let len = ((n as f64 / (row + 1) as f64).sqrt() as usize).clamp(1, 2_000);
+        for _ in 0..len {
+            triplets.push((row, rng.gen_range(0..n), rng.gen_range(-1.0..1.0)));
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+// dense 4x4 blocks along a band, like a FEM matrix with 4 unknowns per node
+fn blocked(n: usize, rng: &mut StdRng) -> CompressedSparseRow<f64> {
+    let nodes = n / 4;
+    let mut triplets = Vec::new();
+    for node in 0..nodes {
+        for neighbour in [node.saturating_sub(1), node, (node + 1).min(nodes - 1)] {
+            for r in 0..4 {
+                for c in 0..4 {
+                    triplets.push((node * 4 + r, neighbour * 4 + c, rng.gen_range(-1.0..1.0)));
+                }
+            }
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+fn best_of(kernel: &Kernel, x: &[f64], y: &mut [f64]) -> f64 {
+    (0..REPS)
+        .map(|_| {
+            let start = Instant::now();
+            kernel(x, y);
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn main() {
+    let pages = if std::env::args().any(|arg| arg == "--huge-pages") {
+        Pages::Huge
+    } else {
+        Pages::Base
+    };
+    let mut rng = StdRng::seed_from_u64(42);
+    let matrices = [
+        ("banded", banded(ROWS, 4, 1.0, 1)),
+        ("uniform", erdos_renyi(ROWS, ROWS, 8.0 / ROWS as f64, 2)),
+        ("power law", power_law(ROWS, &mut rng)),
+        ("4x4 blocks", blocked(ROWS, &mut rng)),
+    ];
+
+    println!(
+        "{:<12} {:>10} {:<10} {:>10} {:>8} {:>8}",
+        "matrix", "nnz", "format", "ms", "GFLOP/s", "fill"
+    );
+
+    for (name, csr) in &matrices {
+        let ell = Ellpack::from_csr(csr);
+        let sell = SlicedEll::from_csr(csr, 8, 256);
+        let bsr = BlockCsr::<f64, 4, 4>::from_csr(csr);
+
+        let kernels: Vec<(&str, usize, Kernel)> = vec![
+            ("csr", csr.nnz(), Box::new(|x, y| csr.spmv(x, y))),
+            ("ell", ell.stored(), Box::new(|x, y| ell.spmv(x, y))),
+            (
+                "sell-8-256",
+                sell.stored(),
+                Box::new(|x, y| sell.spmv(x, y)),
+            ),
+            ("bsr-4x4", bsr.stored(), Box::new(|x, y| bsr.spmv(x, y))),
+        ];
+
+        let mut x = AlignedVec::with_pages(0.0, csr.cols(), pages);
+        x.fill_with(|| rng.gen_range(-1.0..1.0));
+        if let Ok(backing) = page_backing(&x) {
+            println!("x on {}", backing);
+        }
+        let mut expected = vec![0.0; csr.rows()];
+        csr.spmv(&x, &mut expected);
+
+        let mut best = ("", f64::INFINITY);
+        for (format, stored, kernel) in &kernels {
+            let mut y = AlignedVec::with_pages(0.0, csr.rows(), pages);
+            let seconds = best_of(kernel, &x, &mut y);
+
+            // a fast wrong answer doesn't count
+            let max_error = y
+                .iter()
+                .zip(&expected)
+                .map(|(a, b)| (a - b).abs() / b.abs().max(1.0))
+                .fold(0.0, f64::max);
+            assert!(
+                max_error < 1e-12,
+                "{} gave a wrong result on {}",
+                format,
+                name
+            );
+
+            println!(
+                "{:<12} {:>10} {:<10} {:>10.3} {:>8.2} {:>8.2}",
+                name,
+                csr.nnz(),
+                format,
+                seconds * 1e3,
+                2.0 * csr.nnz() as f64 / seconds / 1e9,
+                *stored as f64 / csr.nnz() as f64
+            );
+
+            if seconds < best.1 {
+                best = (format, seconds);
+            }
+        }
+        println!("best format for {}: {}\n", name, best.0);
+    }
+}
@@ -1,4 +1,6 @@
+use std::fmt;
 use std::ops::{Add, Mul};
+use utils::DenseMatrix;
 
 /// CompressedSparseRow (CSR) speeds up large scientific computations by omitting zeroes
 /// By Bentley's principle: "The fastest way to compute is to not compute at all"
@@ -9,29 +11,147 @@ use std::ops::{Add, Mul};
 ///
 /// Used in large sparse matrices where most elements are zero
 /// Common in: Scientific computing, graph algorithms, ML feature matrices
-pub struct CompressedSparseRow<T>
+///
+/// `I` is the column index type and `P` the row pointer type. SpMV streams one value and
+/// one column index per non-zero, so for f64 values narrowing indices from usize to u32
+/// cuts the traffic per non-zero from 16 to 12 bytes. Row pointers have to count up to
+/// nnz and columns only up to cols, hence the separate widths: `CompressedSparseRow<f64, u32, u64>`
+/// handles billions of non-zeros with narrow columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedSparseRow<T, I = usize, P = usize>
 where
     T: Add<Output = T> + Mul<Output = T> + Default + Copy,
 {
-    values: Vec<T>,             // Non-zero values
-    column_indices: Vec<usize>, // Column indices for non-zero elements
-    row_pointers: Vec<usize>,   // Pointers to start of each row in values
-    original_cols: usize,       // Store original matrix dimensions
+    values: Vec<T>,         // Non-zero values
+    column_indices: Vec<I>, // Column indices for non-zero elements
+    row_pointers: Vec<P>,   // Pointers to start of each row in values
+    original_cols: usize,   // Store original matrix dimensions
+}
+
+/// Integer type usable as a CSR column index or row pointer.
+pub trait SparseIndex: Copy + Ord + Default + fmt::Debug + Send + Sync + 'static {
+    /// Largest value the type holds, saturated to usize.
+    const MAX: usize;
+
+    /// None when `value` doesn't fit.
+    fn from_usize(value: usize) -> Option<Self>;
+
+    fn to_usize(self) -> usize;
 }
 
+macro_rules! impl_sparse_index {
+    ($($t:ty),*) => {
+        $(impl SparseIndex for $t {
+            // u64::MAX truncates to usize::MAX on 32-bit targets, which is the saturation we want
+            const MAX: usize = <$t>::MAX as usize;
+
+            #[inline(always)]
+            fn from_usize(value: usize) -> Option<Self> {
+                <$t>::try_from(value).ok()
+            }
+
+            #[inline(always)]
+            fn to_usize(self) -> usize {
+                self as usize
+            }
+        })*
+    };
+}
+
+impl_sparse_index!(u16, u32, u64, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsrError {
+    EmptyRowPointers,
+    RowPointersNotMonotonic {
+        row: usize,
+    },
+    LengthMismatch {
+        nnz: usize,
+        values: usize,
+        column_indices: usize,
+    },
+    ColumnOutOfBounds {
+        index: usize,
+        column: usize,
+        cols: usize,
+    },
+    RowOutOfBounds {
+        row: usize,
+        rows: usize,
+    },
+    ColumnsNotIncreasing {
+        row: usize,
+    },
+    IndexOverflow {
+        value: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for CsrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsrError::EmptyRowPointers => write!(f, "row pointers must contain at least one entry"),
+            CsrError::RowPointersNotMonotonic { row } => {
+                write!(f, "row pointers decrease at row {}", row)
+            }
+            CsrError::LengthMismatch {
+                nnz,
+                values,
+                column_indices,
+            } => write!(
+                f,
+                "row pointers describe {} non-zeros but got {} values and {} column indices",
+                nnz, values, column_indices
+            ),
+            CsrError::ColumnOutOfBounds {
+                index,
+                column,
+                cols,
+            } => write!(
+                f,
+                "column index {} at position {} is out of bounds for {} columns",
+                column, index, cols
+            ),
+            CsrError::RowOutOfBounds { row, rows } => {
+                write!(f, "row {} is out of bounds for {} rows", row, rows)
+            }
+            CsrError::ColumnsNotIncreasing { row } => {
+                write!(f, "columns of row {} are not strictly increasing", row)
+            }
+            CsrError::IndexOverflow { value, max } => write!(
+                f,
+                "{} does not fit in an index type with maximum {}",
+                value, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CsrError {}
+
+// Constructors build usize-indexed matrices (default type parameters don't drive inference,
+// so generic constructors would need annotations at every call), `with_index_types` narrows.
 impl<T> CompressedSparseRow<T>
 where
     T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
 {
+    /// Compresses a matrix given as rows, panicking on ragged rows: a row longer than the
+    /// first would store column indices past `cols`, which `spmv` trusts.
     pub fn new(matrix: Vec<Vec<T>>) -> Self {
-        let cols = matrix[0].len();
+        Self::from_dense(&DenseMatrix::from(matrix))
+    }
+
+    /// Compresses a dense matrix of any layout, dropping its zeroes.
+    pub fn from_dense(matrix: &DenseMatrix<T>) -> Self {
         let mut values = Vec::new();
         let mut column_indices = Vec::new();
-        let mut row_pointers = vec![0];
+        let mut row_pointers = Vec::with_capacity(matrix.rows() + 1);
+        row_pointers.push(0);
 
-        // Convert matrix to CSR format
-        for row in matrix {
-            for (col, &val) in row.iter().enumerate() {
+        for row in matrix.iter_rows() {
+            for (col, &val) in row.enumerate() {
                 if val != T::default() {
                     values.push(val);
                     column_indices.push(col);
@@ -40,6 +160,97 @@ where
             row_pointers.push(values.len());
         }
 
+        Self {
+            values,
+            column_indices,
+            row_pointers,
+            original_cols: matrix.cols(),
+        }
+    }
+
+    /// Builds a CSR from already compressed arrays, validating them first so the kernels
+    /// can index without bounds checks.
+    pub fn from_raw_parts(
+        cols: usize,
+        row_pointers: Vec<usize>,
+        column_indices: Vec<usize>,
+        values: Vec<T>,
+    ) -> Result<Self, CsrError> {
+        validate(cols, &row_pointers, &column_indices, values.len())?;
+
+        Ok(Self {
+            values,
+            column_indices,
+            row_pointers,
+            original_cols: cols,
+        })
+    }
+
+    /// Builds a CSR from `(row, col, value)` entries in any order.
+    /// Duplicate coordinates are summed and explicit zeroes are dropped.
+    pub fn from_triplets(
+        rows: usize,
+        cols: usize,
+        mut triplets: Vec<(usize, usize, T)>,
+    ) -> Result<Self, CsrError> {
+        if let Some(&(row, _, _)) = triplets.iter().find(|(row, _, _)| *row >= rows) {
+            return Err(CsrError::RowOutOfBounds { row, rows });
+        }
+        if let Some((index, &(_, column, _))) = triplets
+            .iter()
+            .enumerate()
+            .find(|(_, (_, column, _))| *column >= cols)
+        {
+            return Err(CsrError::ColumnOutOfBounds {
+                index,
+                column,
+                cols,
+            });
+        }
+
+        triplets.sort_unstable_by_key(|&(row, col, _)| (row, col));
+
+        let mut merged: Vec<(usize, usize, T)> = Vec::with_capacity(triplets.len());
+        for (row, col, val) in triplets {
+            match merged.last_mut() {
+                Some(last) if last.0 == row && last.1 == col => last.2 = last.2 + val,
+                _ => merged.push((row, col, val)),
+            }
+        }
+        merged.retain(|&(_, _, val)| val != T::default());
+
+        let mut row_pointers = vec![0; rows + 1];
+        for &(row, _, _) in &merged {
+            row_pointers[row + 1] += 1;
+        }
+        for row in 0..rows {
+            row_pointers[row + 1] += row_pointers[row];
+        }
+
+        let (column_indices, values) = merged.into_iter().map(|(_, col, val)| (col, val)).unzip();
+
+        Ok(Self {
+            values,
+            column_indices,
+            row_pointers,
+            original_cols: cols,
+        })
+    }
+}
+
+impl<T, I, P> CompressedSparseRow<T, I, P>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+    I: SparseIndex,
+    P: SparseIndex,
+{
+    /// Arrays that already passed `validate`.
+    pub(crate) fn from_validated_parts(
+        cols: usize,
+        row_pointers: Vec<P>,
+        column_indices: Vec<I>,
+        values: Vec<T>,
+    ) -> Self {
         Self {
             values,
             column_indices,
@@ -48,17 +259,34 @@ where
         }
     }
 
+    /// Copies the matrix into other index types, failing if a column index or row pointer
+    /// doesn't fit: `csr.with_index_types::<u32, u64>()`.
+    pub fn with_index_types<I2, P2>(&self) -> Result<CompressedSparseRow<T, I2, P2>, CsrError>
+    where
+        I2: SparseIndex,
+        P2: SparseIndex,
+    {
+        Ok(CompressedSparseRow {
+            values: self.values.clone(),
+            column_indices: convert_indices(&self.column_indices)?,
+            row_pointers: convert_indices(&self.row_pointers)?,
+            original_cols: self.original_cols,
+        })
+    }
+
     pub fn reconstruct(&self) -> Vec<Vec<T>> {
-        let rows = self.row_pointers.len() - 1;
-        let mut result = vec![vec![T::default(); self.original_cols]; rows];
+        self.to_dense().to_vecs()
+    }
 
-        for row in 0..rows {
-            let start = self.row_pointers[row];
-            let end = self.row_pointers[row + 1];
+    /// Expands into a row-major dense matrix.
+    pub fn to_dense(&self) -> DenseMatrix<T> {
+        let mut result = DenseMatrix::new(self.rows(), self.original_cols);
 
-            for idx in start..end {
-                let col = self.column_indices[idx];
-                result[row][col] = self.values[idx];
+        for row in 0..self.rows() {
+            let dense = result.row_slice_mut(row);
+            let (cols, vals) = self.row(row);
+            for (&col, &val) in cols.iter().zip(vals) {
+                dense[col.to_usize()] = val;
             }
         }
 
@@ -66,16 +294,181 @@ where
     }
 
     pub fn get(&self, row: usize, col: usize) -> T {
-        let start = self.row_pointers[row];
-        let end = self.row_pointers[row + 1];
-
-        for idx in start..end {
-            if self.column_indices[idx] == col {
-                return self.values[idx];
+        let (cols, vals) = self.row(row);
+        for (&c, &val) in cols.iter().zip(vals) {
+            if c.to_usize() == col {
+                return val;
             }
         }
         T::default()
     }
+
+    pub fn rows(&self) -> usize {
+        self.row_pointers.len() - 1
+    }
+
+    pub fn cols(&self) -> usize {
+        self.original_cols
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn column_indices(&self) -> &[I] {
+        &self.column_indices
+    }
+
+    pub fn row_pointers(&self) -> &[P] {
+        &self.row_pointers
+    }
+
+    /// Bytes held by values, column indices and row pointers.
+    pub fn storage_bytes(&self) -> usize {
+        std::mem::size_of_val(&self.values[..])
+            + std::mem::size_of_val(&self.column_indices[..])
+            + std::mem::size_of_val(&self.row_pointers[..])
+    }
+
+    pub fn row_len(&self, row: usize) -> usize {
+        self.row_pointers[row + 1].to_usize() - self.row_pointers[row].to_usize()
+    }
+
+    /// Column indices and values of the non-zeros in `row`.
+    #[inline(always)]
+    pub fn row(&self, row: usize) -> (&[I], &[T]) {
+        let range = self.row_pointers[row].to_usize()..self.row_pointers[row + 1].to_usize();
+        (&self.column_indices[range.clone()], &self.values[range])
+    }
+
+    /// Aᵀ, built with a counting sort on the column indices so every row comes out sorted.
+    ///
+    /// Panics if the row count doesn't fit in the column index type.
+    pub fn transpose(&self) -> Self {
+        let row_index = |row| I::from_usize(row).expect("row count does not fit the index type");
+        // nnz already fits P, so every prefix sum does too
+        let mut row_pointers = vec![0; self.cols() + 1];
+        for &col in &self.column_indices {
+            row_pointers[col.to_usize() + 1] += 1;
+        }
+        for col in 0..self.cols() {
+            row_pointers[col + 1] += row_pointers[col];
+        }
+
+        let mut next = row_pointers.clone();
+        let mut column_indices = vec![I::default(); self.nnz()];
+        let mut values = vec![T::default(); self.nnz()];
+        for row in 0..self.rows() {
+            let (cols, vals) = self.row(row);
+            for (&col, &val) in cols.iter().zip(vals) {
+                let slot = &mut next[col.to_usize()];
+                column_indices[*slot] = row_index(row);
+                values[*slot] = val;
+                *slot += 1;
+            }
+        }
+
+        Self {
+            values,
+            column_indices,
+            row_pointers: convert_indices(&row_pointers).unwrap(),
+            original_cols: self.rows(),
+        }
+    }
+
+    /// y = A·x
+    ///
+    /// Each row is a dot product of irregular length, which is exactly what keeps
+    /// this kernel from vectorizing well. See `Ellpack`, `SlicedEll` and `BlockCsr`.
+    pub fn spmv(&self, x: &[T], y: &mut [T]) {
+        assert_eq!(x.len(), self.cols(), "x must have one entry per column");
+        assert_eq!(y.len(), self.rows(), "y must have one entry per row");
+
+        for (row, out) in y.iter_mut().enumerate() {
+            let (cols, vals) = self.row(row);
+            let mut sum = T::default();
+            for (&col, &val) in cols.iter().zip(vals) {
+                // every constructor bounds column indices by `cols`: `from_dense` by
+                // construction (`new` rejects ragged rows), the rest through `validate`
+                sum = sum + val * unsafe { *x.get_unchecked(col.to_usize()) };
+            }
+            *out = sum;
+        }
+    }
+}
+
+/// Checks that raw CSR arrays describe a valid matrix: row pointers start at 0, never
+/// decrease and end at nnz, every column index is below `cols`, and the columns of each
+/// row strictly increase. The merges in `algebra`, `get` and IC(0) rely on the last one,
+/// unsorted or duplicate columns would give them wrong answers rather than errors.
+pub(crate) fn validate<I: SparseIndex, P: SparseIndex>(
+    cols: usize,
+    row_pointers: &[P],
+    column_indices: &[I],
+    values: usize,
+) -> Result<(), CsrError> {
+    let (&first, rest) = row_pointers
+        .split_first()
+        .ok_or(CsrError::EmptyRowPointers)?;
+    if first.to_usize() != 0 {
+        return Err(CsrError::RowPointersNotMonotonic { row: 0 });
+    }
+
+    let mut prev = first;
+    for (row, &ptr) in rest.iter().enumerate() {
+        if ptr < prev {
+            return Err(CsrError::RowPointersNotMonotonic { row });
+        }
+        prev = ptr;
+    }
+
+    let nnz = prev.to_usize();
+    if nnz != values || nnz != column_indices.len() {
+        return Err(CsrError::LengthMismatch {
+            nnz,
+            values,
+            column_indices: column_indices.len(),
+        });
+    }
+
+    if let Some((index, &column)) = column_indices
+        .iter()
+        .enumerate()
+        .find(|(_, &column)| column.to_usize() >= cols)
+    {
+        return Err(CsrError::ColumnOutOfBounds {
+            index,
+            column: column.to_usize(),
+            cols,
+        });
+    }
+
+    for (row, bounds) in row_pointers.windows(2).enumerate() {
+        let columns = &column_indices[bounds[0].to_usize()..bounds[1].to_usize()];
+        if columns.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(CsrError::ColumnsNotIncreasing { row });
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_indices<From: SparseIndex, To: SparseIndex>(
+    indices: &[From],
+) -> Result<Vec<To>, CsrError> {
+    indices
+        .iter()
+        .map(|&index| {
+            To::from_usize(index.to_usize()).ok_or(CsrError::IndexOverflow {
+                value: index.to_usize(),
+                max: To::MAX,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -105,4 +498,130 @@ mod tests {
         assert_eq!(csr.get(4, 3), 1);
         assert_eq!(csr.get(0, 0), 0);
     }
+
+    #[test]
+    #[should_panic(expected = "row 1 has 4 columns, expected 2")]
+    fn test_new_rejects_ragged_rows() {
+        CompressedSparseRow::new(vec![vec![1, 0], vec![0, 0, 0, 2]]);
+    }
+
+    #[test]
+    fn test_from_triplets_and_spmv() {
+        let triplets = vec![
+            (1, 1, 8),
+            (4, 3, 1),
+            (1, 0, 5),
+            (2, 2, 3),
+            (3, 1, 6),
+            (4, 1, 2),
+        ];
+        let csr = CompressedSparseRow::from_triplets(5, 4, triplets).unwrap();
+        assert_eq!(csr.rows(), 5);
+        assert_eq!(csr.cols(), 4);
+        assert_eq!(csr.nnz(), 6);
+        assert_eq!(csr.get(1, 0), 5);
+
+        let mut y = vec![0; 5];
+        csr.spmv(&[1, 2, 3, 4], &mut y);
+        assert_eq!(y, vec![0, 21, 9, 12, 8]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let matrix = vec![vec![1, 0, 2], vec![0, 0, 3], vec![4, 5, 0], vec![0, 6, 0]];
+        let transposed = CompressedSparseRow::new(matrix).transpose();
+        assert_eq!(
+            transposed.reconstruct(),
+            vec![vec![1, 0, 4, 0], vec![0, 0, 5, 6], vec![2, 3, 0, 0]]
+        );
+        assert_eq!(transposed.column_indices(), &[0, 2, 2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_from_triplets_merges_duplicates() {
+        let triplets = vec![(0, 0, 2), (0, 0, 3), (1, 1, 4), (1, 1, -4)];
+        let csr = CompressedSparseRow::from_triplets(2, 2, triplets).unwrap();
+        assert_eq!(csr.nnz(), 1);
+        assert_eq!(csr.get(0, 0), 5);
+        assert_eq!(csr.row_pointers(), &[0, 1, 1]);
+    }
+
+    #[test]
+    fn test_index_types() {
+        let triplets = (0..300)
+            .map(|i| (i % 70, (i * 37) % 1000, i as i64 + 1))
+            .collect();
+        let wide = CompressedSparseRow::from_triplets(70, 1000, triplets).unwrap();
+        let x: Vec<i64> = (0..1000).map(|i| i % 13 - 6).collect();
+        let mut expected = vec![0; 70];
+        wide.spmv(&x, &mut expected);
+
+        let mixed = wide.with_index_types::<u32, u64>().unwrap();
+        let narrow = wide.with_index_types::<u16, u16>().unwrap();
+        assert!(narrow.storage_bytes() < mixed.storage_bytes());
+        assert!(mixed.storage_bytes() < wide.storage_bytes());
+        for (actual, expected) in [
+            (
+                mixed.reconstruct(),
+                mixed.transpose().transpose().reconstruct(),
+            ),
+            (narrow.reconstruct(), wide.reconstruct()),
+        ] {
+            assert_eq!(actual, expected);
+        }
+
+        let mut y = vec![0; 70];
+        mixed.spmv(&x, &mut y);
+        assert_eq!(y, expected);
+        narrow.spmv(&x, &mut y);
+        assert_eq!(y, expected);
+        assert_eq!(narrow.with_index_types::<usize, usize>().unwrap(), wide);
+
+        // u16 holds the columns of a 70_000-wide matrix only up to 65_535
+        let too_wide = CompressedSparseRow::from_triplets(1, 70_000, vec![(0, 69_999, 1)]).unwrap();
+        assert_eq!(
+            too_wide.with_index_types::<u16, usize>(),
+            Err(CsrError::IndexOverflow {
+                value: 69_999,
+                max: 65_535
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_raw_parts_validates() {
+        assert_eq!(
+            CompressedSparseRow::<i32>::from_raw_parts(2, vec![0, 1], vec![2], vec![1]),
+            Err(CsrError::ColumnOutOfBounds {
+                index: 0,
+                column: 2,
+                cols: 2
+            })
+        );
+        assert_eq!(
+            CompressedSparseRow::<i32>::from_raw_parts(2, vec![0, 2, 1], vec![0, 1], vec![1, 1]),
+            Err(CsrError::RowPointersNotMonotonic { row: 1 })
+        );
+        assert!(
+            CompressedSparseRow::from_raw_parts(2, vec![0, 1, 2], vec![1, 0], vec![1, 1]).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_from_raw_parts_rejects_unsorted_columns() {
+        // unsorted and duplicate columns made merge_with store column 0 twice and hadamard
+        // miss a match
+        assert_eq!(
+            CompressedSparseRow::<i32>::from_raw_parts(3, vec![0, 3], vec![2, 0, 0], vec![1; 3]),
+            Err(CsrError::ColumnsNotIncreasing { row: 0 })
+        );
+        assert_eq!(
+            CompressedSparseRow::<i32>::from_raw_parts(3, vec![0, 1, 3], vec![0, 2, 1], vec![1; 3]),
+            Err(CsrError::ColumnsNotIncreasing { row: 1 })
+        );
+        assert_eq!(
+            CompressedSparseRow::<i32>::from_raw_parts(3, vec![0, 2], vec![1, 1], vec![1; 2]),
+            Err(CsrError::ColumnsNotIncreasing { row: 0 })
+        );
+    }
 }
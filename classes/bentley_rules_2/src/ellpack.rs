@@ -0,0 +1,183 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use std::ops::{Add, Mul};
+
+/// ELLPACK pads every row to the length of the longest one so that all rows can be
+/// processed in lockstep.
+///
+/// Entries are stored column-major (`slot * rows + row`), so the SpMV inner loop walks
+/// `values` and `column_indices` contiguously across rows and auto-vectorizes,
+/// with a gather for `x`.
+///
+/// Padding still gathers `x[0]`, but the row lengths select the padded terms away instead
+/// of adding `0 * x[0]`, which is NaN whenever `x[0]` is infinite or NaN.
+///
+/// Space-Time Tradeoff:
+/// - Time: regular loops, no per-row trip count
+/// - Space: `rows * width` entries, a single long row blows it up (see `SlicedEll`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ellpack<T> {
+    rows: usize,
+    cols: usize,
+    width: usize,               // Length of the longest row
+    values: Vec<T>,             // Column-major, padded with T::default()
+    column_indices: Vec<usize>, // Padding points at column 0
+    row_lens: Vec<u32>,         // Slots before the padding of each row
+}
+
+impl<T> Ellpack<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    pub fn from_csr(csr: &CompressedSparseRow<T>) -> Self {
+        let rows = csr.rows();
+        let width = (0..rows).map(|row| csr.row_len(row)).max().unwrap_or(0);
+
+        let mut values = vec![T::default(); rows * width];
+        let mut column_indices = vec![0; rows * width];
+        let row_lens = (0..rows).map(|row| row_len_u32(csr.row_len(row))).collect();
+
+        for row in 0..rows {
+            let (cols, vals) = csr.row(row);
+            for (slot, (&col, &val)) in cols.iter().zip(vals).enumerate() {
+                values[slot * rows + row] = val;
+                column_indices[slot * rows + row] = col;
+            }
+        }
+
+        Self {
+            rows,
+            cols: csr.cols(),
+            width,
+            values,
+            column_indices,
+            row_lens,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of stored entries including padding.
+    pub fn stored(&self) -> usize {
+        self.values.len()
+    }
+
+    /// y = A·x
+    pub fn spmv(&self, x: &[T], y: &mut [T]) {
+        assert_eq!(x.len(), self.cols, "x must have one entry per column");
+        assert_eq!(y.len(), self.rows, "y must have one entry per row");
+
+        y.fill(T::default());
+        if self.rows == 0 {
+            return;
+        }
+
+        // one pass over y per slot, every pass has the same trip count
+        for (slot, (vals, cols)) in self
+            .values
+            .chunks_exact(self.rows)
+            .zip(self.column_indices.chunks_exact(self.rows))
+            .enumerate()
+        {
+            let slot = slot as u32;
+            for (((out, &val), &col), &len) in y.iter_mut().zip(vals).zip(cols).zip(&self.row_lens)
+            {
+                *out = padded_term(*out, val, col, x, slot < len);
+            }
+        }
+    }
+}
+
+/// Row lengths are kept as u32 to keep the extra stream in the SpMV loop narrow.
+pub(crate) fn row_len_u32(len: usize) -> u32 {
+    u32::try_from(len).expect("row longer than u32::MAX entries")
+}
+
+/// `sum + val * x[col]` for a slot inside its row, `sum` for padding. A select rather
+/// than a branch, so the loops over slots still vectorize.
+#[inline(always)]
+pub(crate) fn padded_term<T>(sum: T, val: T, col: usize, x: &[T], in_row: bool) -> T
+where
+    T: Add<Output = T> + Mul<Output = T> + Copy,
+{
+    // column indices come from a validated CSR, padding uses column 0
+    let next = sum + val * unsafe { *x.get_unchecked(col) };
+    if in_row {
+        next
+    } else {
+        sum
+    }
+}
+
+impl<T> From<&CompressedSparseRow<T>> for Ellpack<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    fn from(csr: &CompressedSparseRow<T>) -> Self {
+        Self::from_csr(csr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ellpack_spmv_matches_csr() {
+        let matrix = vec![
+            vec![0, 0, 0, 0],
+            vec![5, 8, 0, 0],
+            vec![0, 0, 3, 0],
+            vec![0, 6, 0, 0],
+            vec![7, 2, 0, 1],
+        ];
+        let csr = CompressedSparseRow::new(matrix);
+        let ell = Ellpack::from(&csr);
+        assert_eq!(ell.width(), 3);
+        assert_eq!(ell.stored(), 15);
+
+        let x = [1, 2, 3, 4];
+        let mut expected = vec![0; 5];
+        let mut actual = vec![-1; 5];
+        csr.spmv(&x, &mut expected);
+        ell.spmv(&x, &mut actual);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_ellpack_empty_rows() {
+        let csr = CompressedSparseRow::<f64>::from_triplets(3, 3, vec![]).unwrap();
+        let ell = Ellpack::from(&csr);
+        let mut y = vec![1.0; 3];
+        ell.spmv(&[1.0; 3], &mut y);
+        assert_eq!(y, vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_padding_ignores_non_finite_x() {
+        // only row 2 has column 0, every other row is padded with it
+        let matrix = vec![
+            vec![0.0, 1.0, 2.0],
+            vec![0.0, 0.0, 3.0],
+            vec![4.0, 5.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ];
+        let csr = CompressedSparseRow::new(matrix);
+        let x = [f64::INFINITY, 1.0, 2.0];
+        let mut expected = vec![0.0; 4];
+        let mut actual = vec![0.0; 4];
+        csr.spmv(&x, &mut expected);
+        Ellpack::from(&csr).spmv(&x, &mut actual);
+        assert_eq!(expected, vec![5.0, 6.0, f64::INFINITY, 0.0]);
+        assert_eq!(actual, expected);
+    }
+}
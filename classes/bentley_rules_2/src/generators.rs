@@ -0,0 +1,310 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use rand::prelude::*;
+
+// Seeded synthetic inputs. Every generator takes a seed and returns the same matrix for the
+// same arguments, so benchmarks are reproducible and comparable across machines.
+//
+// Random values are uniform in [-1, 1), graphs use 1.0 or the edge length.
+
+/// Erdős–Rényi: every entry is non-zero independently with probability `density`.
+///
+/// Instead of flipping a coin per entry, draws the gap to the next non-zero from the
+/// geometric distribution, so the cost is O(nnz) rather than O(rows · cols).
+pub fn erdos_renyi(rows: usize, cols: usize, density: f64, seed: u64) -> CompressedSparseRow<f64> {
+    assert!((0.0..=1.0).contains(&density), "density must be in [0, 1]");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let log_miss = (1.0 - density).ln(); // -inf for density 1, every gap is then 0
+
+    let mut row_pointers = Vec::with_capacity(rows + 1);
+    let mut column_indices = Vec::new();
+    let mut values = Vec::new();
+    row_pointers.push(0);
+
+    for _ in 0..rows {
+        let mut col = 0;
+        while density > 0.0 && col < cols {
+            // 1 - U is in (0, 1], so the log is finite, a huge gap saturates
+            let gap = ((1.0 - rng.gen::<f64>()).ln() / log_miss) as usize;
+            col = col.saturating_add(gap);
+            if col >= cols {
+                break;
+            }
+            column_indices.push(col);
+            values.push(nonzero(&mut rng));
+            col += 1;
+        }
+        row_pointers.push(values.len());
+    }
+
+    CompressedSparseRow::from_raw_parts(cols, row_pointers, column_indices, values).unwrap()
+}
+
+/// Parameters of an R-MAT graph with `2^scale` vertices and `edge_factor · 2^scale` edges.
+///
+/// Each edge picks one quadrant of the adjacency matrix with probabilities a, b, c and
+/// d = 1 - a - b - c, then recurses into it `scale` times. A large `a` concentrates edges
+/// on low ids, the skew knob: a = b = c = 0.25 is uniform, Graph500 uses a = 0.57.
+#[derive(Debug, Clone, Copy)]
+pub struct RmatConfig {
+    pub scale: u32,
+    pub edge_factor: usize,
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub symmetric: bool, // Add every edge in both directions
+}
+
+impl Default for RmatConfig {
+    fn default() -> Self {
+        Self {
+            scale: 16,
+            edge_factor: 16,
+            a: 0.57,
+            b: 0.19,
+            c: 0.19,
+            symmetric: true,
+        }
+    }
+}
+
+/// R-MAT / stochastic Kronecker power-law graph. Parallel edges add up, so a value counts
+/// how often an edge was drawn. Hubs sit at low ids, scramble with
+/// `reorder::random_shuffle` if that locality is unwanted.
+pub fn rmat(config: &RmatConfig, seed: u64) -> CompressedSparseRow<f64> {
+    let (a, b, c) = (config.a, config.b, config.c);
+    assert!(
+        a >= 0.0 && b >= 0.0 && c >= 0.0 && a + b + c <= 1.0,
+        "quadrant probabilities must be non-negative and sum to at most 1"
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n = 1usize << config.scale;
+    let edges = config.edge_factor * n;
+
+    let mut triplets = Vec::with_capacity(edges * if config.symmetric { 2 } else { 1 });
+    for _ in 0..edges {
+        let (mut u, mut v) = (0, 0);
+        for _ in 0..config.scale {
+            let p: f64 = rng.gen();
+            let (down, right) = if p < a {
+                (0, 0)
+            } else if p < a + b {
+                (0, 1)
+            } else if p < a + b + c {
+                (1, 0)
+            } else {
+                (1, 1)
+            };
+            u = u << 1 | down;
+            v = v << 1 | right;
+        }
+        triplets.push((u, v, 1.0));
+        if config.symmetric && u != v {
+            triplets.push((v, u, 1.0));
+        }
+    }
+
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+/// n x n matrix with non-zeros only within `half_width` of the diagonal, each present with
+/// probability `fill`. The diagonal is always present.
+pub fn banded(n: usize, half_width: usize, fill: f64, seed: u64) -> CompressedSparseRow<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut triplets = Vec::new();
+    for row in 0..n {
+        let first = row.saturating_sub(half_width);
+        let last = (row + half_width).min(n - 1);
+        for col in first..=last {
+            if col == row || rng.gen_bool(fill) {
+                triplets.push((row, col, nonzero(&mut rng)));
+            }
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+/// `blocks` square blocks of `block_size` along the diagonal, each entry of a block present
+/// with probability `fill`.
+pub fn block_diagonal(
+    blocks: usize,
+    block_size: usize,
+    fill: f64,
+    seed: u64,
+) -> CompressedSparseRow<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n = blocks * block_size;
+    let mut triplets = Vec::new();
+    for block in 0..blocks {
+        let base = block * block_size;
+        for r in 0..block_size {
+            for c in 0..block_size {
+                if rng.gen_bool(fill) {
+                    triplets.push((base + r, base + c, nonzero(&mut rng)));
+                }
+            }
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+/// 5-point finite difference Laplacian on an nx x ny grid (Dirichlet boundary), symmetric
+/// positive definite with 4 on the diagonal and -1 per grid neighbour.
+pub fn laplacian_2d(nx: usize, ny: usize) -> CompressedSparseRow<f64> {
+    laplacian(&[nx, ny])
+}
+
+/// 7-point finite difference Laplacian on an nx x ny x nz grid.
+pub fn laplacian_3d(nx: usize, ny: usize, nz: usize) -> CompressedSparseRow<f64> {
+    laplacian(&[nx, ny, nz])
+}
+
+// vertex id = x + nx * (y + ny * z), so the neighbour along axis d is `stride[d]` away
+fn laplacian(dims: &[usize]) -> CompressedSparseRow<f64> {
+    let n: usize = dims.iter().product();
+    let strides: Vec<usize> = dims
+        .iter()
+        .scan(1, |stride, &dim| {
+            let current = *stride;
+            *stride *= dim;
+            Some(current)
+        })
+        .collect();
+
+    let mut triplets = Vec::with_capacity(n * (2 * dims.len() + 1));
+    for v in 0..n {
+        triplets.push((v, v, 2.0 * dims.len() as f64));
+        for (&dim, &stride) in dims.iter().zip(&strides) {
+            let coordinate = v / stride % dim;
+            if coordinate + 1 < dim {
+                triplets.extend([(v, v + stride, -1.0), (v + stride, v, -1.0)]);
+            }
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+/// Random geometric graph: n points uniform in the unit square, an edge between every pair
+/// closer than `radius`, valued with the distance. The expected degree is about π·r²·n.
+///
+/// Points are bucketed into cells of side `radius` so only the 3x3 neighbouring cells are
+/// searched, O(n + edges) instead of O(n²).
+pub fn random_geometric(n: usize, radius: f64, seed: u64) -> CompressedSparseRow<f64> {
+    assert!(radius > 0.0, "radius must be positive");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let points: Vec<(f64, f64)> = (0..n).map(|_| (rng.gen(), rng.gen())).collect();
+
+    let side = ((1.0 / radius) as usize).clamp(1, n.max(1));
+    let cell_of = |x: f64| ((x * side as f64) as usize).min(side - 1);
+    let mut cells = vec![Vec::new(); side * side];
+    for (i, &(x, y)) in points.iter().enumerate() {
+        cells[cell_of(y) * side + cell_of(x)].push(i);
+    }
+
+    let mut triplets = Vec::new();
+    for (u, &(x, y)) in points.iter().enumerate() {
+        let (cx, cy) = (cell_of(x), cell_of(y));
+        for ny in cy.saturating_sub(1)..=(cy + 1).min(side - 1) {
+            for nx in cx.saturating_sub(1)..=(cx + 1).min(side - 1) {
+                for &v in &cells[ny * side + nx] {
+                    let distance = (points[v].0 - x).hypot(points[v].1 - y);
+                    if v != u && distance < radius {
+                        triplets.push((u, v, distance));
+                    }
+                }
+            }
+        }
+    }
+    CompressedSparseRow::from_triplets(n, n, triplets).unwrap()
+}
+
+// uniform in [-1, 1) without the (measure zero, but possible) exact 0
+fn nonzero(rng: &mut StdRng) -> f64 {
+    loop {
+        let value = rng.gen_range(-1.0..1.0);
+        if value != 0.0 {
+            return value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reorder::bandwidth;
+
+    fn is_symmetric(m: &CompressedSparseRow<f64>) -> bool {
+        m.transpose() == *m
+    }
+
+    #[test]
+    fn test_erdos_renyi_density_and_seed() {
+        let m = erdos_renyi(400, 300, 0.05, 7);
+        let density = m.nnz() as f64 / (400.0 * 300.0);
+        assert!((density - 0.05).abs() < 0.005, "density {}", density);
+        assert_eq!(m, erdos_renyi(400, 300, 0.05, 7));
+        assert_ne!(m, erdos_renyi(400, 300, 0.05, 8));
+
+        assert_eq!(erdos_renyi(10, 10, 0.0, 1).nnz(), 0);
+        assert_eq!(erdos_renyi(10, 12, 1.0, 1).nnz(), 120);
+    }
+
+    #[test]
+    fn test_rmat_skew() {
+        let degrees = |a: f64| {
+            let config = RmatConfig {
+                scale: 10,
+                edge_factor: 8,
+                a,
+                b: (1.0 - a) / 3.0,
+                c: (1.0 - a) / 3.0,
+                symmetric: true,
+            };
+            let m = rmat(&config, 3);
+            assert!(is_symmetric(&m));
+            let max = (0..m.rows()).map(|row| m.row_len(row)).max().unwrap();
+            max as f64 / (m.nnz() as f64 / m.rows() as f64)
+        };
+        // max degree over mean degree grows with the skew
+        assert!(degrees(0.25) < 4.0);
+        assert!(degrees(0.57) > 10.0);
+    }
+
+    #[test]
+    fn test_banded_and_block_diagonal_structure() {
+        let m = banded(200, 3, 0.5, 1);
+        assert!(bandwidth(&m) <= 3);
+        assert!((0..200).all(|i| m.get(i, i) != 0.0));
+
+        let m = block_diagonal(10, 8, 0.7, 1);
+        assert_eq!(m.rows(), 80);
+        for row in 0..80 {
+            assert!(m.row(row).0.iter().all(|&col| col / 8 == row / 8));
+        }
+    }
+
+    #[test]
+    fn test_laplacians() {
+        let m = laplacian_2d(5, 4);
+        assert_eq!(m.rows(), 20);
+        // 5 per vertex minus the missing neighbours on the boundary
+        assert_eq!(m.nnz(), 20 + 2 * (4 * 4 + 5 * 3));
+        assert!(is_symmetric(&m));
+        // interior rows sum to 0
+        assert_eq!(m.row(6).1.iter().sum::<f64>(), 0.0);
+
+        let m = laplacian_3d(3, 3, 3);
+        assert_eq!(m.get(13, 13), 6.0);
+        assert_eq!(m.row_len(13), 7);
+        assert_eq!(m.row_len(0), 4);
+    }
+
+    #[test]
+    fn test_random_geometric() {
+        let m = random_geometric(2000, 0.03, 5);
+        assert!(is_symmetric(&m));
+        assert!(m.values().iter().all(|&d| d > 0.0 && d < 0.03));
+        let degree = m.nnz() as f64 / 2000.0;
+        let expected = std::f64::consts::PI * 0.03 * 0.03 * 2000.0;
+        assert!((degree - expected).abs() < 1.0, "degree {}", degree);
+    }
+}
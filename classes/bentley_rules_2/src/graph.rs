@@ -0,0 +1,292 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use std::ops::{Add, Mul};
+
+mod bfs;
+mod components;
+pub mod compressed;
+mod pagerank;
+mod sssp;
+mod triangles;
+pub mod validate;
+
+pub use bfs::UNREACHED;
+pub use compressed::{CompressedGraph, Encoding};
+pub use pagerank::PageRankConfig;
+
+/// Edge value of a pattern-only graph.
+///
+/// Zero-sized, so the `values` vector of the underlying CSR never allocates and a
+/// pattern-only graph costs exactly `row_pointers + column_indices`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Unweighted;
+
+impl Add for Unweighted {
+    type Output = Self;
+
+    fn add(self, _: Self) -> Self {
+        Unweighted
+    }
+}
+
+impl Mul for Unweighted {
+    type Output = Self;
+
+    fn mul(self, _: Self) -> Self {
+        Unweighted
+    }
+}
+
+/// Anything that can label an edge. Algorithms that need a length (SSSP) read it as f64,
+/// a pattern-only edge has length 1.
+pub trait EdgeWeight:
+    Add<Output = Self> + Mul<Output = Self> + Default + Copy + PartialEq + Send + Sync
+{
+    fn weight(self) -> f64;
+}
+
+impl EdgeWeight for Unweighted {
+    fn weight(self) -> f64 {
+        1.0
+    }
+}
+
+macro_rules! impl_edge_weight {
+    ($($t:ty),*) => {
+        $(impl EdgeWeight for $t {
+            fn weight(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+impl_edge_weight!(f32, f64, u8, u16, u32, u64, usize);
+
+/// Graph stored as a CSR adjacency matrix: row `u` lists the out-neighbours of `u`,
+/// sorted by id, without self loops or parallel edges.
+///
+/// Undirected graphs store every edge in both directions so the adjacency is symmetric
+/// and doubles as the in-edge list. Directed graphs also keep the transpose, which
+/// pull-style algorithms (bottom-up BFS, pull PageRank) need.
+#[derive(Debug, Clone)]
+pub struct Graph<W: EdgeWeight = Unweighted> {
+    out_edges: CompressedSparseRow<W>,
+    in_edges: Option<CompressedSparseRow<W>>, // None when out_edges is symmetric
+}
+
+impl Graph<Unweighted> {
+    pub fn undirected(vertices: usize, edges: &[(usize, usize)]) -> Self {
+        let edges: Vec<_> = edges.iter().map(|&(u, v)| (u, v, Unweighted)).collect();
+        Self::undirected_weighted(vertices, &edges)
+    }
+
+    pub fn directed(vertices: usize, edges: &[(usize, usize)]) -> Self {
+        let edges: Vec<_> = edges.iter().map(|&(u, v)| (u, v, Unweighted)).collect();
+        Self::directed_weighted(vertices, &edges)
+    }
+}
+
+impl<W: EdgeWeight> Graph<W> {
+    pub fn undirected_weighted(vertices: usize, edges: &[(usize, usize, W)]) -> Self {
+        let arcs = edges
+            .iter()
+            .flat_map(|&(u, v, w)| [(u, v, w), (v, u, w)])
+            .collect();
+
+        Self {
+            out_edges: adjacency(vertices, arcs),
+            in_edges: None,
+        }
+    }
+
+    pub fn directed_weighted(vertices: usize, edges: &[(usize, usize, W)]) -> Self {
+        let out_edges = adjacency(vertices, edges.to_vec());
+        let in_edges = Some(transpose(&out_edges));
+
+        Self {
+            out_edges,
+            in_edges,
+        }
+    }
+
+    /// Treats a square matrix as the adjacency of a directed graph, `A[u][v]` is the edge u -> v.
+    pub fn from_csr(matrix: &CompressedSparseRow<W>) -> Self {
+        assert_eq!(
+            matrix.rows(),
+            matrix.cols(),
+            "adjacency matrix must be square"
+        );
+
+        let arcs = (0..matrix.rows())
+            .flat_map(|u| {
+                let (cols, vals) = matrix.row(u);
+                cols.iter().zip(vals).map(move |(&v, &w)| (u, v, w))
+            })
+            .collect::<Vec<_>>();
+
+        Self::directed_weighted(matrix.rows(), &arcs)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.out_edges.rows()
+    }
+
+    /// Number of stored arcs, an undirected edge counts twice.
+    pub fn arc_count(&self) -> usize {
+        self.out_edges.nnz()
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.in_edges.is_some()
+    }
+
+    pub fn adjacency(&self) -> &CompressedSparseRow<W> {
+        &self.out_edges
+    }
+
+    pub fn out_degree(&self, vertex: usize) -> usize {
+        self.out_edges.row_len(vertex)
+    }
+
+    pub fn neighbors(&self, vertex: usize) -> &[usize] {
+        self.out_edges.row(vertex).0
+    }
+
+    /// Out-neighbours of `vertex` with the weight of each edge.
+    pub fn edges(&self, vertex: usize) -> (&[usize], &[W]) {
+        self.out_edges.row(vertex)
+    }
+
+    pub fn in_neighbors(&self, vertex: usize) -> &[usize] {
+        self.in_edges
+            .as_ref()
+            .unwrap_or(&self.out_edges)
+            .row(vertex)
+            .0
+    }
+}
+
+// Sorts arcs into CSR order, dropping self loops and keeping the first of parallel arcs.
+fn adjacency<W: EdgeWeight>(
+    vertices: usize,
+    mut arcs: Vec<(usize, usize, W)>,
+) -> CompressedSparseRow<W> {
+    arcs.retain(|&(u, v, _)| {
+        assert!(
+            u < vertices && v < vertices,
+            "edge ({}, {}) out of range",
+            u,
+            v
+        );
+        u != v
+    });
+    arcs.sort_by_key(|&(u, v, _)| (u, v));
+    arcs.dedup_by_key(|&mut (u, v, _)| (u, v));
+
+    let mut row_pointers = vec![0; vertices + 1];
+    for &(u, _, _) in &arcs {
+        row_pointers[u + 1] += 1;
+    }
+    for u in 0..vertices {
+        row_pointers[u + 1] += row_pointers[u];
+    }
+
+    let (column_indices, values) = arcs.into_iter().map(|(_, v, w)| (v, w)).unzip();
+    // not from_triplets, a zero weight is still an edge
+    CompressedSparseRow::from_raw_parts(vertices, row_pointers, column_indices, values).unwrap()
+}
+
+fn transpose<W: EdgeWeight>(matrix: &CompressedSparseRow<W>) -> CompressedSparseRow<W> {
+    let arcs = (0..matrix.rows())
+        .flat_map(|u| {
+            let (cols, vals) = matrix.row(u);
+            cols.iter().zip(vals).map(move |(&v, &w)| (v, u, w))
+        })
+        .collect();
+    adjacency(matrix.cols(), arcs)
+}
+
+/// One bit per vertex, used for BFS frontiers and visited sets.
+#[derive(Debug, Clone)]
+pub(crate) struct Bitmap {
+    words: Vec<u64>,
+}
+
+impl Bitmap {
+    pub(crate) fn new(bits: usize) -> Self {
+        Self {
+            words: vec![0; bits.div_ceil(64)],
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    #[inline(always)]
+    pub(crate) fn set(&mut self, bit: usize) {
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub(crate) fn words_mut(&mut self) -> &mut [u64] {
+        &mut self.words
+    }
+
+    /// Indices of the set bits, peeling off the lowest set bit of each word with `w & (w - 1)`.
+    pub(crate) fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undirected_adjacency_is_symmetric_and_clean() {
+        // duplicate edge, reversed duplicate and a self loop
+        let g = Graph::undirected(4, &[(0, 1), (1, 0), (1, 2), (2, 2), (0, 1), (3, 1)]);
+        assert!(!g.is_directed());
+        assert_eq!(g.arc_count(), 6);
+        assert_eq!(g.neighbors(1), &[0, 2, 3]);
+        assert_eq!(g.in_neighbors(1), &[0, 2, 3]);
+        assert_eq!(g.neighbors(2), &[1]);
+        // pattern-only graphs don't spend memory on values
+        assert_eq!(std::mem::size_of_val(g.adjacency().values()), 0);
+    }
+
+    #[test]
+    fn test_directed_keeps_transpose() {
+        let g = Graph::directed_weighted(3, &[(0, 1, 2.0), (0, 2, 0.0), (2, 1, 5.0)]);
+        assert!(g.is_directed());
+        assert_eq!(g.edges(0), (&[1, 2][..], &[2.0, 0.0][..]));
+        assert_eq!(g.in_neighbors(1), &[0, 2]);
+        assert_eq!(g.in_neighbors(0), &[] as &[usize]);
+    }
+
+    #[test]
+    fn test_bitmap_ones() {
+        let mut bitmap = Bitmap::new(200);
+        for bit in [0, 63, 64, 130, 199] {
+            bitmap.set(bit);
+        }
+        assert!(bitmap.get(130));
+        assert!(!bitmap.get(131));
+        assert_eq!(bitmap.ones().collect::<Vec<_>>(), vec![0, 63, 64, 130, 199]);
+    }
+}
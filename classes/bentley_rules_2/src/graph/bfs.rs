@@ -0,0 +1,299 @@
+use super::{Bitmap, EdgeWeight, Graph};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Parent of a vertex the search never reached.
+pub const UNREACHED: usize = usize::MAX;
+
+// Beamer's heuristics: go bottom-up once the frontier touches more than 1/ALPHA of the
+// unexplored edges, come back top-down when the frontier shrinks below n/BETA vertices.
+const ALPHA: usize = 15;
+const BETA: usize = 18;
+
+impl<W: EdgeWeight> Graph<W> {
+    /// Top-down BFS. Returns the BFS tree as a parent array, `parents[source] == source`
+    /// and unreached vertices are `UNREACHED`.
+    ///
+    /// The visited set is a bitmap, 64x smaller than the parent array, so the
+    /// "seen it already?" test on every edge mostly hits cache.
+    pub fn bfs(&self, source: usize) -> Vec<usize> {
+        let n = self.vertex_count();
+        let mut parents = vec![UNREACHED; n];
+        let mut visited = Bitmap::new(n);
+        parents[source] = source;
+        visited.set(source);
+
+        let mut frontier = vec![source];
+        let mut next = Vec::new();
+        while !frontier.is_empty() {
+            for &u in &frontier {
+                for &v in self.neighbors(u) {
+                    if !visited.get(v) {
+                        visited.set(v);
+                        parents[v] = u;
+                        next.push(v);
+                    }
+                }
+            }
+            std::mem::swap(&mut frontier, &mut next);
+            next.clear();
+        }
+
+        parents
+    }
+
+    /// Parallel top-down BFS, vertices are claimed with a CAS on their parent slot.
+    pub fn par_bfs(&self, source: usize) -> Vec<usize> {
+        let parents = unreached(self.vertex_count());
+        parents[source].store(source, Ordering::Relaxed);
+
+        let mut frontier = vec![source];
+        while !frontier.is_empty() {
+            frontier = self.par_top_down_step(&frontier, &parents);
+        }
+
+        parents.into_iter().map(AtomicUsize::into_inner).collect()
+    }
+
+    /// Direction-optimizing BFS (Beamer et al.).
+    ///
+    /// Top-down scans every edge out of the frontier, which is wasteful in the middle
+    /// levels of a small-world graph where most of those edges lead to visited vertices.
+    /// Bottom-up flips it: every unvisited vertex scans its in-edges for any frontier vertex
+    /// and stops at the first hit. The frontier is then a bitmap so the membership test is
+    /// a single bit lookup.
+    pub fn bfs_direction_optimizing(&self, source: usize) -> Vec<usize> {
+        let n = self.vertex_count();
+        let mut parents = vec![UNREACHED; n];
+        parents[source] = source;
+
+        let mut frontier = vec![source];
+        let mut frontier_bits = Bitmap::new(n);
+        let mut next_bits = Bitmap::new(n);
+        let mut frontier_edges = self.out_degree(source);
+        let mut unexplored_edges = self.arc_count() - frontier_edges;
+
+        while !frontier.is_empty() {
+            if frontier_edges > unexplored_edges / ALPHA {
+                frontier_bits.clear();
+                frontier.iter().for_each(|&u| frontier_bits.set(u));
+
+                let mut awake = frontier.len();
+                loop {
+                    let previous = awake;
+                    let (woken, edges) =
+                        self.bottom_up_step(&frontier_bits, &mut next_bits, &mut parents);
+                    std::mem::swap(&mut frontier_bits, &mut next_bits);
+                    awake = woken;
+                    unexplored_edges = unexplored_edges.saturating_sub(edges);
+                    if awake == 0 || (awake < previous && awake < n / BETA) {
+                        break;
+                    }
+                }
+
+                // the woken vertices' edges were already taken off unexplored_edges
+                frontier = frontier_bits.ones().collect();
+                frontier_edges = frontier.iter().map(|&u| self.out_degree(u)).sum();
+            } else {
+                let mut next = Vec::new();
+                for &u in &frontier {
+                    for &v in self.neighbors(u) {
+                        if parents[v] == UNREACHED {
+                            parents[v] = u;
+                            next.push(v);
+                        }
+                    }
+                }
+                frontier = next;
+                frontier_edges = frontier.iter().map(|&u| self.out_degree(u)).sum();
+                unexplored_edges = unexplored_edges.saturating_sub(frontier_edges);
+            }
+        }
+
+        parents
+    }
+
+    /// Parallel direction-optimizing BFS.
+    ///
+    /// Bottom-up steps split the vertices into 64-vertex chunks, one per bitmap word, so
+    /// every task owns both its parent slots and its word of the next frontier and no
+    /// atomics are needed.
+    pub fn par_bfs_direction_optimizing(&self, source: usize) -> Vec<usize> {
+        let n = self.vertex_count();
+        let mut parents = unreached(n);
+        parents[source].store(source, Ordering::Relaxed);
+
+        let mut frontier = vec![source];
+        let mut frontier_bits = Bitmap::new(n);
+        let mut next_bits = Bitmap::new(n);
+        let mut frontier_edges = self.out_degree(source);
+        let mut unexplored_edges = self.arc_count() - frontier_edges;
+
+        while !frontier.is_empty() {
+            if frontier_edges > unexplored_edges / ALPHA {
+                frontier_bits.clear();
+                frontier.iter().for_each(|&u| frontier_bits.set(u));
+
+                let mut awake = frontier.len();
+                loop {
+                    let previous = awake;
+                    let (woken, edges) =
+                        self.par_bottom_up_step(&frontier_bits, &mut next_bits, &mut parents);
+                    std::mem::swap(&mut frontier_bits, &mut next_bits);
+                    awake = woken;
+                    unexplored_edges = unexplored_edges.saturating_sub(edges);
+                    if awake == 0 || (awake < previous && awake < n / BETA) {
+                        break;
+                    }
+                }
+
+                // the woken vertices' edges were already taken off unexplored_edges
+                frontier = frontier_bits.ones().collect();
+                frontier_edges = frontier.iter().map(|&u| self.out_degree(u)).sum();
+            } else {
+                frontier = self.par_top_down_step(&frontier, &parents);
+                frontier_edges = frontier.par_iter().map(|&u| self.out_degree(u)).sum();
+                unexplored_edges = unexplored_edges.saturating_sub(frontier_edges);
+            }
+        }
+
+        parents.into_iter().map(AtomicUsize::into_inner).collect()
+    }
+
+    fn par_top_down_step(&self, frontier: &[usize], parents: &[AtomicUsize]) -> Vec<usize> {
+        frontier
+            .par_iter()
+            .flat_map_iter(|&u| {
+                self.neighbors(u).iter().copied().filter(move |&v| {
+                    // cheap load first, most edges lead to visited vertices
+                    parents[v].load(Ordering::Relaxed) == UNREACHED
+                        && parents[v]
+                            .compare_exchange(UNREACHED, u, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_ok()
+                })
+            })
+            .collect()
+    }
+
+    // Returns how many vertices joined the next frontier and their total out-degree.
+    fn bottom_up_step(
+        &self,
+        frontier: &Bitmap,
+        next: &mut Bitmap,
+        parents: &mut [usize],
+    ) -> (usize, usize) {
+        next.clear();
+        let (mut awake, mut edges) = (0, 0);
+
+        for (v, parent) in parents.iter_mut().enumerate() {
+            if *parent != UNREACHED {
+                continue;
+            }
+            if let Some(&u) = self.in_neighbors(v).iter().find(|&&u| frontier.get(u)) {
+                *parent = u;
+                next.set(v);
+                awake += 1;
+                edges += self.out_degree(v);
+            }
+        }
+
+        (awake, edges)
+    }
+
+    fn par_bottom_up_step(
+        &self,
+        frontier: &Bitmap,
+        next: &mut Bitmap,
+        parents: &mut [AtomicUsize],
+    ) -> (usize, usize) {
+        parents
+            .par_chunks_mut(64)
+            .zip(next.words_mut().par_iter_mut())
+            .enumerate()
+            .map(|(chunk, (parents, word))| {
+                let (mut bits, mut awake, mut edges) = (0u64, 0, 0);
+
+                for (i, parent) in parents.iter_mut().enumerate() {
+                    let parent = parent.get_mut();
+                    if *parent != UNREACHED {
+                        continue;
+                    }
+                    let v = chunk * 64 + i;
+                    if let Some(&u) = self.in_neighbors(v).iter().find(|&&u| frontier.get(u)) {
+                        *parent = u;
+                        bits |= 1 << i;
+                        awake += 1;
+                        edges += self.out_degree(v);
+                    }
+                }
+
+                *word = bits;
+                (awake, edges)
+            })
+            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
+    }
+}
+
+fn unreached(n: usize) -> Vec<AtomicUsize> {
+    (0..n).map(|_| AtomicUsize::new(UNREACHED)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate::validate_bfs_tree;
+    use super::*;
+
+    // two triangles joined by a path, plus an isolated vertex 7
+    fn small() -> Graph {
+        Graph::undirected(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 6),
+                (6, 4),
+            ],
+        )
+    }
+
+    // a hub connected to everything makes the middle level huge, forcing bottom-up steps
+    fn star_of_paths(n: usize) -> Graph {
+        let mut edges = Vec::new();
+        for v in 1..n {
+            edges.push((0, v));
+            if v + 1 < n && v % 3 != 0 {
+                edges.push((v, v + 1));
+            }
+        }
+        Graph::undirected(n, &edges)
+    }
+
+    #[test]
+    fn test_bfs_variants_produce_valid_trees() {
+        for g in [small(), star_of_paths(500)] {
+            for source in [0, 3, 4] {
+                for parents in [
+                    g.bfs(source),
+                    g.par_bfs(source),
+                    g.bfs_direction_optimizing(source),
+                    g.par_bfs_direction_optimizing(source),
+                ] {
+                    validate_bfs_tree(&g, source, &parents).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bfs_directed_unreachable() {
+        let g = Graph::directed(4, &[(0, 1), (1, 2), (3, 0)]);
+        let parents = g.bfs_direction_optimizing(0);
+        assert_eq!(parents, vec![0, 0, 1, UNREACHED]);
+        validate_bfs_tree(&g, 0, &g.par_bfs(0)).unwrap();
+        assert_eq!(small().bfs(0)[7], UNREACHED);
+    }
+}
@@ -0,0 +1,158 @@
+use super::{EdgeWeight, Graph};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// Every variant labels a vertex with the smallest vertex id of its (weakly) connected
+// component, so their outputs can be compared directly.
+impl<W: EdgeWeight> Graph<W> {
+    /// Label propagation: every vertex starts as its own label and edges pull labels down to
+    /// the minimum until nothing changes. O(diameter) passes over the edges, cheap per pass
+    /// and trivially parallel, slow on long paths.
+    pub fn components_label_propagation(&self) -> Vec<usize> {
+        let mut labels: Vec<usize> = (0..self.vertex_count()).collect();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for u in 0..self.vertex_count() {
+                for &v in self.neighbors(u) {
+                    let min = labels[u].min(labels[v]);
+                    if labels[u] != min || labels[v] != min {
+                        labels[u] = min;
+                        labels[v] = min;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        labels
+    }
+
+    pub fn par_components_label_propagation(&self) -> Vec<usize> {
+        let labels: Vec<AtomicUsize> = (0..self.vertex_count()).map(AtomicUsize::new).collect();
+
+        let changed = AtomicBool::new(true);
+        while changed.swap(false, Ordering::Relaxed) {
+            (0..self.vertex_count()).into_par_iter().for_each(|u| {
+                for &v in self.neighbors(u) {
+                    let lu = labels[u].load(Ordering::Relaxed);
+                    let lv = labels[v].load(Ordering::Relaxed);
+                    if lu < lv {
+                        labels[v].fetch_min(lu, Ordering::Relaxed);
+                        changed.store(true, Ordering::Relaxed);
+                    } else if lv < lu {
+                        labels[u].fetch_min(lv, Ordering::Relaxed);
+                        changed.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+
+        labels.into_iter().map(AtomicUsize::into_inner).collect()
+    }
+
+    /// Union-find with path halving. Roots are always linked under the smaller root, so
+    /// the root of every set is its smallest vertex id.
+    pub fn components_union_find(&self) -> Vec<usize> {
+        let mut parents: Vec<usize> = (0..self.vertex_count()).collect();
+
+        fn find(parents: &mut [usize], mut x: usize) -> usize {
+            while parents[x] != x {
+                parents[x] = parents[parents[x]];
+                x = parents[x];
+            }
+            x
+        }
+
+        for u in 0..self.vertex_count() {
+            for &v in self.neighbors(u) {
+                let (ru, rv) = (find(&mut parents, u), find(&mut parents, v));
+                if ru != rv {
+                    parents[ru.max(rv)] = ru.min(rv);
+                }
+            }
+        }
+
+        (0..self.vertex_count())
+            .map(|u| find(&mut parents, u))
+            .collect()
+    }
+
+    /// Concurrent union-find. A union only ever swings a root to a smaller root with a CAS,
+    /// so parent pointers always decrease and the structure can't form a cycle. Path
+    /// halving is a best-effort CAS that is fine to lose.
+    pub fn par_components_union_find(&self) -> Vec<usize> {
+        let parents: Vec<AtomicUsize> = (0..self.vertex_count()).map(AtomicUsize::new).collect();
+
+        let find = |mut x: usize| -> usize {
+            loop {
+                let parent = parents[x].load(Ordering::Relaxed);
+                if parent == x {
+                    return x;
+                }
+                let grandparent = parents[parent].load(Ordering::Relaxed);
+                let _ = parents[x].compare_exchange(
+                    parent,
+                    grandparent,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+                x = grandparent;
+            }
+        };
+
+        (0..self.vertex_count()).into_par_iter().for_each(|u| {
+            for &v in self.neighbors(u) {
+                loop {
+                    let (ru, rv) = (find(u), find(v));
+                    if ru == rv {
+                        break;
+                    }
+                    let (low, high) = (ru.min(rv), ru.max(rv));
+                    // fails if `high` stopped being a root in the meantime, retry from scratch
+                    if parents[high]
+                        .compare_exchange(high, low, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+
+        (0..self.vertex_count()).into_par_iter().map(find).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate::validate_components;
+    use super::*;
+
+    #[test]
+    fn test_components_variants_agree() {
+        // {0, 1, 2, 5}, {3, 4}, {6}, and a long path {7..40} numbered backwards
+        let mut edges = vec![(5, 1), (1, 2), (2, 0), (4, 3)];
+        edges.extend((8..40).map(|v| (v, v - 1)));
+        let g = Graph::undirected(40, &edges);
+
+        let expected = g.components_union_find();
+        validate_components(&g, &expected).unwrap();
+        assert_eq!(&expected[..7], &[0, 0, 0, 3, 3, 0, 6]);
+        assert!(expected[7..].iter().all(|&label| label == 7));
+
+        assert_eq!(g.components_label_propagation(), expected);
+        assert_eq!(g.par_components_label_propagation(), expected);
+        assert_eq!(g.par_components_union_find(), expected);
+    }
+
+    #[test]
+    fn test_components_directed_are_weak() {
+        let g = Graph::directed(4, &[(1, 0), (2, 3)]);
+        assert_eq!(g.components_union_find(), vec![0, 0, 2, 2]);
+        assert_eq!(g.components_label_propagation(), vec![0, 0, 2, 2]);
+        assert_eq!(g.par_components_label_propagation(), vec![0, 0, 2, 2]);
+        assert_eq!(g.par_components_union_find(), vec![0, 0, 2, 2]);
+    }
+}
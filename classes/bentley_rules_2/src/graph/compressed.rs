@@ -0,0 +1,532 @@
+use super::bfs::UNREACHED;
+use super::pagerank::power_iterations;
+use super::{Bitmap, EdgeWeight, Graph, PageRankConfig};
+use crate::compressed_sparse_row::CompressedSparseRow;
+use rayon::prelude::*;
+use std::ops::{Add, Mul};
+
+// Ligra+-style compressed adjacency. A sorted neighbour list is stored as gaps instead of
+// ids: the first neighbour relative to the vertex itself (zigzag, it can be smaller), every
+// later one as `v - previous - 1` (no parallel edges, so gaps are never negative). Gaps are
+// small whenever ids have locality, and small numbers get short codes.
+
+/// How the gaps of one row are turned into bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// LEB128: 7 payload bits per byte, the high bit says "more bytes follow".
+    Varint,
+    /// Like varint with 4-bit units, 3 payload bits each. Gaps under 8 take half a byte,
+    /// rows are padded to a whole byte.
+    Nibble,
+    /// Runs of up to 64 gaps of the same byte width (1, 2, 4 or 8) behind a one-byte header
+    /// `width_class << 6 | (count - 1)`. No continuation bits to test per byte, so it decodes
+    /// fastest, at a slightly worse ratio than varint.
+    RunLength,
+}
+
+const MAX_RUN: usize = 64;
+
+/// Compressed pattern-only adjacency: per vertex a byte offset into one shared buffer and
+/// a degree. Degrees are kept explicitly since nibble padding makes the end of a row
+/// ambiguous, and PageRank needs them anyway.
+#[derive(Debug, Clone)]
+pub struct CompressedAdjacency {
+    encoding: Encoding,
+    offsets: Vec<usize>, // Start of every row in data, one extra entry at the end
+    degrees: Vec<u32>,
+    data: Vec<u8>,
+}
+
+impl CompressedAdjacency {
+    /// Encodes the pattern of a square CSR, the stored values are ignored.
+    ///
+    /// Rows must be free of duplicate columns, which the `Graph` constructors guarantee.
+    pub fn from_csr<T>(matrix: &CompressedSparseRow<T>, encoding: Encoding) -> Self
+    where
+        T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+    {
+        assert_eq!(
+            matrix.rows(),
+            matrix.cols(),
+            "adjacency matrix must be square"
+        );
+        let n = matrix.rows();
+        let mut offsets = Vec::with_capacity(n + 1);
+        let mut degrees = Vec::with_capacity(n);
+        let mut data = Vec::new();
+        let mut gaps = Vec::new();
+
+        offsets.push(0);
+        for u in 0..n {
+            let cols = matrix.row(u).0;
+            degrees.push(u32::try_from(cols.len()).expect("degree does not fit in 32 bits"));
+
+            gaps.clear();
+            if let Some(&first) = cols.first() {
+                gaps.push(zigzag(first as i64 - u as i64));
+            }
+            for pair in cols.windows(2) {
+                assert!(pair[0] < pair[1], "row {} is not strictly increasing", u);
+                gaps.push((pair[1] - pair[0] - 1) as u64);
+            }
+
+            match encoding {
+                Encoding::Varint => gaps.iter().for_each(|&gap| write_varint(gap, &mut data)),
+                Encoding::Nibble => write_nibbles(&gaps, &mut data),
+                Encoding::RunLength => write_runs(&gaps, &mut data),
+            }
+            offsets.push(data.len());
+        }
+
+        Self {
+            encoding,
+            offsets,
+            degrees,
+            data,
+        }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.degrees.len()
+    }
+
+    pub fn arc_count(&self) -> usize {
+        self.degrees.iter().map(|&d| d as usize).sum()
+    }
+
+    #[inline(always)]
+    pub fn degree(&self, vertex: usize) -> usize {
+        self.degrees[vertex] as usize
+    }
+
+    /// Size of the encoded neighbour lists alone.
+    pub fn encoded_bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Total memory of the encoded graph including offsets and degrees.
+    pub fn bytes(&self) -> usize {
+        self.data.len()
+            + self.offsets.len() * std::mem::size_of::<usize>()
+            + self.degrees.len() * std::mem::size_of::<u32>()
+    }
+
+    /// Decodes the neighbours of `vertex` in increasing order.
+    #[inline(always)]
+    pub fn neighbors(&self, vertex: usize) -> Neighbors<'_> {
+        Neighbors {
+            encoding: self.encoding,
+            data: &self.data[self.offsets[vertex]..self.offsets[vertex + 1]],
+            position: 0,
+            remaining: self.degrees[vertex] as usize,
+            previous: vertex,
+            first: true,
+            run_left: 0,
+            run_width: 0,
+        }
+    }
+}
+
+/// Decoder for one row. Every call to `next` decodes exactly one gap, so a traversal that
+/// stops early (bottom-up BFS) only pays for what it reads.
+pub struct Neighbors<'a> {
+    encoding: Encoding,
+    data: &'a [u8],
+    position: usize, // Bytes, nibbles for Encoding::Nibble
+    remaining: usize,
+    previous: usize,
+    first: bool,
+    run_left: usize,
+    run_width: usize,
+}
+
+impl Iterator for Neighbors<'_> {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        // the encoding is the same for every row, so this branch is perfectly predicted
+        let gap = match self.encoding {
+            Encoding::Varint => read_varint(self.data, &mut self.position),
+            Encoding::Nibble => read_nibbles(self.data, &mut self.position),
+            Encoding::RunLength => {
+                if self.run_left == 0 {
+                    let header = self.data[self.position];
+                    self.position += 1;
+                    self.run_width = 1 << (header >> 6);
+                    self.run_left = (header & 63) as usize + 1;
+                }
+                self.run_left -= 1;
+                read_fixed(self.data, &mut self.position, self.run_width)
+            }
+        };
+
+        self.previous = if self.first {
+            self.first = false;
+            (self.previous as i64 + unzigzag(gap)) as usize
+        } else {
+            self.previous + gap as usize + 1
+        };
+        Some(self.previous)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Neighbors<'_> {}
+
+/// `Graph` with both edge directions stored as `CompressedAdjacency`.
+#[derive(Debug, Clone)]
+pub struct CompressedGraph {
+    out_edges: CompressedAdjacency,
+    in_edges: Option<CompressedAdjacency>, // None when out_edges is symmetric
+}
+
+impl CompressedGraph {
+    pub fn from_graph<W: EdgeWeight>(graph: &Graph<W>, encoding: Encoding) -> Self {
+        Self {
+            out_edges: CompressedAdjacency::from_csr(&graph.out_edges, encoding),
+            in_edges: graph
+                .in_edges
+                .as_ref()
+                .map(|in_edges| CompressedAdjacency::from_csr(in_edges, encoding)),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.out_edges.vertex_count()
+    }
+
+    pub fn arc_count(&self) -> usize {
+        self.out_edges.arc_count()
+    }
+
+    pub fn out_degree(&self, vertex: usize) -> usize {
+        self.out_edges.degree(vertex)
+    }
+
+    pub fn neighbors(&self, vertex: usize) -> Neighbors<'_> {
+        self.out_edges.neighbors(vertex)
+    }
+
+    pub fn in_neighbors(&self, vertex: usize) -> Neighbors<'_> {
+        self.in_edges
+            .as_ref()
+            .unwrap_or(&self.out_edges)
+            .neighbors(vertex)
+    }
+
+    /// Memory of both directions, an undirected graph is only stored once.
+    pub fn bytes(&self) -> usize {
+        self.out_edges.bytes() + self.in_edges.as_ref().map_or(0, |e| e.bytes())
+    }
+
+    pub fn encoded_bytes(&self) -> usize {
+        self.out_edges.encoded_bytes() + self.in_edges.as_ref().map_or(0, |e| e.encoded_bytes())
+    }
+
+    /// Same top-down BFS as `Graph::bfs`, decoding neighbour lists on the fly.
+    pub fn bfs(&self, source: usize) -> Vec<usize> {
+        let n = self.vertex_count();
+        let mut parents = vec![UNREACHED; n];
+        let mut visited = Bitmap::new(n);
+        parents[source] = source;
+        visited.set(source);
+
+        let mut frontier = vec![source];
+        let mut next = Vec::new();
+        while !frontier.is_empty() {
+            for &u in &frontier {
+                for v in self.neighbors(u) {
+                    if !visited.get(v) {
+                        visited.set(v);
+                        parents[v] = u;
+                        next.push(v);
+                    }
+                }
+            }
+            std::mem::swap(&mut frontier, &mut next);
+            next.clear();
+        }
+
+        parents
+    }
+
+    /// Same pull PageRank as `Graph::pagerank_pull`.
+    pub fn pagerank_pull(&self, config: &PageRankConfig) -> Vec<f64> {
+        let n = self.vertex_count();
+        power_iterations(
+            n,
+            |u| self.out_degree(u),
+            config,
+            |contributions, base, next| {
+                for (v, rank) in next.iter_mut().enumerate() {
+                    let gathered: f64 = self.in_neighbors(v).map(|u| contributions[u]).sum();
+                    *rank = base + config.damping * gathered;
+                }
+            },
+        )
+    }
+
+    pub fn par_pagerank_pull(&self, config: &PageRankConfig) -> Vec<f64> {
+        let n = self.vertex_count();
+        power_iterations(
+            n,
+            |u| self.out_degree(u),
+            config,
+            |contributions, base, next| {
+                next.par_iter_mut().enumerate().for_each(|(v, rank)| {
+                    let gathered: f64 = self.in_neighbors(v).map(|u| contributions[u]).sum();
+                    *rank = base + config.damping * gathered;
+                })
+            },
+        )
+    }
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[inline(always)]
+fn read_varint(data: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*position];
+        *position += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+// nibbles fill the low half of a byte first
+fn write_nibbles(gaps: &[u64], out: &mut Vec<u8>) {
+    let mut nibbles = Vec::new();
+    for &gap in gaps {
+        let mut value = gap;
+        loop {
+            let payload = (value & 7) as u8;
+            value >>= 3;
+            if value == 0 {
+                nibbles.push(payload);
+                break;
+            }
+            nibbles.push(payload | 8);
+        }
+    }
+    out.extend(
+        nibbles
+            .chunks(2)
+            .map(|pair| pair[0] | pair.get(1).map_or(0, |high| high << 4)),
+    );
+}
+
+#[inline(always)]
+fn read_nibbles(data: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let nibble = (data[*position / 2] >> ((*position & 1) * 4)) & 15;
+        *position += 1;
+        value |= ((nibble & 7) as u64) << shift;
+        if nibble < 8 {
+            return value;
+        }
+        shift += 3;
+    }
+}
+
+fn width_class(gap: u64) -> u8 {
+    match gap {
+        0..=0xff => 0,
+        0x100..=0xffff => 1,
+        0x1_0000..=0xffff_ffff => 2,
+        _ => 3,
+    }
+}
+
+fn write_runs(gaps: &[u64], out: &mut Vec<u8>) {
+    let mut start = 0;
+    while start < gaps.len() {
+        let class = width_class(gaps[start]);
+        let len = gaps[start..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&gap| width_class(gap) == class)
+            .count();
+
+        out.push(class << 6 | (len - 1) as u8);
+        for &gap in &gaps[start..start + len] {
+            out.extend_from_slice(&gap.to_le_bytes()[..1 << class]);
+        }
+        start += len;
+    }
+}
+
+#[inline(always)]
+fn read_fixed(data: &[u8], position: &mut usize, width: usize) -> u64 {
+    let bytes = &data[*position..*position + width];
+    *position += width;
+    match width {
+        1 => bytes[0] as u64,
+        2 => u16::from_le_bytes([bytes[0], bytes[1]]) as u64,
+        4 => u32::from_le_bytes(bytes.try_into().unwrap()) as u64,
+        _ => u64::from_le_bytes(bytes.try_into().unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: [Encoding; 3] = [Encoding::Varint, Encoding::Nibble, Encoding::RunLength];
+
+    fn scattered_graph(n: usize) -> Graph {
+        // mixes short gaps (ring), medium (stride) and long ones (multiplicative hash)
+        let edges: Vec<_> = (0..n)
+            .flat_map(|u| [(u, (u + 1) % n), (u, (u + 37) % n), (u, (u * 7919 + 3) % n)])
+            .collect();
+        Graph::undirected(n, &edges)
+    }
+
+    #[test]
+    fn test_codes_round_trip() {
+        let gaps = [
+            0,
+            1,
+            7,
+            8,
+            127,
+            128,
+            255,
+            256,
+            65_535,
+            65_536,
+            1 << 40,
+            u64::MAX,
+        ];
+        for encoding in ENCODINGS {
+            let mut data = Vec::new();
+            match encoding {
+                Encoding::Varint => gaps.iter().for_each(|&gap| write_varint(gap, &mut data)),
+                Encoding::Nibble => write_nibbles(&gaps, &mut data),
+                Encoding::RunLength => write_runs(&gaps, &mut data),
+            }
+            let mut position = 0;
+            let (mut run_left, mut run_width) = (0, 0);
+            for &gap in &gaps {
+                let decoded = match encoding {
+                    Encoding::Varint => read_varint(&data, &mut position),
+                    Encoding::Nibble => read_nibbles(&data, &mut position),
+                    Encoding::RunLength => {
+                        if run_left == 0 {
+                            run_width = 1 << (data[position] >> 6);
+                            run_left = (data[position] & 63) as usize + 1;
+                            position += 1;
+                        }
+                        run_left -= 1;
+                        read_fixed(&data, &mut position, run_width)
+                    }
+                };
+                assert_eq!(decoded, gap, "{:?}", encoding);
+            }
+        }
+
+        for value in [0, 1, -1, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_neighbors_match_csr() {
+        let graph = scattered_graph(1000);
+        let directed = Graph::directed(6, &[(5, 0), (5, 4), (0, 5), (3, 1), (3, 2)]);
+        for encoding in ENCODINGS {
+            let compressed = CompressedGraph::from_graph(&graph, encoding);
+            assert_eq!(compressed.arc_count(), graph.arc_count());
+            for u in 0..graph.vertex_count() {
+                assert_eq!(compressed.neighbors(u).len(), graph.out_degree(u));
+                assert!(compressed
+                    .neighbors(u)
+                    .eq(graph.neighbors(u).iter().copied()));
+            }
+
+            let compressed = CompressedGraph::from_graph(&directed, encoding);
+            for u in 0..6 {
+                assert!(compressed
+                    .neighbors(u)
+                    .eq(directed.neighbors(u).iter().copied()));
+                assert!(compressed
+                    .in_neighbors(u)
+                    .eq(directed.in_neighbors(u).iter().copied()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_traversals_match_csr() {
+        let graph = scattered_graph(2000);
+        let config = PageRankConfig::default();
+        let ranks = graph.pagerank_pull(&config);
+        for encoding in ENCODINGS {
+            let compressed = CompressedGraph::from_graph(&graph, encoding);
+            // same visiting order, so the very same tree
+            assert_eq!(compressed.bfs(17), graph.bfs(17));
+            assert_eq!(compressed.pagerank_pull(&config), ranks);
+            assert_eq!(compressed.par_pagerank_pull(&config), ranks);
+        }
+    }
+
+    #[test]
+    fn test_locality_compresses() {
+        // ring plus chords of length 2: every gap after the first is tiny
+        let n = 10_000;
+        let edges: Vec<_> = (0..n)
+            .flat_map(|u| [(u, (u + 1) % n), (u, (u + 2) % n)])
+            .collect();
+        let graph = Graph::undirected(n, &edges);
+        let payload = |encoding| {
+            let compressed = CompressedAdjacency::from_csr(graph.adjacency(), encoding);
+            compressed.encoded_bytes() as f64 / compressed.arc_count() as f64
+        };
+        // one byte or one nibble per edge, except at the wrap-around
+        assert!(payload(Encoding::Varint) < 1.01);
+        assert!(payload(Encoding::Nibble) < 0.51);
+        assert!(payload(Encoding::RunLength) < 1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "adjacency matrix must be square")]
+    fn test_rejects_non_square() {
+        // column 3 would be a neighbour id past the 2 vertices
+        let matrix = CompressedSparseRow::new(vec![vec![0, 1, 0, 1], vec![1, 0, 0, 0]]);
+        CompressedAdjacency::from_csr(&matrix, Encoding::Varint);
+    }
+}
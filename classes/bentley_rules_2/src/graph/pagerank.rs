@@ -0,0 +1,186 @@
+use super::{EdgeWeight, Graph};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageRankConfig {
+    pub damping: f64,
+    pub tolerance: f64, // Stop once the L1 change between iterations drops below this
+    pub max_iterations: usize,
+}
+
+impl Default for PageRankConfig {
+    fn default() -> Self {
+        Self {
+            damping: 0.85,
+            tolerance: 1e-10,
+            max_iterations: 100,
+        }
+    }
+}
+
+// Both variants compute the same iteration
+//     rank'[v] = (1 - d) / n + d * (dangling / n + sum over u -> v of rank[u] / out_degree(u))
+// and differ only in the direction the contributions travel.
+impl<W: EdgeWeight> Graph<W> {
+    /// Pull PageRank: every vertex gathers from its in-neighbours.
+    /// Writes are private to the vertex, so the parallel version needs no synchronization.
+    pub fn pagerank_pull(&self, config: &PageRankConfig) -> Vec<f64> {
+        self.pagerank_with(config, |contributions, base, next| {
+            for (v, rank) in next.iter_mut().enumerate() {
+                let gathered: f64 = self.in_neighbors(v).iter().map(|&u| contributions[u]).sum();
+                *rank = base + config.damping * gathered;
+            }
+        })
+    }
+
+    pub fn par_pagerank_pull(&self, config: &PageRankConfig) -> Vec<f64> {
+        self.pagerank_with(config, |contributions, base, next| {
+            next.par_iter_mut().enumerate().for_each(|(v, rank)| {
+                let gathered: f64 = self.in_neighbors(v).iter().map(|&u| contributions[u]).sum();
+                *rank = base + config.damping * gathered;
+            })
+        })
+    }
+
+    /// Push PageRank: every vertex scatters its contribution along its out-edges.
+    /// Needs no transpose, but in parallel two pushes can hit the same target.
+    pub fn pagerank_push(&self, config: &PageRankConfig) -> Vec<f64> {
+        self.pagerank_with(config, |contributions, base, next| {
+            next.fill(0.0);
+            for (u, &contribution) in contributions.iter().enumerate() {
+                for &v in self.neighbors(u) {
+                    next[v] += contribution;
+                }
+            }
+            next.iter_mut()
+                .for_each(|rank| *rank = base + config.damping * *rank);
+        })
+    }
+
+    /// Parallel push PageRank, contributions are accumulated with a CAS loop on the
+    /// bits of an f64 since there is no atomic float add.
+    pub fn par_pagerank_push(&self, config: &PageRankConfig) -> Vec<f64> {
+        let accumulators: Vec<AtomicU64> = (0..self.vertex_count())
+            .map(|_| AtomicU64::new(0))
+            .collect();
+
+        self.pagerank_with(config, |contributions, base, next| {
+            accumulators
+                .par_iter()
+                .for_each(|acc| acc.store(0.0f64.to_bits(), Ordering::Relaxed));
+            contributions
+                .par_iter()
+                .enumerate()
+                .for_each(|(u, &contribution)| {
+                    for &v in self.neighbors(u) {
+                        atomic_add(&accumulators[v], contribution);
+                    }
+                });
+            next.par_iter_mut()
+                .zip(&accumulators)
+                .for_each(|(rank, acc)| {
+                    *rank = base + config.damping * f64::from_bits(acc.load(Ordering::Relaxed));
+                });
+        })
+    }
+
+    fn pagerank_with<F>(&self, config: &PageRankConfig, step: F) -> Vec<f64>
+    where
+        F: FnMut(&[f64], f64, &mut [f64]),
+    {
+        power_iterations(self.vertex_count(), |u| self.out_degree(u), config, step)
+    }
+}
+
+// Runs power iterations, `step(contributions, base, next)` fills `next` from the
+// per-vertex contributions rank[u] / out_degree(u).
+pub(super) fn power_iterations<D, F>(
+    n: usize,
+    out_degree: D,
+    config: &PageRankConfig,
+    mut step: F,
+) -> Vec<f64>
+where
+    D: Fn(usize) -> usize,
+    F: FnMut(&[f64], f64, &mut [f64]),
+{
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut ranks = vec![1.0 / n as f64; n];
+    let mut next = vec![0.0; n];
+    let mut contributions = vec![0.0; n];
+
+    for _ in 0..config.max_iterations {
+        // dangling vertices have nowhere to send their rank, spread it over everyone
+        let mut dangling = 0.0;
+        for (u, (contribution, &rank)) in contributions.iter_mut().zip(&ranks).enumerate() {
+            let degree = out_degree(u);
+            if degree == 0 {
+                dangling += rank;
+                *contribution = 0.0;
+            } else {
+                *contribution = rank / degree as f64;
+            }
+        }
+
+        let base = (1.0 - config.damping) / n as f64 + config.damping * dangling / n as f64;
+        step(&contributions, base, &mut next);
+
+        let change: f64 = ranks.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        std::mem::swap(&mut ranks, &mut next);
+        if change < config.tolerance {
+            break;
+        }
+    }
+
+    ranks
+}
+
+fn atomic_add(cell: &AtomicU64, value: f64) {
+    let _ = cell.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + value).to_bits())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate::validate_pagerank;
+    use super::*;
+
+    #[test]
+    fn test_pagerank_symmetric_cycle_is_uniform() {
+        let g = Graph::undirected(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]);
+        for ranks in [
+            g.pagerank_pull(&PageRankConfig::default()),
+            g.par_pagerank_push(&PageRankConfig::default()),
+        ] {
+            for rank in ranks {
+                assert!((rank - 0.2).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pagerank_variants_agree_on_directed_graph() {
+        // 4 is dangling, 0 collects most links
+        let g = Graph::directed(5, &[(1, 0), (2, 0), (3, 0), (0, 1), (1, 2), (2, 4), (3, 4)]);
+        let config = PageRankConfig::default();
+        let pull = g.pagerank_pull(&config);
+        validate_pagerank(&g, &config, &pull, 1e-8).unwrap();
+
+        for ranks in [
+            g.par_pagerank_pull(&config),
+            g.pagerank_push(&config),
+            g.par_pagerank_push(&config),
+        ] {
+            validate_pagerank(&g, &config, &ranks, 1e-8).unwrap();
+            for (a, b) in ranks.iter().zip(&pull) {
+                assert!((a - b).abs() < 1e-9);
+            }
+        }
+        assert!(pull[0] > pull[3]);
+    }
+}
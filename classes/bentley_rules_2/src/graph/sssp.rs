@@ -0,0 +1,235 @@
+use super::{EdgeWeight, Graph};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Delta-stepping (Meyer & Sanders) sits between Dijkstra and Bellman-Ford: tentative
+// distances are kept in buckets of width `delta` and a whole bucket is relaxed at once.
+// Light edges (weight <= delta) can land back in the current bucket so they are relaxed
+// until it empties, heavy edges can't and are relaxed once per bucket.
+//
+// - delta -> 0: Dijkstra, no wasted work but no parallelism
+// - delta -> inf: Bellman-Ford, everything is one parallel bucket with lots of re-relaxing
+//
+// Weights must be non-negative and finite, unreachable vertices end at f64::INFINITY.
+// Only non-empty buckets are stored, keyed by index, so a heavy edge far past the
+// current bucket costs one map entry rather than every bucket in between.
+impl<W: EdgeWeight> Graph<W> {
+    // Both versions settle a bucket for good once it empties, which a negative edge into
+    // an earlier bucket would silently break. NaN fails the check too, and an infinite
+    // weight has no bucket to go in.
+    fn check_weights(&self) {
+        for u in 0..self.vertex_count() {
+            let (targets, weights) = self.edges(u);
+            for (&v, &w) in targets.iter().zip(weights) {
+                let w = w.weight();
+                assert!(w >= 0.0, "negative edge weight {} on ({}, {})", w, u, v);
+                assert!(w.is_finite(), "infinite edge weight on ({}, {})", u, v);
+            }
+        }
+    }
+
+    pub fn sssp_delta_stepping(&self, source: usize, delta: f64) -> Vec<f64> {
+        assert!(delta > 0.0, "delta must be positive");
+        self.check_weights();
+
+        let mut dist = vec![f64::INFINITY; self.vertex_count()];
+        dist[source] = 0.0;
+        let mut buckets = BTreeMap::from([(0, vec![source])]);
+
+        let relax = |dist: &mut [f64], buckets: &mut Buckets, v: usize, d: f64| {
+            if d < dist[v] {
+                dist[v] = d;
+                push_to_bucket(buckets, v, d, delta);
+            }
+        };
+
+        while let Some(current) = buckets.keys().next().copied() {
+            let mut settled = Vec::new();
+
+            // light edges can refill the current bucket, heavy ones only later buckets
+            while let Some(mut frontier) = buckets.remove(&current) {
+                frontier.sort_unstable();
+                frontier.dedup();
+                // a vertex whose distance improved since it was queued lives in a lower bucket now
+                frontier.retain(|&u| bucket_of(dist[u], delta) == current);
+
+                for &u in &frontier {
+                    let (targets, weights) = self.edges(u);
+                    for (&v, &w) in targets.iter().zip(weights) {
+                        let w = w.weight();
+                        if w <= delta {
+                            let d = dist[u] + w;
+                            relax(&mut dist, &mut buckets, v, d);
+                        }
+                    }
+                }
+                settled.extend(frontier);
+            }
+
+            settled.sort_unstable();
+            settled.dedup();
+            for &u in &settled {
+                let (targets, weights) = self.edges(u);
+                for (&v, &w) in targets.iter().zip(weights) {
+                    let w = w.weight();
+                    if w > delta {
+                        let d = dist[u] + w;
+                        relax(&mut dist, &mut buckets, v, d);
+                    }
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Parallel delta-stepping. A bucket's vertices are relaxed in parallel with an atomic
+    /// min on the distance bits: non-negative f64s order the same as their bit patterns,
+    /// so `AtomicU64::fetch_min` works as a float min.
+    pub fn par_sssp_delta_stepping(&self, source: usize, delta: f64) -> Vec<f64> {
+        assert!(delta > 0.0, "delta must be positive");
+        self.check_weights();
+
+        let dist: Vec<AtomicU64> = (0..self.vertex_count())
+            .map(|_| AtomicU64::new(f64::INFINITY.to_bits()))
+            .collect();
+        dist[source].store(0.0f64.to_bits(), Ordering::Relaxed);
+        let dist = &dist[..];
+        let load = |v: usize| f64::from_bits(dist[v].load(Ordering::Relaxed));
+
+        // relaxes the selected edges of every vertex in `from`, returns the improved targets
+        let relax_all = |from: &[usize], light: bool| -> Vec<usize> {
+            from.par_iter()
+                .flat_map_iter(|&u| {
+                    let du = load(u);
+                    let (targets, weights) = self.edges(u);
+                    targets
+                        .iter()
+                        .zip(weights)
+                        .filter(move |&(_, &w)| (w.weight() <= delta) == light)
+                        .filter_map(move |(&v, &w)| {
+                            let d = (du + w.weight()).to_bits();
+                            (dist[v].fetch_min(d, Ordering::Relaxed) > d).then_some(v)
+                        })
+                })
+                .collect()
+        };
+
+        let mut buckets = BTreeMap::from([(0, vec![source])]);
+        while let Some(current) = buckets.keys().next().copied() {
+            let mut settled = Vec::new();
+
+            while let Some(mut frontier) = buckets.remove(&current) {
+                frontier.par_sort_unstable();
+                frontier.dedup();
+                frontier.retain(|&u| bucket_of(load(u), delta) == current);
+
+                for v in relax_all(&frontier, true) {
+                    push_to_bucket(&mut buckets, v, load(v), delta);
+                }
+                settled.extend(frontier);
+            }
+
+            settled.par_sort_unstable();
+            settled.dedup();
+            for v in relax_all(&settled, false) {
+                push_to_bucket(&mut buckets, v, load(v), delta);
+            }
+        }
+
+        dist.iter()
+            .map(|d| f64::from_bits(d.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+type Buckets = BTreeMap<usize, Vec<usize>>;
+
+fn bucket_of(distance: f64, delta: f64) -> usize {
+    // saturates, distances past usize::MAX buckets share the last one
+    (distance / delta) as usize
+}
+
+fn push_to_bucket(buckets: &mut Buckets, vertex: usize, distance: f64, delta: f64) {
+    buckets
+        .entry(bucket_of(distance, delta))
+        .or_default()
+        .push(vertex);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate::validate_sssp;
+    use super::*;
+
+    #[test]
+    fn test_sssp_hand_built() {
+        //      1.0      1.0
+        //   0 ----- 1 ----- 2
+        //   |               |
+        //   +------ 5.0 ----+      3 unreachable
+        let g = Graph::directed_weighted(4, &[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 5.0)]);
+        for delta in [0.5, 1.0, 3.0, 100.0] {
+            let dist = g.sssp_delta_stepping(0, delta);
+            assert_eq!(dist, vec![0.0, 1.0, 2.0, f64::INFINITY]);
+            assert_eq!(g.par_sssp_delta_stepping(0, delta), dist);
+        }
+    }
+
+    #[test]
+    fn test_sssp_grid_validates() {
+        // 10x10 grid with varied weights, light and heavy edges for every delta tried
+        let side = 10;
+        let mut edges = Vec::new();
+        for r in 0..side {
+            for c in 0..side {
+                let v = r * side + c;
+                let w = ((r * 7 + c * 13) % 10) as f64 + 0.5;
+                if c + 1 < side {
+                    edges.push((v, v + 1, w));
+                }
+                if r + 1 < side {
+                    edges.push((v, v + side, 10.5 - w));
+                }
+            }
+        }
+        let g = Graph::undirected_weighted(side * side, &edges);
+
+        for delta in [0.5, 2.0, 7.0] {
+            let serial = g.sssp_delta_stepping(0, delta);
+            validate_sssp(&g, 0, &serial).unwrap();
+            validate_sssp(&g, 0, &g.par_sssp_delta_stepping(0, delta)).unwrap();
+        }
+
+        // unweighted edges have length 1, so distances are hop counts
+        let unweighted = Graph::undirected(3, &[(0, 1), (1, 2)]);
+        assert_eq!(unweighted.sssp_delta_stepping(2, 1.0), vec![2.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative edge weight -2 on (1, 2)")]
+    fn test_par_sssp_rejects_negative_weights() {
+        // 0 -> 2 directly costs 1, through 1 it costs -1
+        let g = Graph::directed_weighted(3, &[(0, 1, 1.0), (1, 2, -2.0), (0, 2, 1.0)]);
+        g.par_sssp_delta_stepping(0, 1.0);
+    }
+
+    #[test]
+    fn test_sssp_large_weight_to_delta_ratio() {
+        // bucket indices up to 1e18, far more than could ever be allocated densely
+        let g = Graph::directed_weighted(4, &[(0, 1, 1e15), (1, 2, 1e-3), (0, 3, 1e18)]);
+        for delta in [1e-3, 1.0] {
+            let dist = g.sssp_delta_stepping(0, delta);
+            assert_eq!(dist, vec![0.0, 1e15, 1e15 + 1e-3, 1e18]);
+            assert_eq!(g.par_sssp_delta_stepping(0, delta), dist);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "infinite edge weight on (0, 1)")]
+    fn test_sssp_rejects_infinite_weights() {
+        let g = Graph::directed_weighted(2, &[(0, 1, f64::INFINITY)]);
+        g.sssp_delta_stepping(0, 1.0);
+    }
+}
@@ -0,0 +1,96 @@
+use super::{EdgeWeight, Graph};
+use rayon::prelude::*;
+
+impl<W: EdgeWeight> Graph<W> {
+    /// Counts every triangle once, as u < v < w.
+    ///
+    /// Adjacency lists are sorted, so for each edge u < v the common neighbours above v are a
+    /// merge of two sorted lists: no hashing and purely sequential memory access.
+    pub fn triangle_count(&self) -> usize {
+        assert!(
+            !self.is_directed(),
+            "triangle counting needs an undirected graph"
+        );
+        (0..self.vertex_count()).map(|u| self.triangles_at(u)).sum()
+    }
+
+    pub fn par_triangle_count(&self) -> usize {
+        assert!(
+            !self.is_directed(),
+            "triangle counting needs an undirected graph"
+        );
+        (0..self.vertex_count())
+            .into_par_iter()
+            .map(|u| self.triangles_at(u))
+            .sum()
+    }
+
+    // Triangles whose smallest vertex is u.
+    fn triangles_at(&self, u: usize) -> usize {
+        let adj_u = self.neighbors(u);
+        let above_u = &adj_u[adj_u.partition_point(|&v| v <= u)..];
+
+        above_u
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let adj_v = self.neighbors(v);
+                let mut a = &above_u[i + 1..];
+                let mut b = &adj_v[adj_v.partition_point(|&w| w <= v)..];
+                let mut count = 0;
+                while let (Some(&x), Some(&y)) = (a.first(), b.first()) {
+                    count += (x == y) as usize;
+                    a = &a[(x <= y) as usize..];
+                    b = &b[(y <= x) as usize..];
+                }
+                count
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // brute force over all triples
+    fn reference(g: &Graph) -> usize {
+        let n = g.vertex_count();
+        let connected = |a: usize, b: usize| g.neighbors(a).binary_search(&b).is_ok();
+        let mut count = 0;
+        for u in 0..n {
+            for v in u + 1..n {
+                for w in v + 1..n {
+                    if connected(u, v) && connected(v, w) && connected(u, w) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_triangle_count_small_graphs() {
+        // K4 has 4 triangles
+        let k4 = Graph::undirected(4, &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+        assert_eq!(k4.triangle_count(), 4);
+        assert_eq!(k4.par_triangle_count(), 4);
+
+        // a square with one diagonal, 2 triangles
+        let square = Graph::undirected(4, &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)]);
+        assert_eq!(square.triangle_count(), 2);
+
+        let mut edges = Vec::new();
+        for u in 0..30 {
+            for v in u + 1..30 {
+                if (u * 7 + v * 3) % 5 < 2 {
+                    edges.push((u, v));
+                }
+            }
+        }
+        let g = Graph::undirected(30, &edges);
+        assert_eq!(g.triangle_count(), reference(&g));
+        assert_eq!(g.par_triangle_count(), reference(&g));
+    }
+}
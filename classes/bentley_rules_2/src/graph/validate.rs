@@ -0,0 +1,198 @@
+// Checkers for algorithm outputs, in the spirit of the Graph500 BFS validator: rather than
+// comparing against one "right answer" they check the properties every right answer has,
+// so any valid BFS tree or any tie-break of a shortest path passes.
+
+use super::bfs::UNREACHED;
+use super::{EdgeWeight, Graph, PageRankConfig};
+
+/// A BFS parent array is valid when
+/// - the source is its own parent and the parent links form a tree rooted at it
+/// - every tree edge is a graph edge
+/// - tree depths are shortest hop counts: no graph edge skips a level
+/// - exactly the vertices reachable from the source are reached
+pub fn validate_bfs_tree<W: EdgeWeight>(
+    graph: &Graph<W>,
+    source: usize,
+    parents: &[usize],
+) -> Result<(), String> {
+    let n = graph.vertex_count();
+    if parents.len() != n {
+        return Err(format!("expected {} parents, got {}", n, parents.len()));
+    }
+    if parents[source] != source {
+        return Err(format!("source {} is not its own parent", source));
+    }
+
+    let mut depths = vec![usize::MAX; n];
+    depths[source] = 0;
+    for start in 0..n {
+        if parents[start] == UNREACHED || depths[start] != usize::MAX {
+            continue;
+        }
+        // walk up until a vertex with a known depth, a walk longer than n is a cycle
+        let mut path = vec![start];
+        let mut v = start;
+        while depths[v] == usize::MAX {
+            let parent = parents[v];
+            if parent == UNREACHED {
+                return Err(format!("{} hangs off unreached vertex {}", start, v));
+            }
+            if graph.neighbors(parent).binary_search(&v).is_err() {
+                return Err(format!("tree edge {} -> {} is not in the graph", parent, v));
+            }
+            if path.len() > n {
+                return Err(format!("parent links from {} form a cycle", start));
+            }
+            v = parent;
+            path.push(v);
+        }
+        let mut depth = depths[v];
+        for &u in path.iter().rev().skip(1) {
+            depth += 1;
+            depths[u] = depth;
+        }
+    }
+
+    for u in 0..n {
+        for &v in graph.neighbors(u) {
+            match (depths[u], depths[v]) {
+                (usize::MAX, _) => {}
+                (_, usize::MAX) => {
+                    return Err(format!("{} is reachable through {} but unreached", v, u))
+                }
+                (du, dv) if dv > du + 1 => {
+                    return Err(format!(
+                        "edge {} -> {} skips from depth {} to {}",
+                        u, v, du, dv
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Ranks must sum to one and be a fixed point of the PageRank iteration.
+pub fn validate_pagerank<W: EdgeWeight>(
+    graph: &Graph<W>,
+    config: &PageRankConfig,
+    ranks: &[f64],
+    tolerance: f64,
+) -> Result<(), String> {
+    let n = graph.vertex_count();
+    let total: f64 = ranks.iter().sum();
+    if (total - 1.0).abs() > tolerance {
+        return Err(format!("ranks sum to {} instead of 1", total));
+    }
+
+    let dangling: f64 = (0..n)
+        .filter(|&u| graph.out_degree(u) == 0)
+        .map(|u| ranks[u])
+        .sum();
+    let base = (1.0 - config.damping) / n as f64 + config.damping * dangling / n as f64;
+
+    for v in 0..n {
+        let gathered: f64 = graph
+            .in_neighbors(v)
+            .iter()
+            .map(|&u| ranks[u] / graph.out_degree(u) as f64)
+            .sum();
+        let expected = base + config.damping * gathered;
+        if (ranks[v] - expected).abs() > tolerance {
+            return Err(format!(
+                "rank of {} is {}, iteration gives {}",
+                v, ranks[v], expected
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Labels must agree across every edge, and every label must be the smallest vertex of its
+/// component, which a separate BFS flood checks.
+pub fn validate_components<W: EdgeWeight>(
+    graph: &Graph<W>,
+    labels: &[usize],
+) -> Result<(), String> {
+    let n = graph.vertex_count();
+    for u in 0..n {
+        for &v in graph.neighbors(u) {
+            if labels[u] != labels[v] {
+                return Err(format!(
+                    "edge {} - {} joins labels {} and {}",
+                    u, v, labels[u], labels[v]
+                ));
+            }
+        }
+    }
+
+    let mut seen = vec![false; n];
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        // vertices are visited in id order, so start is the smallest of its component
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(u) = stack.pop() {
+            if labels[u] != start {
+                return Err(format!(
+                    "{} is labelled {}, expected {}",
+                    u, labels[u], start
+                ));
+            }
+            for &v in graph.neighbors(u).iter().chain(graph.in_neighbors(u)) {
+                if !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Distances are shortest paths when the source is at 0, no edge can shorten any distance,
+/// and every reached vertex has an incoming edge that is tight (achieves its distance).
+pub fn validate_sssp<W: EdgeWeight>(
+    graph: &Graph<W>,
+    source: usize,
+    dist: &[f64],
+) -> Result<(), String> {
+    const EPSILON: f64 = 1e-9;
+
+    if dist[source] != 0.0 {
+        return Err(format!("source distance is {}", dist[source]));
+    }
+
+    let n = graph.vertex_count();
+    let mut tight = vec![false; n];
+    tight[source] = true;
+    for u in 0..n {
+        let (targets, weights) = graph.edges(u);
+        for (&v, &w) in targets.iter().zip(weights) {
+            let through_u = dist[u] + w.weight();
+            if dist[v] > through_u + EPSILON {
+                return Err(format!(
+                    "edge {} -> {} shortens {} to {}",
+                    u, v, dist[v], through_u
+                ));
+            }
+            if dist[u].is_finite() && (dist[v] - through_u).abs() <= EPSILON {
+                tight[v] = true;
+            }
+        }
+    }
+
+    match (0..n).find(|&v| dist[v].is_finite() && !tight[v]) {
+        Some(v) => Err(format!(
+            "no edge explains the distance {} of {}",
+            dist[v], v
+        )),
+        None => Ok(()),
+    }
+}
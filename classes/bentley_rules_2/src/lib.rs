@@ -0,0 +1,11 @@
+pub mod algebra;
+pub mod block_csr;
+pub mod compressed_sparse_row;
+pub mod csr_file;
+pub mod ellpack;
+pub mod generators;
+pub mod graph;
+pub mod reorder;
+pub mod rules;
+pub mod sliced_ell;
+pub mod solvers;
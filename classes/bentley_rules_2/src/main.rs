@@ -1,5 +1,5 @@
-mod compressed_sparse_row;
-use utils::time_it;
+use bentley_rules_2::generators::erdos_renyi;
+use utils::{time_it, DenseMatrix};
 
 const SIZE: usize = 8192;
 
@@ -12,54 +12,41 @@ fn main() {
     assert_eq!(result_sparse, result_regular, "Results don't match!");
 }
 
-fn generate_matrices() -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
-    // Column matrix A (SIZE x 1)
-    let mut a: Vec<Vec<usize>> = vec![vec![0; 1]; SIZE];
-    for i in 0..SIZE {
-        a[i][0] = if i % 3 == 0 { 0 } else { (i % 10) + 1 };
-    }
+fn generate_matrices() -> (DenseMatrix<f64>, DenseMatrix<f64>) {
+    // Column matrix A (SIZE x 1), a third of it zero
+    let a = erdos_renyi(SIZE, 1, 2.0 / 3.0, 1).to_dense();
 
-    // Square matrix B (SIZE x SIZE)
-    let mut b = vec![vec![0; SIZE]; SIZE];
-    for i in 0..SIZE {
-        for j in 0..SIZE {
-            // Create a sparse matrix with ~60% zeros
-            b[i][j] = if (i + j) % 3 == 0 {
-                0
-            } else {
-                ((i + j) % 10) + 1
-            };
-        }
-    }
+    // Square matrix B (SIZE x SIZE) with ~60% zeros at random positions
+    let b = erdos_renyi(SIZE, SIZE, 0.4, 2).to_dense();
 
     (a, b)
 }
 
 // The idea of sparsity is to avoid storing and computing on zeroes.
 /// “The fastest way to compute is not to compute at all”
-fn sparsity(a: &Vec<Vec<usize>>, b: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-    let mut c = vec![vec![0; 1]; SIZE];
+fn sparsity(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>) -> DenseMatrix<f64> {
+    let mut c = DenseMatrix::new(SIZE, 1);
 
     for i in 0..SIZE {
         for k in 0..SIZE {
-            let temp_b = b[i][k];
-            let temp_a = a[k][0];
+            let temp_b = b[(i, k)];
+            let temp_a = a[(k, 0)];
 
-            if temp_a == 0 || temp_b == 0 {
+            if temp_a == 0.0 || temp_b == 0.0 {
                 continue;
             }
-            c[i][0] += temp_b * temp_a
+            c[(i, 0)] += temp_b * temp_a
         }
     }
 
     c
 }
-fn non_sparsity(a: &Vec<Vec<usize>>, b: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-    let mut c = vec![vec![0; 1]; SIZE];
+fn non_sparsity(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>) -> DenseMatrix<f64> {
+    let mut c = DenseMatrix::new(SIZE, 1);
 
     for i in 0..SIZE {
         for k in 0..SIZE {
-            c[i][0] += b[i][k] * a[k][0];
+            c[(i, 0)] += b[(i, k)] * a[(k, 0)];
         }
     }
 
@@ -0,0 +1,503 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use crate::graph::Graph;
+use rand::prelude::*;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::ops::{Add, Mul};
+
+// Vertex orderings that improve locality.
+//
+// SpMV reads x[col] for every non-zero and BFS reads the state of every neighbour. When
+// connected vertices have nearby ids those reads hit the same cache lines, when ids are
+// random every read is a miss. Renumbering costs one pass up front and is paid back by
+// every kernel that runs on the matrix afterwards.
+//
+// All orderings look at the symmetric pattern of A + Aᵀ and ignore values.
+
+/// A relabelling of `0..n`, kept in both directions so applying and undoing it are both
+/// a single gather.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permutation {
+    new_to_old: Vec<usize>,
+    old_to_new: Vec<usize>,
+}
+
+impl Permutation {
+    /// `order[new] = old`, panics when `order` isn't a permutation of `0..order.len()`.
+    pub fn from_order(order: Vec<usize>) -> Self {
+        let mut old_to_new = vec![usize::MAX; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            assert!(
+                old < order.len() && old_to_new[old] == usize::MAX,
+                "not a permutation: {} appears twice or is out of range",
+                old
+            );
+            old_to_new[old] = new;
+        }
+
+        Self {
+            new_to_old: order,
+            old_to_new,
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        Self::from_order((0..n).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.new_to_old.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new_to_old.is_empty()
+    }
+
+    pub fn new_to_old(&self) -> &[usize] {
+        &self.new_to_old
+    }
+
+    pub fn old_to_new(&self) -> &[usize] {
+        &self.old_to_new
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            new_to_old: self.old_to_new.clone(),
+            old_to_new: self.new_to_old.clone(),
+        }
+    }
+
+    /// Moves a vector into the new numbering, `result[new] = x[old]`.
+    pub fn apply<T: Copy>(&self, x: &[T]) -> Vec<T> {
+        assert_eq!(x.len(), self.len());
+        self.new_to_old.iter().map(|&old| x[old]).collect()
+    }
+
+    /// Moves a vector back into the original numbering.
+    pub fn undo<T: Copy>(&self, x: &[T]) -> Vec<T> {
+        assert_eq!(x.len(), self.len());
+        self.old_to_new.iter().map(|&new| x[new]).collect()
+    }
+}
+
+impl<T> CompressedSparseRow<T>
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    /// P·A·Pᵀ: row and column `old` both become `new`, so `(P·A·Pᵀ)(P·x) = P·(A·x)`.
+    pub fn permute_symmetric(&self, permutation: &Permutation) -> Self {
+        assert_eq!(
+            self.rows(),
+            self.cols(),
+            "symmetric permutation needs a square matrix"
+        );
+        assert_eq!(permutation.len(), self.rows());
+
+        let mut row_pointers = Vec::with_capacity(self.rows() + 1);
+        let mut column_indices = Vec::with_capacity(self.nnz());
+        let mut values = Vec::with_capacity(self.nnz());
+        let mut row: Vec<(usize, T)> = Vec::new();
+        row_pointers.push(0);
+
+        for &old in permutation.new_to_old() {
+            let (cols, vals) = self.row(old);
+            row.clear();
+            row.extend(
+                cols.iter()
+                    .zip(vals)
+                    .map(|(&col, &val)| (permutation.old_to_new()[col], val)),
+            );
+            row.sort_unstable_by_key(|&(col, _)| col);
+            for &(col, val) in &row {
+                column_indices.push(col);
+                values.push(val);
+            }
+            row_pointers.push(values.len());
+        }
+
+        Self::from_raw_parts(self.cols(), row_pointers, column_indices, values).unwrap()
+    }
+}
+
+/// max |i - j| over the non-zeros, how far from the diagonal the matrix spreads.
+pub fn bandwidth<T>(matrix: &CompressedSparseRow<T>) -> usize
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    (0..matrix.rows())
+        .flat_map(|row| matrix.row(row).0.iter().map(move |&col| row.abs_diff(col)))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Sum over rows of the distance from the first non-zero to the diagonal, the size of the
+/// envelope a skyline solver has to store.
+pub fn profile<T>(matrix: &CompressedSparseRow<T>) -> usize
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    (0..matrix.rows())
+        .map(|row| match matrix.row(row).0.first() {
+            Some(&first) if first < row => row - first,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Reverse Cuthill-McKee: BFS from a pseudo-peripheral vertex visiting neighbours by
+/// increasing degree, then reversed. Keeps every edge between adjacent BFS levels, so the
+/// bandwidth is bounded by the widest two levels.
+pub fn reverse_cuthill_mckee<T>(matrix: &CompressedSparseRow<T>) -> Permutation
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    let graph = pattern(matrix);
+    let n = graph.vertex_count();
+    let mut visited = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+
+    // one BFS per component, starting from the lowest degree vertex left
+    let mut by_degree: Vec<usize> = (0..n).collect();
+    by_degree.sort_by_key(|&v| graph.out_degree(v));
+
+    let mut neighbors = Vec::new();
+    for &seed in &by_degree {
+        if visited[seed] {
+            continue;
+        }
+        let start = pseudo_peripheral(&graph, seed);
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            neighbors.clear();
+            neighbors.extend(graph.neighbors(u).iter().copied().filter(|&v| !visited[v]));
+            neighbors.sort_by_key(|&v| graph.out_degree(v));
+            for &v in &neighbors {
+                visited[v] = true;
+                queue.push_back(v);
+            }
+        }
+    }
+
+    order.reverse();
+    Permutation::from_order(order)
+}
+
+/// Highest degree first. Hubs are touched by most rows, packing them together keeps the hot
+/// part of `x` in a few cache lines (hub sorting).
+pub fn degree_sort<T>(matrix: &CompressedSparseRow<T>) -> Permutation
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    let graph = pattern(matrix);
+    let mut order: Vec<usize> = (0..graph.vertex_count()).collect();
+    order.sort_by_key(|&v| Reverse(graph.out_degree(v)));
+    Permutation::from_order(order)
+}
+
+/// Plain BFS order from vertex 0 (and the smallest unvisited vertex of every other
+/// component), neighbours visited in id order.
+pub fn bfs_order<T>(matrix: &CompressedSparseRow<T>) -> Permutation
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    let graph = pattern(matrix);
+    let n = graph.vertex_count();
+    let mut visited = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+
+    for seed in 0..n {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        let mut queue = VecDeque::from([seed]);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &v in graph.neighbors(u) {
+                if !visited[v] {
+                    visited[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+
+    Permutation::from_order(order)
+}
+
+/// A cheap take on Gorder (Wei et al.): greedily place next the vertex sharing the most
+/// neighbours with, or linked directly to, the last `window` placed vertices, so vertices
+/// used together end up in the same cache lines.
+///
+/// The "lite" part: neighbourhoods of hubs with more than `sqrt(n)` edges are not expanded
+/// when scoring siblings, which caps the O(sum deg²) cost that makes full Gorder slow.
+pub fn gorder_lite<T>(matrix: &CompressedSparseRow<T>, window: usize) -> Permutation
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    assert!(window > 0, "window must be positive");
+
+    let graph = pattern(matrix);
+    let n = graph.vertex_count();
+    let hub_degree = (n as f64).sqrt().max(16.0) as usize;
+
+    let mut heap = UnitHeap::new(n);
+    let mut order = Vec::with_capacity(n);
+
+    let mut by_degree: Vec<usize> = (0..n).collect();
+    by_degree.sort_by_key(|&v| Reverse(graph.out_degree(v)));
+    let mut next_seed = 0;
+
+    let adjust = |heap: &mut UnitHeap, v: usize, increase: bool| {
+        for &u in graph.neighbors(v) {
+            heap.adjust(u, increase);
+            if graph.out_degree(u) > hub_degree {
+                continue;
+            }
+            for &sibling in graph.neighbors(u) {
+                heap.adjust(sibling, increase);
+            }
+        }
+    };
+
+    while order.len() < n {
+        // nothing related to the window, start over from the biggest unplaced hub
+        let v = heap.pop_max().unwrap_or_else(|| {
+            while !heap.contains(by_degree[next_seed]) {
+                next_seed += 1;
+            }
+            by_degree[next_seed]
+        });
+        heap.remove(v);
+        order.push(v);
+
+        adjust(&mut heap, v, true);
+        if order.len() > window {
+            adjust(&mut heap, order[order.len() - 1 - window], false);
+        }
+    }
+
+    Permutation::from_order(order)
+}
+
+const NONE: usize = usize::MAX;
+
+// Gorder's "unit heap": unplaced vertices live in doubly-linked lists bucketed by score.
+// Scores only ever move by one, so an update is an O(1) relink instead of a heap push.
+struct UnitHeap {
+    score: Vec<usize>,
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    head: Vec<usize>, // First vertex of every score bucket
+    top: usize,       // No bucket above this one is non-empty
+}
+
+impl UnitHeap {
+    fn new(n: usize) -> Self {
+        let mut heap = Self {
+            score: vec![0; n],
+            prev: vec![NONE; n],
+            next: vec![NONE; n],
+            head: vec![NONE; 1],
+            top: 0,
+        };
+        for v in (0..n).rev() {
+            heap.link(v);
+        }
+        heap
+    }
+
+    fn contains(&self, v: usize) -> bool {
+        self.score[v] != NONE
+    }
+
+    fn link(&mut self, v: usize) {
+        let s = self.score[v];
+        if s >= self.head.len() {
+            self.head.resize(s + 1, NONE);
+        }
+        self.prev[v] = NONE;
+        self.next[v] = self.head[s];
+        if self.head[s] != NONE {
+            self.prev[self.head[s]] = v;
+        }
+        self.head[s] = v;
+        self.top = self.top.max(s);
+    }
+
+    fn unlink(&mut self, v: usize) {
+        let (prev, next) = (self.prev[v], self.next[v]);
+        if prev == NONE {
+            self.head[self.score[v]] = next;
+        } else {
+            self.next[prev] = next;
+        }
+        if next != NONE {
+            self.prev[next] = prev;
+        }
+    }
+
+    fn adjust(&mut self, v: usize, increase: bool) {
+        if !self.contains(v) {
+            return;
+        }
+        self.unlink(v);
+        if increase {
+            self.score[v] += 1;
+        } else {
+            self.score[v] = self.score[v].saturating_sub(1);
+        }
+        self.link(v);
+    }
+
+    fn remove(&mut self, v: usize) {
+        self.unlink(v);
+        self.score[v] = NONE;
+    }
+
+    // Highest scoring vertex, None when every vertex left scores zero.
+    fn pop_max(&mut self) -> Option<usize> {
+        while self.top > 0 && self.head[self.top] == NONE {
+            self.top -= 1;
+        }
+        (self.top > 0).then(|| self.head[self.top])
+    }
+}
+
+/// Random relabelling, the control that shows what no locality at all costs.
+pub fn random_shuffle(n: usize, seed: u64) -> Permutation {
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+    Permutation::from_order(order)
+}
+
+// Symmetric, diagonal-free pattern of A + Aᵀ.
+fn pattern<T>(matrix: &CompressedSparseRow<T>) -> Graph
+where
+    T: Add<Output = T> + Mul<Output = T> + Default + Copy + PartialEq,
+{
+    assert_eq!(
+        matrix.rows(),
+        matrix.cols(),
+        "orderings need a square matrix"
+    );
+    let edges: Vec<(usize, usize)> = (0..matrix.rows())
+        .flat_map(|row| matrix.row(row).0.iter().map(move |&col| (row, col)))
+        .collect();
+    Graph::undirected(matrix.rows(), &edges)
+}
+
+// George-Liu: hop to a minimum degree vertex of the last BFS level while that increases the
+// eccentricity. Ends near one end of the component's longest path.
+fn pseudo_peripheral(graph: &Graph, start: usize) -> usize {
+    let mut current = start;
+    let (mut eccentricity, mut last_level) = bfs_last_level(graph, current);
+
+    loop {
+        let candidate = *last_level
+            .iter()
+            .min_by_key(|&&v| graph.out_degree(v))
+            .unwrap();
+        let (candidate_eccentricity, candidate_level) = bfs_last_level(graph, candidate);
+        if candidate_eccentricity <= eccentricity {
+            return current;
+        }
+        current = candidate;
+        eccentricity = candidate_eccentricity;
+        last_level = candidate_level;
+    }
+}
+
+fn bfs_last_level(graph: &Graph, start: usize) -> (usize, Vec<usize>) {
+    let mut depth = vec![usize::MAX; graph.vertex_count()];
+    depth[start] = 0;
+    let mut level = vec![start];
+    let mut eccentricity = 0;
+
+    loop {
+        let mut next = Vec::new();
+        for &u in &level {
+            for &v in graph.neighbors(u) {
+                if depth[v] == usize::MAX {
+                    depth[v] = depth[u] + 1;
+                    next.push(v);
+                }
+            }
+        }
+        if next.is_empty() {
+            return (eccentricity, level);
+        }
+        eccentricity += 1;
+        level = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // path 0 - 1 - ... - (n-1), renumbered randomly
+    fn shuffled_path(n: usize) -> CompressedSparseRow<f64> {
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push((i, i, 2.0));
+            if i + 1 < n {
+                triplets.push((i, i + 1, -1.0));
+                triplets.push((i + 1, i, -1.0));
+            }
+        }
+        let path = CompressedSparseRow::from_triplets(n, n, triplets).unwrap();
+        path.permute_symmetric(&random_shuffle(n, 7))
+    }
+
+    #[test]
+    fn test_permutation_roundtrip() {
+        let p = Permutation::from_order(vec![2, 0, 3, 1]);
+        let x = [10, 11, 12, 13];
+        assert_eq!(p.apply(&x), vec![12, 10, 13, 11]);
+        assert_eq!(p.undo(&p.apply(&x)), x.to_vec());
+        assert_eq!(p.inverse().apply(&x), p.undo(&x));
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn test_permutation_rejects_duplicates() {
+        Permutation::from_order(vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn test_permute_symmetric_commutes_with_spmv() {
+        let a = shuffled_path(50);
+        let x: Vec<f64> = (0..50).map(|i| (i * i % 17) as f64).collect();
+        let mut ax = vec![0.0; 50];
+        a.spmv(&x, &mut ax);
+
+        for p in [
+            reverse_cuthill_mckee(&a),
+            degree_sort(&a),
+            bfs_order(&a),
+            gorder_lite(&a, 5),
+            random_shuffle(50, 1),
+        ] {
+            let b = a.permute_symmetric(&p);
+            assert_eq!(b.nnz(), a.nnz());
+            let mut bx = vec![0.0; 50];
+            b.spmv(&p.apply(&x), &mut bx);
+            assert_eq!(p.undo(&bx), ax);
+        }
+    }
+
+    #[test]
+    fn test_rcm_restores_band() {
+        let a = shuffled_path(200);
+        assert!(bandwidth(&a) > 10);
+        let b = a.permute_symmetric(&reverse_cuthill_mckee(&a));
+        assert_eq!(bandwidth(&b), 1);
+        assert_eq!(profile(&b), 199);
+        assert_eq!(bandwidth(&a.permute_symmetric(&bfs_order(&a))), 2);
+    }
+}
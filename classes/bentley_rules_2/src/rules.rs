@@ -0,0 +1,140 @@
+use std::hint::black_box;
+use std::time::Instant;
+
+// Bentley's rules for writing efficient programs, one module per rule.
+//
+// Every module has the same shape:
+//
+//   pub const SIZE: usize                    benchmark problem size
+//   pub fn input(n: usize, seed: u64) -> Input
+//   pub fn naive(input: &Input) -> Output
+//   pub fn optimized(input: &Input) -> Output
+//
+// `naive` is the obvious code, `optimized` applies the rule and nothing else, and both
+// return exactly the same output (inputs are chosen so that even float code agrees bit for
+// bit). Some rules are ones the compiler already applies by itself; their speedup near 1 in
+// the `rules` table is the point.
+
+pub mod algebraic_identities;
+pub mod augmentation;
+pub mod caching;
+pub mod coarsening;
+pub mod combining_tests;
+pub mod compile_time_init;
+pub mod fast_path;
+pub mod hoisting;
+pub mod inlining;
+pub mod lazy_evaluation;
+pub mod loop_fusion;
+pub mod loop_unrolling;
+pub mod ordering_tests;
+pub mod packing;
+pub mod precomputation;
+pub mod sentinels;
+pub mod short_circuiting;
+pub mod sparsity;
+pub mod tail_recursion;
+pub mod wasted_iterations;
+
+/// Bentley's grouping of the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    DataStructures,
+    Logic,
+    Loops,
+    Functions,
+}
+
+/// One catalog entry, with the module's input type erased.
+#[derive(Clone, Copy)]
+pub struct Rule {
+    pub name: &'static str,
+    pub category: Category,
+    pub summary: &'static str,
+    pub size: usize,
+    /// Runs both versions on `input(n, seed)`, true when they return the same output.
+    pub agree: fn(n: usize, seed: u64) -> bool,
+    /// Best of `reps` seconds for (naive, optimized) on `input(n, seed)`.
+    pub time: fn(n: usize, seed: u64, reps: usize) -> (f64, f64),
+}
+
+macro_rules! rule {
+    ($module:ident, $category:ident => $summary:expr) => {
+        Rule {
+            name: stringify!($module),
+            category: Category::$category,
+            summary: $summary,
+            size: $module::SIZE,
+            agree: |n, seed| {
+                let input = $module::input(n, seed);
+                $module::naive(&input) == $module::optimized(&input)
+            },
+            time: |n, seed, reps| {
+                let input = $module::input(n, seed);
+                (
+                    best_of(reps, || $module::naive(black_box(&input))),
+                    best_of(reps, || $module::optimized(black_box(&input))),
+                )
+            },
+        }
+    };
+}
+
+/// Every rule, in the order of the lecture.
+pub fn catalog() -> Vec<Rule> {
+    vec![
+        rule!(packing, DataStructures => "one bit per flag instead of one byte"),
+        rule!(augmentation, DataStructures => "tail pointer makes append O(1)"),
+        rule!(precomputation, DataStructures => "Pascal's triangle instead of products"),
+        rule!(compile_time_init, DataStructures => "the table is built by rustc"),
+        rule!(caching, DataStructures => "remember the last result"),
+        rule!(lazy_evaluation, DataStructures => "compute only until the answer is known"),
+        rule!(sparsity, DataStructures => "skip the zeroes, CSR instead of dense"),
+        rule!(algebraic_identities, Logic => "compare squares instead of roots"),
+        rule!(short_circuiting, Logic => "stop once the limit is exceeded"),
+        rule!(ordering_tests, Logic => "cheap, selective test first"),
+        rule!(fast_path, Logic => "bounding box before the distance"),
+        rule!(combining_tests, Logic => "one table lookup instead of nested ifs"),
+        rule!(hoisting, Loops => "loop-invariant total out of the loop"),
+        rule!(sentinels, Loops => "sentinels remove the bounds tests of a merge"),
+        rule!(loop_unrolling, Loops => "eight independent accumulators"),
+        rule!(loop_fusion, Loops => "min and max in one pass"),
+        rule!(wasted_iterations, Loops => "visit only the upper triangle"),
+        rule!(inlining, Functions => "inlined helper lets the loop vectorize"),
+        rule!(tail_recursion, Functions => "loop on one side of the partition"),
+        rule!(coarsening, Functions => "insertion sort below 16 elements"),
+    ]
+}
+
+fn best_of<T>(reps: usize, mut f: impl FnMut() -> T) -> f64 {
+    (0..reps.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naive_and_optimized_agree() {
+        for rule in catalog() {
+            // small and odd sizes hit the remainder loops and base cases
+            for (n, seed) in [(0, 1), (1, 2), (17, 3), (100, 4), (333, 5)] {
+                assert!((rule.agree)(n, seed), "{} differs for n = {}", rule.name, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalog_names_are_unique() {
+        let mut names: Vec<_> = catalog().iter().map(|rule| rule.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), catalog().len());
+    }
+}
@@ -0,0 +1,64 @@
+use rand::prelude::*;
+
+// Exploit algebraic identities: replace expensive logical expressions with algebraic
+// equivalents. sqrt(u) <= v exactly when u <= v² (for v >= 0), so a collision test needs no
+// square root. Coordinates are small integers, which keeps both tests exact and equal.
+
+pub const SIZE: usize = 3_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ball {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub r: f64,
+}
+
+pub type Input = Vec<Ball>;
+
+pub(super) fn square(x: f64) -> f64 {
+    x * x
+}
+
+/// `n` balls on an integer grid in a 1000³ box with radius up to 40.
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut coordinate = |range| rng.gen_range(range) as f64;
+    (0..n)
+        .map(|_| Ball {
+            x: coordinate(0..1000),
+            y: coordinate(0..1000),
+            z: coordinate(0..1000),
+            r: coordinate(1..40),
+        })
+        .collect()
+}
+
+pub(super) fn collides(a: &Ball, b: &Ball) -> bool {
+    let d = (square(a.x - b.x) + square(a.y - b.y) + square(a.z - b.z)).sqrt();
+    d <= a.r + b.r
+}
+
+fn collides_squared(a: &Ball, b: &Ball) -> bool {
+    let d_squared = square(a.x - b.x) + square(a.y - b.y) + square(a.z - b.z);
+    d_squared <= square(a.r + b.r)
+}
+
+pub(super) fn count_pairs(balls: &[Ball], test: impl Fn(&Ball, &Ball) -> bool) -> usize {
+    let mut count = 0;
+    for (i, a) in balls.iter().enumerate() {
+        for b in &balls[i + 1..] {
+            count += test(a, b) as usize;
+        }
+    }
+    count
+}
+
+/// Number of colliding pairs.
+pub fn naive(balls: &Input) -> usize {
+    count_pairs(balls, collides)
+}
+
+pub fn optimized(balls: &Input) -> usize {
+    count_pairs(balls, collides_squared)
+}
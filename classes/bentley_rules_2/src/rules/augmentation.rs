@@ -0,0 +1,79 @@
+// Augmentation: add information to a data structure to make common operations cheaper. A
+// singly linked list that only knows its head walks the whole list on every append;
+// remembering the tail as well makes append O(1).
+
+pub const SIZE: usize = 20_000;
+
+pub type Input = Vec<u32>;
+
+pub fn input(n: usize, _seed: u64) -> Input {
+    (0..n as u32).collect()
+}
+
+const NIL: usize = usize::MAX;
+
+// Nodes live in a vector, `next` links them by index.
+#[derive(Default)]
+struct List {
+    values: Vec<u32>,
+    next: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>, // only kept up to date by append_fast
+}
+
+impl List {
+    fn push_node(&mut self, value: u32) -> usize {
+        self.values.push(value);
+        self.next.push(NIL);
+        self.values.len() - 1
+    }
+
+    fn append_slow(&mut self, value: u32) {
+        let node = self.push_node(value);
+        match self.head {
+            None => self.head = Some(node),
+            Some(mut last) => {
+                while self.next[last] != NIL {
+                    last = self.next[last];
+                }
+                self.next[last] = node;
+            }
+        }
+    }
+
+    fn append_fast(&mut self, value: u32) {
+        let node = self.push_node(value);
+        match self.tail {
+            None => self.head = Some(node),
+            Some(last) => self.next[last] = node,
+        }
+        self.tail = Some(node);
+    }
+
+    fn to_vec(&self) -> Vec<u32> {
+        let mut result = Vec::with_capacity(self.values.len());
+        let mut node = self.head.unwrap_or(NIL);
+        while node != NIL {
+            result.push(self.values[node]);
+            node = self.next[node];
+        }
+        result
+    }
+}
+
+/// Builds a list by appending every value, returns it in list order.
+pub fn naive(values: &Input) -> Vec<u32> {
+    let mut list = List::default();
+    for &value in values {
+        list.append_slow(value);
+    }
+    list.to_vec()
+}
+
+pub fn optimized(values: &Input) -> Vec<u32> {
+    let mut list = List::default();
+    for &value in values {
+        list.append_fast(value);
+    }
+    list.to_vec()
+}
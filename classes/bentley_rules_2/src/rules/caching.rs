@@ -0,0 +1,45 @@
+use rand::prelude::*;
+
+// Caching: store results that have been accessed recently so the program need not compute
+// them again. Queries come in runs of equal arguments (think of a renderer asking for the
+// same distance for every pixel of a span), so remembering just the last one hits often.
+
+pub const SIZE: usize = 2_000_000;
+
+pub type Input = Vec<(f64, f64)>;
+
+/// `n` points in runs of about 8 repeats.
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut points = Vec::with_capacity(n);
+    while points.len() < n {
+        let point = (rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0));
+        let run = rng.gen_range(1..16).min(n - points.len());
+        points.extend(std::iter::repeat_n(point, run));
+    }
+    points
+}
+
+// deliberately not cheap: a polar angle plus a norm
+fn expensive(x: f64, y: f64) -> f64 {
+    x.hypot(y) + y.atan2(x).sin()
+}
+
+pub fn naive(points: &Input) -> Vec<f64> {
+    points.iter().map(|&(x, y)| expensive(x, y)).collect()
+}
+
+pub fn optimized(points: &Input) -> Vec<f64> {
+    let mut cached: Option<((f64, f64), f64)> = None;
+    points
+        .iter()
+        .map(|&point| match cached {
+            Some((last, result)) if last == point => result,
+            _ => {
+                let result = expensive(point.0, point.1);
+                cached = Some((point, result));
+                result
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,51 @@
+use super::tail_recursion::{self, partition, quicksort};
+
+// Coarsening recursion: increase the size of the base case and handle it with more
+// efficient code that avoids function-call overhead. Below a few dozen elements insertion
+// sort beats partitioning, and most calls of a quicksort are on tiny slices.
+
+pub const SIZE: usize = 5_000_000;
+
+const BASE: usize = 16;
+
+pub type Input = Vec<u32>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    tail_recursion::input(n, seed)
+}
+
+fn insertion_sort(a: &mut [u32]) {
+    for i in 1..a.len() {
+        let value = a[i];
+        let mut j = i;
+        while j > 0 && a[j - 1] > value {
+            a[j] = a[j - 1];
+            j -= 1;
+        }
+        a[j] = value;
+    }
+}
+
+fn quicksort_coarse(a: &mut [u32]) {
+    if a.len() <= BASE {
+        insertion_sort(a);
+        return;
+    }
+    let p = partition(a);
+    let (left, right) = a.split_at_mut(p);
+    quicksort_coarse(left);
+    quicksort_coarse(&mut right[1..]);
+}
+
+/// Sorted copy.
+pub fn naive(values: &Input) -> Vec<u32> {
+    let mut a = values.clone();
+    quicksort(&mut a);
+    a
+}
+
+pub fn optimized(values: &Input) -> Vec<u32> {
+    let mut a = values.clone();
+    quicksort_coarse(&mut a);
+    a
+}
@@ -0,0 +1,74 @@
+use rand::prelude::*;
+
+// Combining tests: replace a sequence of tests with one test or a switch. A full adder
+// written as nested ifs over its three input bits takes three unpredictable branches per
+// bit; packing the bits into one index turns it into a table lookup.
+//
+// Modern compilers already combine such trees into branch-free selects and vectorize them,
+// while the gather from the table doesn't vectorize, so here the lookup loses. The rule
+// still holds wherever the tests can't be if-converted.
+
+pub const SIZE: usize = 10_000_000;
+
+pub type Input = Vec<[u8; 3]>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            [
+                rng.gen_range(0..2),
+                rng.gen_range(0..2),
+                rng.gen_range(0..2),
+            ]
+        })
+        .collect()
+}
+
+/// (sum, carry) of a full adder for every triple of bits.
+pub fn naive(bits: &Input) -> Vec<(u8, u8)> {
+    bits.iter()
+        .map(|&[a, b, c]| {
+            if a == 0 {
+                if b == 0 {
+                    if c == 0 {
+                        (0, 0)
+                    } else {
+                        (1, 0)
+                    }
+                } else if c == 0 {
+                    (1, 0)
+                } else {
+                    (0, 1)
+                }
+            } else if b == 0 {
+                if c == 0 {
+                    (1, 0)
+                } else {
+                    (0, 1)
+                }
+            } else if c == 0 {
+                (0, 1)
+            } else {
+                (1, 1)
+            }
+        })
+        .collect()
+}
+
+const ADDER: [(u8, u8); 8] = [
+    (0, 0),
+    (1, 0),
+    (1, 0),
+    (0, 1),
+    (1, 0),
+    (0, 1),
+    (0, 1),
+    (1, 1),
+];
+
+pub fn optimized(bits: &Input) -> Vec<(u8, u8)> {
+    bits.iter()
+        .map(|&[a, b, c]| ADDER[(a << 2 | b << 1 | c) as usize])
+        .collect()
+}
@@ -0,0 +1,51 @@
+use super::precomputation::{self, MAX_N};
+
+// Compile-time initialization: store the values of constants during compilation, saving
+// work at execution time. Same Pascal's triangle as in `precomputation`, but built by a
+// `const fn` so the binary ships the finished table. It only pays when the table is needed
+// by short-lived calls, so queries come in small batches here.
+
+pub const SIZE: usize = 2_000;
+
+const BATCH: usize = 16;
+
+pub type Input = Vec<(usize, usize)>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    precomputation::input(n, seed)
+}
+
+const fn pascal_triangle() -> [[u64; MAX_N + 1]; MAX_N + 1] {
+    let mut table = [[0u64; MAX_N + 1]; MAX_N + 1];
+    let mut n = 0;
+    while n <= MAX_N {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+static PASCAL: [[u64; MAX_N + 1]; MAX_N + 1] = pascal_triangle();
+
+/// Sum of C(n, k), answering BATCH queries per call of a lookup routine.
+pub fn naive(queries: &Input) -> u64 {
+    queries.chunks(BATCH).fold(0, |sum, batch| {
+        let table = precomputation::pascal_triangle();
+        batch
+            .iter()
+            .fold(sum, |sum, &(n, k)| sum.wrapping_add(table[n][k]))
+    })
+}
+
+pub fn optimized(queries: &Input) -> u64 {
+    queries.chunks(BATCH).fold(0, |sum, batch| {
+        batch
+            .iter()
+            .fold(sum, |sum, &(n, k)| sum.wrapping_add(PASCAL[n][k]))
+    })
+}
@@ -0,0 +1,34 @@
+use super::algebraic_identities::{self, collides, count_pairs, Ball};
+
+// Creating a fast path: most pairs of balls are far apart along some axis. Checking that
+// first rejects them with a subtraction and a compare, and only the few close pairs pay for
+// the full distance.
+//
+// Measure before keeping one: the plain loop has no branches, so LLVM vectorizes it, square
+// roots included, and on x86_64 that beats the early exits by about 2x. A fast path pays
+// off when the slow path can't be vectorized away, an actual call or a memory access.
+
+pub const SIZE: usize = 3_000;
+
+pub type Input = Vec<Ball>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    algebraic_identities::input(n, seed)
+}
+
+fn collides_fast(a: &Ball, b: &Ball) -> bool {
+    let reach = a.r + b.r;
+    if (a.x - b.x).abs() > reach || (a.y - b.y).abs() > reach || (a.z - b.z).abs() > reach {
+        return false;
+    }
+    collides(a, b)
+}
+
+/// Number of colliding pairs.
+pub fn naive(balls: &Input) -> usize {
+    count_pairs(balls, collides)
+}
+
+pub fn optimized(balls: &Input) -> usize {
+    count_pairs(balls, collides_fast)
+}
@@ -0,0 +1,25 @@
+use rand::prelude::*;
+
+// Hoisting: avoid recomputing loop-invariant code each time through the body of a loop.
+// LLVM hoists cheap pure expressions by itself (the `exp(sqrt(π/2))` factor of
+// c/hoisting.c costs nothing at -O3), but not a whole loop like the total here, so
+// normalizing by it inside the loop is quadratic.
+
+pub const SIZE: usize = 20_000;
+
+pub type Input = Vec<f64>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(0.0..1.0)).collect()
+}
+
+/// Every value divided by the sum of all values.
+pub fn naive(x: &Input) -> Vec<f64> {
+    (0..x.len()).map(|i| x[i] / x.iter().sum::<f64>()).collect()
+}
+
+pub fn optimized(x: &Input) -> Vec<f64> {
+    let total: f64 = x.iter().sum();
+    x.iter().map(|&value| value / total).collect()
+}
@@ -0,0 +1,33 @@
+use rand::prelude::*;
+
+// Inlining: avoid the overhead of a function call by replacing a call with the body of the
+// function itself. The call costs little on its own; what matters is that a loop containing
+// an opaque call can't be vectorized.
+
+pub const SIZE: usize = 20_000_000;
+
+pub type Input = Vec<i64>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(-1000..1000)).collect()
+}
+
+#[inline(never)]
+fn square_call(x: i64) -> i64 {
+    x * x
+}
+
+#[inline(always)]
+fn square_inline(x: i64) -> i64 {
+    x * x
+}
+
+/// Sum of squares.
+pub fn naive(x: &Input) -> i64 {
+    x.iter().map(|&value| square_call(value)).sum()
+}
+
+pub fn optimized(x: &Input) -> i64 {
+    x.iter().map(|&value| square_inline(value)).sum()
+}
@@ -0,0 +1,43 @@
+use rand::prelude::*;
+
+// Lazy evaluation: don't perform a calculation until its result is needed. Mapping the whole
+// input through an expensive function and then searching the result does all the work even
+// though the answer is usually found early; an iterator chain computes on demand.
+
+pub const SIZE: usize = 200_000;
+
+pub type Input = Vec<u64>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(1..1_000_000)).collect()
+}
+
+// Collatz stopping time, a few hundred steps at most for these inputs
+fn stopping_time(mut x: u64) -> u32 {
+    let mut steps = 0;
+    while x != 1 {
+        x = if x.is_multiple_of(2) {
+            x / 2
+        } else {
+            3 * x + 1
+        };
+        steps += 1;
+    }
+    steps
+}
+
+const THRESHOLD: u32 = 300;
+
+/// Index of the first number that takes more than THRESHOLD steps to reach 1.
+pub fn naive(numbers: &Input) -> Option<usize> {
+    let times: Vec<u32> = numbers.iter().map(|&x| stopping_time(x)).collect();
+    times.iter().position(|&t| t > THRESHOLD)
+}
+
+pub fn optimized(numbers: &Input) -> Option<usize> {
+    numbers
+        .iter()
+        .map(|&x| stopping_time(x))
+        .position(|t| t > THRESHOLD)
+}
@@ -0,0 +1,47 @@
+use rand::prelude::*;
+
+// Loop fusion (jamming): combine multiple loops over the same index range into a single
+// loop, saving the overhead of loop control and, more importantly, a second pass over
+// arrays that don't fit in cache.
+
+pub const SIZE: usize = 10_000_000;
+
+pub struct Input {
+    a: Vec<i32>,
+    b: Vec<i32>,
+}
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut values = || (0..n).map(|_| rng.gen()).collect();
+    Input {
+        a: values(),
+        b: values(),
+    }
+}
+
+/// Element-wise (min, max) of a and b.
+pub fn naive(input: &Input) -> (Vec<i32>, Vec<i32>) {
+    let n = input.a.len();
+    let mut min = vec![0; n];
+    let mut max = vec![0; n];
+    for (i, out) in min.iter_mut().enumerate() {
+        *out = input.a[i].min(input.b[i]);
+    }
+    for (i, out) in max.iter_mut().enumerate() {
+        *out = input.a[i].max(input.b[i]);
+    }
+    (min, max)
+}
+
+pub fn optimized(input: &Input) -> (Vec<i32>, Vec<i32>) {
+    let n = input.a.len();
+    let mut min = vec![0; n];
+    let mut max = vec![0; n];
+    for (i, (low, high)) in min.iter_mut().zip(&mut max).enumerate() {
+        let (a, b) = (input.a[i], input.b[i]);
+        *low = a.min(b);
+        *high = a.max(b);
+    }
+    (min, max)
+}
@@ -0,0 +1,39 @@
+use rand::prelude::*;
+
+// Loop unrolling: save work by combining several consecutive iterations into a single one.
+// A float maximum over a slice is one long dependency chain the compiler may not break up;
+// eight accumulators make eight independent chains. Max is exact, so the order doesn't
+// change the result.
+
+pub const SIZE: usize = 20_000_000;
+
+pub type Input = Vec<f64>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(-1e6..1e6)).collect()
+}
+
+/// Largest element, -inf for an empty slice.
+pub fn naive(x: &Input) -> f64 {
+    let mut max = f64::NEG_INFINITY;
+    for &value in x {
+        max = max.max(value);
+    }
+    max
+}
+
+pub fn optimized(x: &Input) -> f64 {
+    let mut max = [f64::NEG_INFINITY; 8];
+    let chunks = x.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        for lane in 0..8 {
+            max[lane] = max[lane].max(chunk[lane]);
+        }
+    }
+    for &value in tail {
+        max[0] = max[0].max(value);
+    }
+    max.into_iter().fold(f64::NEG_INFINITY, f64::max)
+}
@@ -0,0 +1,44 @@
+use rand::prelude::*;
+
+// Ordering tests: in a sequence of logical tests, perform those that are more often
+// successful (for ||) or less often successful (for &&) and cheaper first. A primality test
+// is a loop the compiler won't swap with the modulo behind it, so the order written is the
+// order run.
+
+pub const SIZE: usize = 200_000;
+
+pub type Input = Vec<u64>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(0..10_000_000)).collect()
+}
+
+fn is_prime(x: u64) -> bool {
+    if x < 2 {
+        return false;
+    }
+    let mut d = 2;
+    while d * d <= x {
+        if x.is_multiple_of(d) {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+/// How many numbers are primes that are 3 mod 4, with 3 mod 4 true a quarter of the time.
+pub fn naive(numbers: &Input) -> usize {
+    numbers
+        .iter()
+        .filter(|&&x| is_prime(x) && x % 4 == 3)
+        .count()
+}
+
+pub fn optimized(numbers: &Input) -> usize {
+    numbers
+        .iter()
+        .filter(|&&x| x % 4 == 3 && is_prime(x))
+        .count()
+}
@@ -0,0 +1,55 @@
+// Packing: store several values per machine word. A sieve with one bool per number reads
+// eight bits of memory per bit of information; a bitset over the odd numbers only is 16x
+// smaller, so far more of it stays in cache while the sieve sweeps it.
+
+pub const SIZE: usize = 50_000_000;
+
+pub type Input = usize;
+
+pub fn input(n: usize, _seed: u64) -> Input {
+    n
+}
+
+/// Number of primes below `n`.
+pub fn naive(&n: &Input) -> usize {
+    if n < 3 {
+        return 0;
+    }
+    let mut composite = vec![false; n];
+    let mut count = 0;
+    for i in 2..n {
+        if composite[i] {
+            continue;
+        }
+        count += 1;
+        for multiple in (i * i..n).step_by(i) {
+            composite[multiple] = true;
+        }
+    }
+    count
+}
+
+pub fn optimized(&n: &Input) -> usize {
+    if n < 3 {
+        return 0;
+    }
+    // bit k stands for the odd number 2k + 1
+    let odds = n / 2;
+    let mut composite = vec![0u64; odds.div_ceil(64)];
+    composite[0] |= 1; // 1 is not prime
+    let mut k = 1;
+    while (2 * k + 1) * (2 * k + 1) < n {
+        if composite[k / 64] >> (k % 64) & 1 == 0 {
+            let p = 2 * k + 1;
+            // odd multiples of p from p², every 2p
+            for multiple in ((p * p) / 2..odds).step_by(p) {
+                composite[multiple / 64] |= 1 << (multiple % 64);
+            }
+        }
+        k += 1;
+    }
+
+    let mut primes: usize = composite.iter().map(|w| w.count_zeros() as usize).sum();
+    primes -= composite.len() * 64 - odds; // padding bits of the last word
+    primes + 1 // 2
+}
@@ -0,0 +1,58 @@
+use rand::prelude::*;
+
+// Precomputation: perform calculations in advance to avoid doing them at mission-critical
+// times. Each binomial coefficient costs k multiplications and divisions; a Pascal's triangle
+// built once answers every later query with one load.
+
+pub const SIZE: usize = 1_000_000;
+
+/// Largest n whose coefficients all fit the multiplicative formula in a u64.
+pub const MAX_N: usize = 60;
+
+pub type Input = Vec<(usize, usize)>;
+
+/// `n` queries (n, k) with k <= n <= MAX_N.
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            let top = rng.gen_range(0..=MAX_N);
+            (top, rng.gen_range(0..=top))
+        })
+        .collect()
+}
+
+// C(n, k) = n/1 · (n-1)/2 · ... , every partial product is itself a binomial coefficient
+pub(super) fn choose(n: usize, k: usize) -> u64 {
+    let k = k.min(n - k);
+    let mut c = 1u64;
+    for i in 0..k {
+        c = c * (n - i) as u64 / (i + 1) as u64;
+    }
+    c
+}
+
+pub(super) fn pascal_triangle() -> Vec<[u64; MAX_N + 1]> {
+    let mut table = vec![[0u64; MAX_N + 1]; MAX_N + 1];
+    for n in 0..=MAX_N {
+        table[n][0] = 1;
+        for k in 1..=n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+        }
+    }
+    table
+}
+
+/// Sum of C(n, k) over all queries.
+pub fn naive(queries: &Input) -> u64 {
+    queries
+        .iter()
+        .fold(0, |sum, &(n, k)| sum.wrapping_add(choose(n, k)))
+}
+
+pub fn optimized(queries: &Input) -> u64 {
+    let table = pascal_triangle();
+    queries
+        .iter()
+        .fold(0, |sum, &(n, k)| sum.wrapping_add(table[n][k]))
+}
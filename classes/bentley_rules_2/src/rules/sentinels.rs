@@ -0,0 +1,67 @@
+use rand::prelude::*;
+
+// Sentinels: special dummy values placed in a data structure to simplify the logic of
+// boundary conditions. Ending both sorted runs with u32::MAX means the merge can never run
+// off either one, so the loop tests neither index and needs no tail loops.
+
+pub const SIZE: usize = 5_000_000;
+
+/// Two sorted runs, each followed by a u32::MAX sentinel that is not part of the data.
+pub struct Input {
+    a: Vec<u32>,
+    b: Vec<u32>,
+}
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut run = |len| {
+        let mut run: Vec<u32> = (0..len).map(|_| rng.gen_range(0..u32::MAX)).collect();
+        run.sort_unstable();
+        run.push(u32::MAX);
+        run
+    };
+    Input {
+        a: run(n / 2),
+        b: run(n - n / 2),
+    }
+}
+
+/// Both runs merged into one sorted vector.
+pub fn naive(input: &Input) -> Vec<u32> {
+    let a = &input.a[..input.a.len() - 1];
+    let b = &input.b[..input.b.len() - 1];
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] <= b[j] {
+            out.push(a[i]);
+            i += 1;
+        } else {
+            out.push(b[j]);
+            j += 1;
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+    out
+}
+
+pub fn optimized(input: &Input) -> Vec<u32> {
+    let (a, b) = (&input.a, &input.b);
+    let len = a.len() + b.len() - 2;
+    let mut out = Vec::with_capacity(len);
+    let (mut i, mut j) = (0, 0);
+    for _ in 0..len {
+        // i and j never pass their sentinel: data values are below u32::MAX and only len
+        // elements are taken, so a sentinel is compared but never consumed
+        let (x, y) = unsafe { (*a.get_unchecked(i), *b.get_unchecked(j)) };
+        if x <= y {
+            out.push(x);
+            i += 1;
+        } else {
+            out.push(y);
+            j += 1;
+        }
+    }
+    out
+}
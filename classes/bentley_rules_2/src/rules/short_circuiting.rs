@@ -0,0 +1,38 @@
+use rand::prelude::*;
+
+// Short-circuiting: when testing a condition, stop evaluating as soon as the answer is
+// known. Whether a sum of non-negative numbers exceeds a limit is decided the moment the
+// running sum passes it.
+
+pub const SIZE: usize = 10_000_000;
+
+pub struct Input {
+    values: Vec<u32>,
+    limit: u64,
+}
+
+/// The limit is reached about a tenth of the way in.
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Input {
+        values: (0..n).map(|_| rng.gen_range(0..1000)).collect(),
+        limit: 50 * n as u64,
+    }
+}
+
+/// Is the sum of all values above the limit?
+pub fn naive(input: &Input) -> bool {
+    let sum: u64 = input.values.iter().map(|&v| v as u64).sum();
+    sum > input.limit
+}
+
+pub fn optimized(input: &Input) -> bool {
+    let mut sum = 0u64;
+    for &v in &input.values {
+        sum += v as u64;
+        if sum > input.limit {
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,45 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use crate::generators::erdos_renyi;
+use rand::prelude::*;
+use utils::DenseMatrix;
+
+// Sparsity: the fastest way to compute is not to compute at all. A matrix-vector product
+// over a 5% dense matrix spends 95% of the dense loop multiplying by zero; CSR stores and
+// touches only the non-zeros. Both sum a row in column order, so the results are identical.
+
+pub const SIZE: usize = 4_000;
+
+pub struct Input {
+    dense: DenseMatrix<f64>,
+    sparse: CompressedSparseRow<f64>,
+    x: Vec<f64>,
+}
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let sparse = erdos_renyi(n, n, 0.05, seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    Input {
+        dense: sparse.to_dense(),
+        sparse,
+        x: (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect(),
+    }
+}
+
+/// y = A·x
+pub fn naive(input: &Input) -> Vec<f64> {
+    let a = &input.dense;
+    (0..a.rows())
+        .map(|row| {
+            a.row_slice(row)
+                .iter()
+                .zip(&input.x)
+                .fold(0.0, |sum, (&a, &x)| sum + a * x)
+        })
+        .collect()
+}
+
+pub fn optimized(input: &Input) -> Vec<f64> {
+    let mut y = vec![0.0; input.sparse.rows()];
+    input.sparse.spmv(&input.x, &mut y);
+    y
+}
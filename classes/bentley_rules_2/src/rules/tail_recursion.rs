@@ -0,0 +1,69 @@
+use rand::prelude::*;
+
+// Tail-recursion elimination: replace a recursive call at the end of a function with a
+// branch. Rust doesn't guarantee it, so quicksort keeps both recursive calls unless the
+// second is turned into a loop by hand. Recursing into the smaller side also bounds the
+// stack depth by log n.
+
+pub const SIZE: usize = 5_000_000;
+
+pub type Input = Vec<u32>;
+
+pub fn input(n: usize, seed: u64) -> Input {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen()).collect()
+}
+
+// Lomuto partition around the middle element, returns its final position
+pub(super) fn partition(a: &mut [u32]) -> usize {
+    let last = a.len() - 1;
+    a.swap(a.len() / 2, last);
+    let pivot = a[last];
+    let mut store = 0;
+    for i in 0..last {
+        if a[i] < pivot {
+            a.swap(i, store);
+            store += 1;
+        }
+    }
+    a.swap(store, last);
+    store
+}
+
+pub(super) fn quicksort(a: &mut [u32]) {
+    if a.len() <= 1 {
+        return;
+    }
+    let p = partition(a);
+    let (left, right) = a.split_at_mut(p);
+    quicksort(left);
+    quicksort(&mut right[1..]);
+}
+
+fn quicksort_loop(mut a: &mut [u32]) {
+    while a.len() > 1 {
+        let p = partition(a);
+        let (left, right) = a.split_at_mut(p);
+        let right = &mut right[1..];
+        if left.len() < right.len() {
+            quicksort_loop(left);
+            a = right;
+        } else {
+            quicksort_loop(right);
+            a = left;
+        }
+    }
+}
+
+/// Sorted copy.
+pub fn naive(values: &Input) -> Vec<u32> {
+    let mut a = values.clone();
+    quicksort(&mut a);
+    a
+}
+
+pub fn optimized(values: &Input) -> Vec<u32> {
+    let mut a = values.clone();
+    quicksort_loop(&mut a);
+    a
+}
@@ -0,0 +1,742 @@
+use crate::compressed_sparse_row::CompressedSparseRow;
+use rayon::prelude::*;
+use std::fmt;
+
+// Iterative and triangular solvers for Ax = b on CSR matrices.
+//
+// Krylov methods only touch A through SpMV, so they inherit all of CSR's savings: one
+// iteration costs O(nnz) instead of the O(n²) of a dense matrix-vector product, and nothing
+// ever fills in the zeroes the way a direct factorization would.
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolverConfig {
+    pub tolerance: f64, // Stop once ||b - Ax|| / ||b|| drops below this
+    pub max_iterations: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-10,
+            max_iterations: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub x: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+    pub residual_history: Vec<f64>, // Relative residual before the first and after every iteration
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolverError {
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    NotTriangular {
+        row: usize,
+        col: usize,
+    },
+    ZeroPivot {
+        row: usize,
+    },
+    NotPositiveDefinite {
+        row: usize,
+        pivot: f64,
+    },
+    Breakdown {
+        iteration: usize,
+        quantity: &'static str,
+        value: f64,
+    },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::NotSquare { rows, cols } => {
+                write!(f, "matrix is {}x{}, expected a square matrix", rows, cols)
+            }
+            SolverError::NotTriangular { row, col } => {
+                write!(
+                    f,
+                    "entry ({}, {}) is on the wrong side of the diagonal",
+                    row, col
+                )
+            }
+            SolverError::ZeroPivot { row } => write!(f, "zero or missing diagonal in row {}", row),
+            SolverError::NotPositiveDefinite { row, pivot } => {
+                write!(f, "pivot {} in row {} is not positive", pivot, row)
+            }
+            SolverError::Breakdown {
+                iteration,
+                quantity,
+                value,
+            } => write!(
+                f,
+                "breakdown in iteration {}: {} is {}",
+                iteration, quantity, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// z = M⁻¹·r for some cheap approximation M of A.
+pub trait Preconditioner {
+    fn apply(&self, r: &[f64], z: &mut [f64]);
+}
+
+/// No preconditioning, M = I.
+pub struct Identity;
+
+impl Preconditioner for Identity {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        z.copy_from_slice(r);
+    }
+}
+
+/// M = diag(A). Costs one multiply per entry, fixes badly scaled rows.
+pub struct Jacobi {
+    inverse_diagonal: Vec<f64>,
+}
+
+impl Jacobi {
+    pub fn new(a: &CompressedSparseRow<f64>) -> Result<Self, SolverError> {
+        check_square(a)?;
+        let inverse_diagonal = (0..a.rows())
+            .map(|row| match a.get(row, row) {
+                0.0 => Err(SolverError::ZeroPivot { row }),
+                diagonal => Ok(1.0 / diagonal),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { inverse_diagonal })
+    }
+}
+
+impl Preconditioner for Jacobi {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        for ((z, &r), &d) in z.iter_mut().zip(r).zip(&self.inverse_diagonal) {
+            *z = r * d;
+        }
+    }
+}
+
+/// IC(0): L·Lᵀ ≈ A where L keeps exactly the sparsity of the lower triangle of A.
+///
+/// Dropping every fill-in entry keeps the factor as cheap to store and apply as A itself,
+/// applying it is one forward and one backward level-scheduled triangular solve.
+pub struct IncompleteCholesky {
+    lower: CompressedSparseRow<f64>,
+    upper: CompressedSparseRow<f64>, // Lᵀ
+    lower_levels: LevelSchedule,
+    upper_levels: LevelSchedule,
+}
+
+impl IncompleteCholesky {
+    /// Fails when a pivot turns non-positive, which can happen even for SPD matrices since
+    /// the dropped fill-in changes the factorization.
+    pub fn new(a: &CompressedSparseRow<f64>) -> Result<Self, SolverError> {
+        check_square(a)?;
+        let n = a.rows();
+
+        let mut row_pointers = vec![0];
+        let mut column_indices = Vec::new();
+        let mut values = Vec::new();
+        for row in 0..n {
+            let (cols, vals) = a.row(row);
+            let below = cols.partition_point(|&col| col <= row);
+            if cols[..below].last() != Some(&row) {
+                return Err(SolverError::ZeroPivot { row });
+            }
+            column_indices.extend_from_slice(&cols[..below]);
+            values.extend_from_slice(&vals[..below]);
+            row_pointers.push(values.len());
+        }
+
+        // row i: L[i][j] = (A[i][j] - Σ_{k<j} L[i][k]·L[j][k]) / L[j][j], then the diagonal
+        for i in 0..n {
+            let (start, end) = (row_pointers[i], row_pointers[i + 1]);
+            for idx in start..end {
+                let j = column_indices[idx];
+                let (j_start, j_end) = (row_pointers[j], row_pointers[j + 1]);
+
+                // sorted merge of the parts of rows i and j left of column j
+                let (mut a_idx, mut b_idx, mut dot) = (start, j_start, 0.0);
+                while a_idx < idx && b_idx < j_end - 1 {
+                    let (ca, cb) = (column_indices[a_idx], column_indices[b_idx]);
+                    if ca == cb {
+                        dot += values[a_idx] * values[b_idx];
+                    }
+                    a_idx += (ca <= cb) as usize;
+                    b_idx += (cb <= ca) as usize;
+                }
+
+                if j < i {
+                    values[idx] = (values[idx] - dot) / values[j_end - 1];
+                } else {
+                    let pivot = values[idx] - dot;
+                    if pivot <= 0.0 {
+                        return Err(SolverError::NotPositiveDefinite { row: i, pivot });
+                    }
+                    values[idx] = pivot.sqrt();
+                }
+            }
+        }
+
+        let lower = CompressedSparseRow::from_raw_parts(n, row_pointers, column_indices, values)
+            .expect("lower triangle of a valid CSR is valid");
+        let upper = lower.transpose();
+        let lower_levels = LevelSchedule::lower(&lower)?;
+        let upper_levels = LevelSchedule::upper(&upper)?;
+
+        Ok(Self {
+            lower,
+            upper,
+            lower_levels,
+            upper_levels,
+        })
+    }
+
+    pub fn lower(&self) -> &CompressedSparseRow<f64> {
+        &self.lower
+    }
+}
+
+impl Preconditioner for IncompleteCholesky {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        // both factors were validated on construction
+        let y = par_solve_lower(&self.lower, &self.lower_levels, r).unwrap();
+        z.copy_from_slice(&par_solve_upper(&self.upper, &self.upper_levels, &y).unwrap());
+    }
+}
+
+/// Preconditioned Conjugate Gradient, for symmetric positive definite A.
+///
+/// Each iteration is one SpMV, one preconditioner application, two dot products and three
+/// axpys. Converges in at most n iterations in exact arithmetic, in practice in about
+/// sqrt(condition number).
+///
+/// Fails with `Breakdown` when pᵀAp or rᵀz reaches zero or stops being finite, which
+/// happens when A or the preconditioner is singular or indefinite.
+pub fn conjugate_gradient<P: Preconditioner>(
+    a: &CompressedSparseRow<f64>,
+    b: &[f64],
+    preconditioner: &P,
+    config: &SolverConfig,
+) -> Result<Solution, SolverError> {
+    let n = b.len();
+    assert_eq!(a.rows(), n, "b must have one entry per row");
+
+    let mut x = vec![0.0; n];
+    let b_norm = norm(b);
+    if b_norm == 0.0 {
+        return Ok(Solution::trivial(x));
+    }
+
+    let mut r = b.to_vec();
+    let mut z = vec![0.0; n];
+    preconditioner.apply(&r, &mut z);
+    let mut p = z.clone();
+    let mut ap = vec![0.0; n];
+    let mut rz = check_breakdown(0, "rᵀz", dot(&r, &z))?;
+
+    let mut history = vec![1.0];
+    for iteration in 1..=config.max_iterations {
+        a.spmv(&p, &mut ap);
+        let alpha = rz / check_breakdown(iteration, "pᵀAp", dot(&p, &ap))?;
+        axpy(alpha, &p, &mut x);
+        axpy(-alpha, &ap, &mut r);
+
+        let relative = norm(&r) / b_norm;
+        history.push(relative);
+        if relative < config.tolerance {
+            return Ok(Solution::new(x, iteration, true, history));
+        }
+
+        preconditioner.apply(&r, &mut z);
+        let rz_next = check_breakdown(iteration, "rᵀz", dot(&r, &z))?;
+        let beta = rz_next / rz;
+        rz = rz_next;
+        for (p, &z) in p.iter_mut().zip(&z) {
+            *p = z + beta * *p;
+        }
+    }
+
+    Ok(Solution::new(x, config.max_iterations, false, history))
+}
+
+/// Right-preconditioned BiCGSTAB, for general (non-symmetric) A.
+///
+/// Two SpMVs per iteration. Fails with `Breakdown` when ρ, ω or one of the denominators
+/// r̂ᵀv and tᵀt reaches zero or stops being finite.
+pub fn bicgstab<P: Preconditioner>(
+    a: &CompressedSparseRow<f64>,
+    b: &[f64],
+    preconditioner: &P,
+    config: &SolverConfig,
+) -> Result<Solution, SolverError> {
+    let n = b.len();
+    assert_eq!(a.rows(), n, "b must have one entry per row");
+
+    let mut x = vec![0.0; n];
+    let b_norm = norm(b);
+    if b_norm == 0.0 {
+        return Ok(Solution::trivial(x));
+    }
+
+    let mut r = b.to_vec();
+    let r_hat = r.clone();
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let mut v = vec![0.0; n];
+    let mut p = vec![0.0; n];
+    let mut p_hat = vec![0.0; n];
+    let mut s = vec![0.0; n];
+    let mut s_hat = vec![0.0; n];
+    let mut t = vec![0.0; n];
+
+    let mut history = vec![1.0];
+    for iteration in 1..=config.max_iterations {
+        // ρ and ω divide in β
+        let rho_next = check_breakdown(iteration, "ρ", dot(&r_hat, &r))?;
+        let beta = (rho_next / rho) * (alpha / omega);
+        rho = rho_next;
+        for ((p, &r), &v) in p.iter_mut().zip(&r).zip(&v) {
+            *p = r + beta * (*p - omega * v);
+        }
+
+        preconditioner.apply(&p, &mut p_hat);
+        a.spmv(&p_hat, &mut v);
+        alpha = rho / check_breakdown(iteration, "r̂ᵀv", dot(&r_hat, &v))?;
+        for ((s, &r), &v) in s.iter_mut().zip(&r).zip(&v) {
+            *s = r - alpha * v;
+        }
+
+        let relative = norm(&s) / b_norm;
+        if relative < config.tolerance {
+            axpy(alpha, &p_hat, &mut x);
+            history.push(relative);
+            return Ok(Solution::new(x, iteration, true, history));
+        }
+
+        preconditioner.apply(&s, &mut s_hat);
+        a.spmv(&s_hat, &mut t);
+        let tt = check_breakdown(iteration, "tᵀt", dot(&t, &t))?;
+        omega = check_breakdown(iteration, "ω", dot(&t, &s) / tt)?;
+        axpy(alpha, &p_hat, &mut x);
+        axpy(omega, &s_hat, &mut x);
+        for ((r, &s), &t) in r.iter_mut().zip(&s).zip(&t) {
+            *r = s - omega * t;
+        }
+
+        let relative = norm(&r) / b_norm;
+        history.push(relative);
+        if relative < config.tolerance {
+            return Ok(Solution::new(x, iteration, true, history));
+        }
+    }
+
+    Ok(Solution::new(x, config.max_iterations, false, history))
+}
+
+/// Solves L·x = b for lower triangular L with a non-zero diagonal, row by row.
+pub fn solve_lower(l: &CompressedSparseRow<f64>, b: &[f64]) -> Result<Vec<f64>, SolverError> {
+    check_square(l)?;
+    let mut x = vec![0.0; b.len()];
+    for row in 0..l.rows() {
+        x[row] = lower_row(l, row, b[row], &x)?;
+    }
+    Ok(x)
+}
+
+/// Solves U·x = b for upper triangular U with a non-zero diagonal, last row first.
+pub fn solve_upper(u: &CompressedSparseRow<f64>, b: &[f64]) -> Result<Vec<f64>, SolverError> {
+    check_square(u)?;
+    let mut x = vec![0.0; b.len()];
+    for row in (0..u.rows()).rev() {
+        x[row] = upper_row(u, row, b[row], &x)?;
+    }
+    Ok(x)
+}
+
+/// Rows of a triangular matrix grouped into levels: a row only depends on rows of earlier
+/// levels, so all rows of one level can be solved in parallel.
+///
+/// A forward solve looks inherently serial, but row i only waits for the rows its non-zeros
+/// point at. For a 2D Poisson matrix that is ~2·sqrt(n) levels instead of n steps.
+#[derive(Debug, Clone)]
+pub struct LevelSchedule {
+    rows: Vec<usize>,           // Rows sorted by level
+    level_pointers: Vec<usize>, // Start of every level in rows
+}
+
+impl LevelSchedule {
+    pub fn lower(l: &CompressedSparseRow<f64>) -> Result<Self, SolverError> {
+        check_square(l)?;
+        let mut levels = vec![0; l.rows()];
+        for row in 0..l.rows() {
+            let (cols, _) = l.row(row);
+            if let Some(&col) = cols.iter().find(|&&col| col > row) {
+                return Err(SolverError::NotTriangular { row, col });
+            }
+            levels[row] = cols
+                .iter()
+                .filter(|&&col| col < row)
+                .map(|&col| levels[col] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        Ok(Self::from_levels(&levels))
+    }
+
+    pub fn upper(u: &CompressedSparseRow<f64>) -> Result<Self, SolverError> {
+        check_square(u)?;
+        let mut levels = vec![0; u.rows()];
+        for row in (0..u.rows()).rev() {
+            let (cols, _) = u.row(row);
+            if let Some(&col) = cols.iter().find(|&&col| col < row) {
+                return Err(SolverError::NotTriangular { row, col });
+            }
+            levels[row] = cols
+                .iter()
+                .filter(|&&col| col > row)
+                .map(|&col| levels[col] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        Ok(Self::from_levels(&levels))
+    }
+
+    fn from_levels(levels: &[usize]) -> Self {
+        let count = levels.iter().max().map_or(0, |&max| max + 1);
+        let mut level_pointers = vec![0; count + 1];
+        for &level in levels {
+            level_pointers[level + 1] += 1;
+        }
+        for level in 0..count {
+            level_pointers[level + 1] += level_pointers[level];
+        }
+
+        let mut next = level_pointers.clone();
+        let mut rows = vec![0; levels.len()];
+        for (row, &level) in levels.iter().enumerate() {
+            rows[next[level]] = row;
+            next[level] += 1;
+        }
+
+        Self {
+            rows,
+            level_pointers,
+        }
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.level_pointers.len() - 1
+    }
+
+    fn levels(&self) -> impl Iterator<Item = &[usize]> {
+        self.level_pointers
+            .windows(2)
+            .map(|w| &self.rows[w[0]..w[1]])
+    }
+}
+
+/// Level-scheduled parallel forward solve, `schedule` must come from `LevelSchedule::lower(l)`.
+pub fn par_solve_lower(
+    l: &CompressedSparseRow<f64>,
+    schedule: &LevelSchedule,
+    b: &[f64],
+) -> Result<Vec<f64>, SolverError> {
+    par_solve(schedule, b, |row, x| lower_row(l, row, b[row], x))
+}
+
+/// Level-scheduled parallel backward solve, `schedule` must come from `LevelSchedule::upper(u)`.
+pub fn par_solve_upper(
+    u: &CompressedSparseRow<f64>,
+    schedule: &LevelSchedule,
+    b: &[f64],
+) -> Result<Vec<f64>, SolverError> {
+    par_solve(schedule, b, |row, x| upper_row(u, row, b[row], x))
+}
+
+fn par_solve<F>(schedule: &LevelSchedule, b: &[f64], solve_row: F) -> Result<Vec<f64>, SolverError>
+where
+    F: Fn(usize, &[f64]) -> Result<f64, SolverError> + Sync,
+{
+    let mut x = vec![0.0; b.len()];
+    let mut solved = Vec::new();
+    for level in schedule.levels() {
+        // rows of one level only read x of earlier levels, compute them all then scatter
+        level
+            .par_iter()
+            .map(|&row| solve_row(row, &x))
+            .collect_into_vec(&mut solved);
+        for (&row, value) in level.iter().zip(solved.drain(..)) {
+            x[row] = value?;
+        }
+    }
+    Ok(x)
+}
+
+fn lower_row(
+    l: &CompressedSparseRow<f64>,
+    row: usize,
+    b: f64,
+    x: &[f64],
+) -> Result<f64, SolverError> {
+    let (cols, vals) = l.row(row);
+    let mut sum = b;
+    let mut diagonal = 0.0;
+    for (&col, &val) in cols.iter().zip(vals) {
+        match col.cmp(&row) {
+            std::cmp::Ordering::Less => sum -= val * x[col],
+            std::cmp::Ordering::Equal => diagonal = val,
+            std::cmp::Ordering::Greater => return Err(SolverError::NotTriangular { row, col }),
+        }
+    }
+    if diagonal == 0.0 {
+        return Err(SolverError::ZeroPivot { row });
+    }
+    Ok(sum / diagonal)
+}
+
+fn upper_row(
+    u: &CompressedSparseRow<f64>,
+    row: usize,
+    b: f64,
+    x: &[f64],
+) -> Result<f64, SolverError> {
+    let (cols, vals) = u.row(row);
+    let mut sum = b;
+    let mut diagonal = 0.0;
+    for (&col, &val) in cols.iter().zip(vals) {
+        match col.cmp(&row) {
+            std::cmp::Ordering::Greater => sum -= val * x[col],
+            std::cmp::Ordering::Equal => diagonal = val,
+            std::cmp::Ordering::Less => return Err(SolverError::NotTriangular { row, col }),
+        }
+    }
+    if diagonal == 0.0 {
+        return Err(SolverError::ZeroPivot { row });
+    }
+    Ok(sum / diagonal)
+}
+
+impl Solution {
+    fn new(x: Vec<f64>, iterations: usize, converged: bool, residual_history: Vec<f64>) -> Self {
+        Self {
+            x,
+            iterations,
+            converged,
+            residual_history,
+        }
+    }
+
+    // b = 0 is solved by x = 0 before the first iteration
+    fn trivial(x: Vec<f64>) -> Self {
+        Self::new(x, 0, true, vec![0.0])
+    }
+}
+
+fn check_square(a: &CompressedSparseRow<f64>) -> Result<(), SolverError> {
+    if a.rows() != a.cols() {
+        return Err(SolverError::NotSquare {
+            rows: a.rows(),
+            cols: a.cols(),
+        });
+    }
+    Ok(())
+}
+
+// Krylov scalars that are divided by later, zero or non-finite means the iteration is lost
+fn check_breakdown(
+    iteration: usize,
+    quantity: &'static str,
+    value: f64,
+) -> Result<f64, SolverError> {
+    if value == 0.0 || !value.is_finite() {
+        return Err(SolverError::Breakdown {
+            iteration,
+            quantity,
+            value,
+        });
+    }
+    Ok(value)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+// y += alpha·x
+fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    for (y, &x) in y.iter_mut().zip(x) {
+        *y += alpha * x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::laplacian_2d;
+
+    fn residual(a: &CompressedSparseRow<f64>, x: &[f64], b: &[f64]) -> f64 {
+        let mut ax = vec![0.0; b.len()];
+        a.spmv(x, &mut ax);
+        let r: Vec<f64> = ax.iter().zip(b).map(|(ax, b)| b - ax).collect();
+        norm(&r) / norm(b)
+    }
+
+    #[test]
+    fn test_cg_poisson() {
+        let a = laplacian_2d(20, 20);
+        let b: Vec<f64> = (0..400).map(|i| ((i * 7) % 11) as f64 - 5.0).collect();
+        let config = SolverConfig {
+            tolerance: 1e-10,
+            max_iterations: 500,
+        };
+
+        let plain = conjugate_gradient(&a, &b, &Identity, &config).unwrap();
+        let jacobi = conjugate_gradient(&a, &b, &Jacobi::new(&a).unwrap(), &config).unwrap();
+        let ic0 = IncompleteCholesky::new(&a).unwrap();
+        let ic0 = conjugate_gradient(&a, &b, &ic0, &config).unwrap();
+
+        for solution in [&plain, &jacobi, &ic0] {
+            assert!(solution.converged);
+            assert!(residual(&a, &solution.x, &b) < 1e-9);
+            assert_eq!(solution.residual_history.len(), solution.iterations + 1);
+            assert!(solution.residual_history.last().unwrap() < &config.tolerance);
+        }
+        // a constant diagonal makes Jacobi a no-op, IC(0) should roughly halve the iterations
+        assert_eq!(plain.iterations, jacobi.iterations);
+        assert!(ic0.iterations * 3 < plain.iterations * 2);
+    }
+
+    #[test]
+    fn test_cg_respects_iteration_limit() {
+        let a = laplacian_2d(20, 20);
+        let b = vec![1.0; 400];
+        let config = SolverConfig {
+            tolerance: 1e-12,
+            max_iterations: 5,
+        };
+        let solution = conjugate_gradient(&a, &b, &Identity, &config).unwrap();
+        assert!(!solution.converged);
+        assert_eq!(solution.iterations, 5);
+        assert_eq!(solution.residual_history.len(), 6);
+    }
+
+    #[test]
+    fn test_bicgstab_nonsymmetric() {
+        // Poisson plus an upwind convection term, no longer symmetric
+        let side = 15;
+        let poisson = laplacian_2d(side, side);
+        let mut triplets = Vec::new();
+        for row in 0..poisson.rows() {
+            let (cols, vals) = poisson.row(row);
+            for (&col, &val) in cols.iter().zip(vals) {
+                let convection = if col + 1 == row { -0.5 } else { 0.0 };
+                triplets.push((row, col, val + convection));
+            }
+        }
+        let a = CompressedSparseRow::from_triplets(side * side, side * side, triplets).unwrap();
+        let b: Vec<f64> = (0..side * side).map(|i| (i % 5) as f64).collect();
+
+        for solution in [
+            bicgstab(&a, &b, &Identity, &SolverConfig::default()).unwrap(),
+            bicgstab(&a, &b, &Jacobi::new(&a).unwrap(), &SolverConfig::default()).unwrap(),
+        ] {
+            assert!(solution.converged);
+            assert!(residual(&a, &solution.x, &b) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_singular_system_breaks_down() {
+        // b = (1, 1) spans the null space of A, so the first search direction has Ap = 0
+        let a = CompressedSparseRow::new(vec![vec![1.0, -1.0], vec![-1.0, 1.0]]);
+        let b = [1.0, 1.0];
+        let config = SolverConfig::default();
+
+        assert_eq!(
+            conjugate_gradient(&a, &b, &Identity, &config).unwrap_err(),
+            SolverError::Breakdown {
+                iteration: 1,
+                quantity: "pᵀAp",
+                value: 0.0
+            }
+        );
+        assert_eq!(
+            bicgstab(&a, &b, &Identity, &config).unwrap_err(),
+            SolverError::Breakdown {
+                iteration: 1,
+                quantity: "r̂ᵀv",
+                value: 0.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_triangular_solves() {
+        let a = laplacian_2d(12, 12);
+        let ic = IncompleteCholesky::new(&a).unwrap();
+        let l = ic.lower();
+        let u = l.transpose();
+        let b: Vec<f64> = (0..144).map(|i| (i as f64).sin()).collect();
+
+        let x = solve_lower(l, &b).unwrap();
+        assert!(residual(l, &x, &b) < 1e-12);
+        let levels = LevelSchedule::lower(l).unwrap();
+        assert_eq!(par_solve_lower(l, &levels, &b).unwrap(), x);
+        // anti-diagonals of the grid are independent
+        assert_eq!(levels.level_count(), 2 * 12 - 1);
+
+        let x = solve_upper(&u, &b).unwrap();
+        assert!(residual(&u, &x, &b) < 1e-12);
+        let levels = LevelSchedule::upper(&u).unwrap();
+        assert_eq!(par_solve_upper(&u, &levels, &b).unwrap(), x);
+
+        assert_eq!(
+            solve_lower(&u, &b),
+            Err(SolverError::NotTriangular { row: 0, col: 1 })
+        );
+    }
+
+    #[test]
+    fn test_ic0_is_exact_without_fill() {
+        // tridiagonal matrices have no fill-in, so IC(0) is the exact Cholesky factor
+        let n = 10;
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push((i, i, 2.0));
+            if i + 1 < n {
+                triplets.extend([(i, i + 1, -1.0), (i + 1, i, -1.0)]);
+            }
+        }
+        let a = CompressedSparseRow::from_triplets(n, n, triplets).unwrap();
+        let l = IncompleteCholesky::new(&a).unwrap().lower().reconstruct();
+
+        for i in 0..n {
+            for j in 0..n {
+                let llt: f64 = (0..n).map(|k| l[i][k] * l[j][k]).sum();
+                assert!((llt - a.get(i, j)).abs() < 1e-12);
+            }
+        }
+
+        let indefinite = CompressedSparseRow::new(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        assert!(matches!(
+            IncompleteCholesky::new(&indefinite),
+            Err(SolverError::NotPositiveDefinite { row: 1, .. })
+        ));
+    }
+}
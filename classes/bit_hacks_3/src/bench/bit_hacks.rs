@@ -0,0 +1,139 @@
+use bit_hacks_3::hacks::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use criterion::{measurement::WallTime, Throughput};
+use rand::prelude::*;
+
+// Every hack against the standard library method on the same random words. With
+// -C target-cpu=native the std versions compile to single instructions (popcnt, lzcnt,
+// tzcnt), which is the bar the hacks have to clear on older targets.
+
+const WORDS: usize = 4096;
+
+fn random_words<T>(seed: u64) -> Vec<T>
+where
+    rand::distr::StandardUniform: Distribution<T>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..WORDS).map(|_| rng.random()).collect()
+}
+
+// Sums f over the words so nothing gets optimized away
+fn bench_unary<T: Copy>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    words: &[T],
+    f: impl Fn(T) -> u64,
+) {
+    group.bench_function(name, |bencher| {
+        bencher.iter(|| {
+            black_box(words)
+                .iter()
+                .fold(0u64, |sum, &x| sum.wrapping_add(f(x)))
+        })
+    });
+}
+
+fn bench_popcount(c: &mut Criterion) {
+    let words: Vec<u32> = random_words(1);
+    let mut group = c.benchmark_group("popcount u32");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "table", &words, |x| popcount_table(x) as u64);
+    bench_unary(&mut group, "parallel", &words, |x| {
+        popcount_parallel(x) as u64
+    });
+    bench_unary(&mut group, "count_ones", &words, |x| x.count_ones() as u64);
+    group.finish();
+
+    let words: Vec<u64> = random_words(2);
+    let mut group = c.benchmark_group("popcount u64");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "table", &words, |x| popcount_table(x) as u64);
+    bench_unary(&mut group, "parallel", &words, |x| {
+        popcount_parallel(x) as u64
+    });
+    bench_unary(&mut group, "count_ones", &words, |x| x.count_ones() as u64);
+    group.finish();
+}
+
+fn bench_log2(c: &mut Criterion) {
+    // | 1 keeps zero out, both sides would panic on it
+    let words: Vec<u32> = random_words::<u32>(3).iter().map(|x| x | 1).collect();
+    let mut group = c.benchmark_group("log2 u32");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "de Bruijn log2", &words, |x| log2(x) as u64);
+    bench_unary(&mut group, "31 - leading_zeros", &words, |x| {
+        (31 - x.leading_zeros()) as u64
+    });
+    bench_unary(&mut group, "de Bruijn trailing_zeros", &words, |x| {
+        trailing_zeros(x) as u64
+    });
+    bench_unary(&mut group, "trailing_zeros", &words, |x| {
+        x.trailing_zeros() as u64
+    });
+    group.finish();
+}
+
+fn bench_bits(c: &mut Criterion) {
+    let words: Vec<u32> = random_words(4);
+    let mut group = c.benchmark_group("bits u32");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "hack reverse", &words, |x| {
+        reverse_bits(x) as u64
+    });
+    bench_unary(&mut group, "reverse_bits", &words, |x| {
+        x.reverse_bits() as u64
+    });
+    // small enough that every result fits
+    let small: Vec<u32> = words.iter().map(|x| x >> 2).collect();
+    bench_unary(&mut group, "hack round up", &small, |x| {
+        round_up_pow2(x) as u64
+    });
+    bench_unary(&mut group, "next_power_of_two", &small, |x| {
+        x.next_power_of_two() as u64
+    });
+    group.finish();
+}
+
+// Random pairs make the comparison a coin flip, the worst case for a branch
+fn bench_branchless(c: &mut Criterion) {
+    let x: Vec<i32> = random_words(5);
+    let y: Vec<i32> = random_words(6);
+    let pairs: Vec<(i32, i32)> = x.into_iter().zip(y).collect();
+    let mut group = c.benchmark_group("min max i32");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "branchless", &pairs, |(x, y)| {
+        (branchless_min(x, y) as i64 + branchless_max(x, y) as i64) as u64
+    });
+    bench_unary(&mut group, "Ord::min/max", &pairs, |(x, y)| {
+        (x.min(y) as i64 + x.max(y) as i64) as u64
+    });
+    bench_unary(&mut group, "if/else", &pairs, |(x, y)| {
+        let (low, high) = if x < y { (x, y) } else { (y, x) };
+        (low as i64 + high as i64) as u64
+    });
+    group.finish();
+
+    let n = 1_000_000_007u32;
+    let reduced: Vec<(u32, u32)> = pairs
+        .iter()
+        .map(|&(x, y)| (x as u32 % n, y as u32 % n))
+        .collect();
+    let mut group = c.benchmark_group("add mod u32");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    bench_unary(&mut group, "add_mod", &reduced, |(x, y)| {
+        add_mod(x, y, n) as u64
+    });
+    bench_unary(&mut group, "(x + y) % n", &reduced, |(x, y)| {
+        (x as u64 + y as u64) % n as u64
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_popcount,
+    bench_log2,
+    bench_bits,
+    bench_branchless
+);
+criterion_main!(benches);
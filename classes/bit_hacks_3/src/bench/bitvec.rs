@@ -0,0 +1,98 @@
+use bit_hacks_3::bitvec::BitVec;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::prelude::*;
+
+// Each BitVec operation against the bit-at-a-time way of doing the same thing.
+
+fn random_bits(len: usize, density: f64, seed: u64) -> BitVec {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..len).map(|_| rng.random_bool(density)).collect()
+}
+
+fn bench_rotate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotate");
+    let len = 4096;
+    let bits = random_bits(len, 0.5, 1);
+
+    // the naive rotation costs the amount times the length, the reversal only the length
+    for by in [1, 64, 1000] {
+        group.bench_with_input(BenchmarkId::new("naive", by), &by, |bencher, &by| {
+            bencher.iter_batched_ref(
+                || bits.clone(),
+                |bits| bits.rotate_range_naive(3..len - 5, by),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("reversal", by), &by, |bencher, &by| {
+            bencher.iter_batched_ref(
+                || bits.clone(),
+                |bits| bits.rotate_range(3..len - 5, by),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_rank(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rank");
+    let len = 1 << 20;
+    let bits = random_bits(len, 0.5, 2);
+    let index = bits.rank_index();
+    let mut rng = StdRng::seed_from_u64(3);
+    let queries: Vec<usize> = (0..1000).map(|_| rng.random_range(0..len)).collect();
+
+    group.bench_function("index", |bencher| {
+        bencher.iter(|| {
+            queries
+                .iter()
+                .map(|&i| index.rank(black_box(i)))
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("popcount prefix", |bencher| {
+        bencher.iter(|| {
+            queries
+                .iter()
+                .map(|&i| {
+                    let words = &bits.words()[..i / 64];
+                    let whole: u32 = words.iter().map(|w| w.count_ones()).sum();
+                    let part = bits.words()[i / 64] & ((1 << (i % 64)) - 1);
+                    (whole + part.count_ones()) as usize
+                })
+                .sum::<usize>()
+        })
+    });
+    let ones = index.ones();
+    group.bench_function("select", |bencher| {
+        bencher.iter(|| {
+            queries
+                .iter()
+                .map(|&k| index.select(black_box(k % ones)).unwrap())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+fn bench_iter_ones(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_ones");
+    let len = 1 << 16;
+    for density in [0.01, 0.5] {
+        let bits = random_bits(len, density, 4);
+        group.bench_with_input(
+            BenchmarkId::new("lowest set bit", density),
+            &bits,
+            |bencher, bits| bencher.iter(|| bits.iter_ones().sum::<usize>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get every bit", density),
+            &bits,
+            |bencher, bits| bencher.iter(|| (0..len).filter(|&i| bits.get(i)).sum::<usize>()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_rotate, bench_rank, bench_iter_ones);
+criterion_main!(benches);
@@ -1,75 +1,189 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use bit_hacks_3::merge::{
+    merge_branchless_into, merge_into, merge_simd_into, merge_sort, merge_sort_with,
+};
+use criterion::Throughput;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use rand::prelude::*;
+use utils::perf::{Counter, Event};
 
-pub fn merge(a: &[i32], b: &[i32]) -> Vec<i32> {
-    let mut result = Vec::with_capacity(a.len() + b.len());
-    let (mut i, mut j) = (0, 0);
-
-    while i < a.len() && j < b.len() {
-        if a[i] <= b[j] {
-            result.push(a[i]);
-            i += 1;
-        } else {
-            result.push(b[j]);
-            j += 1;
-        }
-    }
+type Merge = fn(&[i32], &[i32], &mut [i32]);
 
-    result.extend_from_slice(&a[i..]);
-    result.extend_from_slice(&b[j..]);
+const MERGES: [(&str, Merge); 3] = [
+    ("Standard Merge", merge_into),
+    ("Branchless Merge", merge_branchless_into),
+    ("SIMD Merge", merge_simd_into),
+];
 
-    result
+fn generate_sorted_data(size: usize, seed: u64) -> Vec<i32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data: Vec<i32> = (0..size).map(|_| rng.random_range(-500..1000)).collect();
+    data.sort();
+    data
 }
 
-pub fn merge_branchless(a: &[i32], b: &[i32]) -> Vec<i32> {
-    let mut result = Vec::with_capacity(a.len() + b.len());
-    let (mut i, mut j) = (0, 0);
+fn bench_merges(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Merge Algorithms");
 
-    while i < a.len() && j < b.len() {
-        let cmp = (a[i] <= b[j]) as usize;
-        let not_cmp = 1 - cmp;
+    for size in [10, 100, 1000, 10000].iter() {
+        let a = generate_sorted_data(*size, 1);
+        let b = generate_sorted_data(*size, 2);
+        // the output is allocated once, the merges themselves don't allocate
+        let mut out = vec![0; a.len() + b.len()];
+
+        for (name, merge) in MERGES {
+            group.bench_with_input(
+                BenchmarkId::new(name, size),
+                &(&a, &b),
+                |bencher, (a, b)| bencher.iter(|| merge(black_box(a), black_box(b), &mut out)),
+            );
+        }
+    }
+    group.finish();
+}
 
-        let min = b[j] ^ ((b[j] ^ a[i]) & (-(cmp as i32)));
-        result.push(min);
+// The values 0..2 * size dealt out in blocks of k, alternately to a and b. The merge
+// then switches inputs every k elements: k = 1 interleaves, k = size puts all of a below
+// all of b.
+fn runs(size: usize, k: usize) -> (Vec<i32>, Vec<i32>) {
+    (0..2 * size as i32).partition(|&value| (value as usize / k).is_multiple_of(2))
+}
 
-        i += cmp;
-        j += not_cmp;  // Changed from !cmp to not_cmp
+// Uniform random is the worst case for a branch, a coin flip per element. Everything else
+// gives the predictor a pattern: long runs from one side, a strict alternation it can
+// learn from history, or ties that always go to a.
+fn distributions(size: usize) -> Vec<(String, Vec<i32>, Vec<i32>)> {
+    let mut inputs = vec![(
+        "uniform".to_string(),
+        generate_sorted_data(size, 1),
+        generate_sorted_data(size, 2),
+    )];
+    for k in [1, 4, 16, 64, size] {
+        let label = match k {
+            1 => "alternating".to_string(),
+            k if k == size => "disjoint".to_string(),
+            k => format!("runs of {}", k),
+        };
+        let (a, b) = runs(size, k);
+        inputs.push((label, a, b));
     }
 
-    result.extend_from_slice(&a[i..]);
-    result.extend_from_slice(&b[j..]);
+    let few_values = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut data: Vec<i32> = (0..size).map(|_| rng.random_range(0..4)).collect();
+        data.sort();
+        data
+    };
+    inputs.push((
+        "4 distinct values".to_string(),
+        few_values(3),
+        few_values(4),
+    ));
+    inputs
+}
 
-    result
+// Branch misses per output element, measured outside criterion since it only times
+const REPEATS: usize = 100;
+
+fn report_branch_misses(inputs: &[(String, Vec<i32>, Vec<i32>)]) {
+    let mut counter = match Counter::new(Event::BranchMisses) {
+        Ok(counter) => counter,
+        Err(error) => {
+            println!(
+                "No branch-miss counts, perf counters unavailable: {}",
+                error
+            );
+            return;
+        }
+    };
+
+    println!(
+        "{:<18} {:<18} {:>15}",
+        "distribution", "merge", "misses/element"
+    );
+    for (label, a, b) in inputs {
+        let mut out = vec![0; a.len() + b.len()];
+        for (name, merge) in MERGES {
+            let ((), misses) = counter
+                .measure(|| {
+                    for _ in 0..REPEATS {
+                        merge(black_box(a), black_box(b), &mut out);
+                    }
+                })
+                .expect("reading the counter");
+            let per_element = misses as f64 / (REPEATS * out.len()) as f64;
+            println!("{:<18} {:<18} {:>15.4}", label, name, per_element);
+        }
+    }
 }
 
-fn generate_sorted_data(size: usize) -> Vec<i32> {
-    let mut rng = StdRng::seed_from_u64(42);
-    let mut data: Vec<i32> = (0..size).map(|_| rng.random_range(-500..1000)).collect();
-    data.sort();
-    data
+fn bench_distributions(c: &mut Criterion) {
+    let inputs = distributions(10_000);
+    report_branch_misses(&inputs);
+
+    let mut group = c.benchmark_group("Merge Distributions");
+    for (label, a, b) in &inputs {
+        let mut out = vec![0; a.len() + b.len()];
+        group.throughput(Throughput::Elements(out.len() as u64));
+        for (name, merge) in MERGES {
+            group.bench_with_input(BenchmarkId::new(name, label), &(a, b), |bencher, (a, b)| {
+                bencher.iter(|| merge(black_box(a), black_box(b), &mut out))
+            });
+        }
+    }
+    group.finish();
 }
 
-fn bench_merges(c: &mut Criterion) {
-    let mut group = c.benchmark_group("Merge Algorithms");
+fn bench_sorts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Merge Sort");
 
-    for size in [10, 100, 1000, 10000].iter() {
-        let a = generate_sorted_data(*size);
-        let b = generate_sorted_data(*size);
+    for size in [1000, 100_000, 1_000_000] {
+        let mut rng = StdRng::seed_from_u64(size as u64);
+        let data: Vec<i32> = (0..size).map(|_| rng.random()).collect();
+        let mut scratch = vec![0; size];
 
         group.bench_with_input(
-            BenchmarkId::new("Standard Merge", size),
-            &(a.clone(), b.clone()),
-            |bencher, (a, b)| bencher.iter(|| merge(black_box(a), black_box(b)))
+            BenchmarkId::new("merge_sort", size),
+            &data,
+            |bencher, data| {
+                bencher.iter_batched_ref(
+                    || data.clone(),
+                    |data| merge_sort(data),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("merge_sort_with", size),
+            &data,
+            |bencher, data| {
+                bencher.iter_batched_ref(
+                    || data.clone(),
+                    |data| merge_sort_with(data, &mut scratch),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("slice::sort", size),
+            &data,
+            |bencher, data| {
+                bencher.iter_batched_ref(|| data.clone(), |data| data.sort(), BatchSize::LargeInput)
+            },
         );
-
         group.bench_with_input(
-            BenchmarkId::new("Branchless Merge", size),
-            &(a.clone(), b.clone()),
-            |bencher, (a, b)| bencher.iter(|| merge_branchless(black_box(a), black_box(b)))
+            BenchmarkId::new("slice::sort_unstable", size),
+            &data,
+            |bencher, data| {
+                bencher.iter_batched_ref(
+                    || data.clone(),
+                    |data| data.sort_unstable(),
+                    BatchSize::LargeInput,
+                )
+            },
         );
     }
     group.finish();
 }
 
-criterion_group!(benches, bench_merges);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, bench_merges, bench_distributions, bench_sorts);
+criterion_main!(benches);
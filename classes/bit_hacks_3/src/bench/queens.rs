@@ -0,0 +1,27 @@
+use bit_hacks_3::queens::{count_bitboard, count_naive, count_parallel};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn bench_solvers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("N-Queens");
+    group.sample_size(10);
+
+    for n in [8, 10, 12] {
+        group.bench_with_input(BenchmarkId::new("naive", n), &n, |bencher, &n| {
+            bencher.iter(|| count_naive(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("bitboard", n), &n, |bencher, &n| {
+            bencher.iter(|| count_bitboard(black_box(n)))
+        });
+        for split_depth in [1, 2, 4] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("parallel split {}", split_depth), n),
+                &n,
+                |bencher, &n| bencher.iter(|| count_parallel(black_box(n), split_depth)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_solvers);
+criterion_main!(benches);
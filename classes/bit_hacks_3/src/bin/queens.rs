@@ -0,0 +1,54 @@
+use bit_hacks_3::queens::{count_bitboard, count_naive, count_parallel, KNOWN_COUNTS};
+use std::time::Instant;
+
+// Counts N-Queens solutions with every solver for n = 1..=max_n, checking each count
+// against OEIS A000170. The naive solver drops out past NAIVE_MAX_N, where it takes
+// minutes.
+//
+//   cargo run --release --bin queens [max_n] [split_depth]
+
+const MAX_N: usize = 16;
+const SPLIT_DEPTH: usize = 3;
+const NAIVE_MAX_N: usize = 13;
+
+fn main() {
+    let mut args = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("not a number"));
+    let max_n: usize = args.next().unwrap_or(MAX_N);
+    let split_depth: usize = args.next().unwrap_or(SPLIT_DEPTH);
+
+    println!(
+        "{:>3} {:>16} {:>10} {:>10} {:>10}",
+        "n",
+        "solutions",
+        "naive s",
+        "bitboard s",
+        format!("split {} s", split_depth)
+    );
+
+    for n in 1..=max_n {
+        let time = |count: &dyn Fn() -> u64| {
+            let start = Instant::now();
+            let solutions = count();
+            let seconds = start.elapsed().as_secs_f64();
+            if let Some(&known) = KNOWN_COUNTS.get(n) {
+                assert_eq!(solutions, known, "wrong count for n = {}", n);
+            }
+            (solutions, seconds)
+        };
+
+        let naive = if n <= NAIVE_MAX_N {
+            format!("{:.4}", time(&|| count_naive(n)).1)
+        } else {
+            "-".to_string()
+        };
+        let (solutions, bitboard) = time(&|| count_bitboard(n));
+        let (_, parallel) = time(&|| count_parallel(n, split_depth));
+
+        println!(
+            "{:>3} {:>16} {:>10} {:>10.4} {:>10.4}",
+            n, solutions, naive, bitboard, parallel
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use crate::hacks::lowest_set_bit;
+use std::fmt;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Not, Range};
+
+// A fixed-length bit vector packed into u64 words, bit i in word i / 64 at position
+// i % 64. Bits past `len` in the last word are always zero, so whole-word operations
+// (popcount, equality, and/or/xor) never see garbage; only `!` has to mask them off.
+//
+// Rank and select live in a separate `RankIndex` borrowed from the vector. The index is
+// a snapshot, and the borrow makes sure the vector can't change under it.
+
+const WORD: usize = u64::BITS as usize;
+
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// `len` bits, all clear.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The backing words, lowest bits first.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    fn locate(&self, i: usize) -> (usize, u64) {
+        assert!(i < self.len, "bit {} of {}", i, self.len);
+        (i / WORD, 1 << (i % WORD))
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        let (word, mask) = self.locate(i);
+        self.words[word] & mask != 0
+    }
+
+    pub fn set(&mut self, i: usize) {
+        let (word, mask) = self.locate(i);
+        self.words[word] |= mask;
+    }
+
+    pub fn clear(&mut self, i: usize) {
+        let (word, mask) = self.locate(i);
+        self.words[word] &= !mask;
+    }
+
+    pub fn flip(&mut self, i: usize) {
+        let (word, mask) = self.locate(i);
+        self.words[word] ^= mask;
+    }
+
+    /// Sets bit `i` to `value` without branching on it.
+    pub fn assign(&mut self, i: usize, value: bool) {
+        let (word, mask) = self.locate(i);
+        // clear the bit, then or in the mask if value is 1
+        self.words[word] = (self.words[word] & !mask) | (mask & (value as u64).wrapping_neg());
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Indices of the set bits in increasing order.
+    pub fn iter_ones(&self) -> Ones<'_> {
+        Ones {
+            words: &self.words,
+            index: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    // Zeroes the bits past len in the last word, after an operation that set them
+    fn mask_tail(&mut self) {
+        if !self.len.is_multiple_of(WORD) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << (self.len % WORD)) - 1;
+            }
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        // only a pair that differs needs changing, and then both bits flip
+        if self.get(i) != self.get(j) {
+            self.flip(i);
+            self.flip(j);
+        }
+    }
+
+    /// Reverses the order of the bits in `range`.
+    pub fn reverse_range(&mut self, range: Range<usize>) {
+        self.check_range(&range);
+        let (mut i, mut j) = (range.start, range.end);
+        while i + 1 < j {
+            j -= 1;
+            self.swap(i, j);
+            i += 1;
+        }
+    }
+
+    fn check_range(&self, range: &Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range {:?} of {} bits",
+            range,
+            self.len
+        );
+    }
+
+    // Right rotation by `by`, as a left rotation of the same range when `by` is negative
+    fn right_amount(range: &Range<usize>, by: isize) -> usize {
+        let n = range.len();
+        if n == 0 {
+            return 0;
+        }
+        by.rem_euclid(n as isize) as usize
+    }
+
+    /// Rotates the bits in `range` by one position at a time, `by` times.
+    ///
+    /// Positive `by` moves every bit to a higher index, and the bits pushed off the end
+    /// of the range wrap around to its start; negative `by` rotates the other way. This
+    /// is the obvious version, O(len * by), kept as the baseline for `rotate_range`.
+    pub fn rotate_range_naive(&mut self, range: Range<usize>, by: isize) {
+        self.check_range(&range);
+        for _ in 0..Self::right_amount(&range, by) {
+            let last = self.get(range.end - 1);
+            for i in (range.start + 1..range.end).rev() {
+                self.assign(i, self.get(i - 1));
+            }
+            self.assign(range.start, last);
+        }
+    }
+
+    /// Rotates the bits in `range` like `rotate_range_naive`, in O(len) with three
+    /// reversals.
+    ///
+    /// Rotating AB right by |B| gives BA. Reversing AB gives B'A', and reversing each
+    /// part back in place gives BA, whatever the amount.
+    pub fn rotate_range(&mut self, range: Range<usize>, by: isize) {
+        self.check_range(&range);
+        let split = range.start + Self::right_amount(&range, by);
+        self.reverse_range(range.clone());
+        self.reverse_range(range.start..split);
+        self.reverse_range(split..range.end);
+    }
+
+    fn check_same_len(&self, other: &BitVec) {
+        assert_eq!(
+            self.len, other.len,
+            "combining {} bits with {}",
+            self.len, other.len
+        );
+    }
+
+    /// Rank and select index over the current contents.
+    pub fn rank_index(&self) -> RankIndex<'_> {
+        RankIndex::new(self)
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(bits: I) -> Self {
+        let mut vec = BitVec::new(0);
+        for bit in bits {
+            if vec.len.is_multiple_of(WORD) {
+                vec.words.push(0);
+            }
+            vec.len += 1;
+            vec.assign(vec.len - 1, bit);
+        }
+        vec
+    }
+}
+
+// Bit 0 first, the way the indices read
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len {
+            f.write_str(if self.get(i) { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+// The word-parallel operations: 64 bits per instruction, and the zero tails of both
+// operands stay zero under and, or and xor
+
+impl BitAndAssign<&BitVec> for BitVec {
+    fn bitand_assign(&mut self, other: &BitVec) {
+        self.check_same_len(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+}
+
+impl BitOrAssign<&BitVec> for BitVec {
+    fn bitor_assign(&mut self, other: &BitVec) {
+        self.check_same_len(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, other: &BitVec) {
+        self.check_same_len(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+}
+
+impl Not for BitVec {
+    type Output = BitVec;
+
+    fn not(mut self) -> BitVec {
+        for word in &mut self.words {
+            *word = !*word;
+        }
+        self.mask_tail();
+        self
+    }
+}
+
+/// Iterator over the set bits of a `BitVec`, from `BitVec::iter_ones`.
+///
+/// Each step peels the lowest set bit off the current word, so the cost is one step per
+/// set bit plus one per word, however sparse the vector is.
+pub struct Ones<'a> {
+    words: &'a [u64],
+    index: usize,
+    current: u64,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+        let bit = lowest_set_bit(self.current);
+        self.current ^= bit;
+        Some(self.index * WORD + bit.trailing_zeros() as usize)
+    }
+}
+
+// Bits per superblock: the absolute count before each superblock takes a u64, the count
+// within its superblock before each word fits a u16, so the index costs 64 + 8 * 16 bits
+// per 512, 37.5% on top of the vector itself.
+const SUPERBLOCK: usize = 8 * WORD;
+
+/// Constant time rank and O(log n) select over a `BitVec`.
+pub struct RankIndex<'a> {
+    bits: &'a BitVec,
+    superblocks: Vec<u64>,
+    blocks: Vec<u16>,
+}
+
+impl<'a> RankIndex<'a> {
+    fn new(bits: &'a BitVec) -> Self {
+        let mut superblocks = Vec::with_capacity(bits.words.len().div_ceil(8));
+        let mut blocks = Vec::with_capacity(bits.words.len());
+        let mut total = 0;
+        for chunk in bits.words.chunks(SUPERBLOCK / WORD) {
+            superblocks.push(total);
+            let mut within = 0;
+            for word in chunk {
+                blocks.push(within);
+                within += word.count_ones() as u16;
+            }
+            total += within as u64;
+        }
+        Self {
+            bits,
+            superblocks,
+            blocks,
+        }
+    }
+
+    /// Set bits in `0..i`, for any `i` up to the length.
+    ///
+    /// Two table lookups and one popcount of the bits below i in i's own word.
+    pub fn rank(&self, i: usize) -> usize {
+        assert!(i <= self.bits.len, "rank {} of {} bits", i, self.bits.len);
+        if i == self.bits.len {
+            return self.ones();
+        }
+        let word = i / WORD;
+        let below = self.bits.words[word] & ((1 << (i % WORD)) - 1);
+        self.superblocks[i / SUPERBLOCK] as usize
+            + self.blocks[word] as usize
+            + below.count_ones() as usize
+    }
+
+    pub fn ones(&self) -> usize {
+        match self.superblocks.last() {
+            Some(&last) => {
+                let start = (self.superblocks.len() - 1) * SUPERBLOCK / WORD;
+                let rest: u32 = self.bits.words[start..]
+                    .iter()
+                    .map(|w| w.count_ones())
+                    .sum();
+                last as usize + rest as usize
+            }
+            None => 0,
+        }
+    }
+
+    /// Index of the set bit with rank `k` (the k+1-th one), `None` if there are at most
+    /// k set bits.
+    ///
+    /// Binary search over the superblocks, a scan over at most 8 block counts, then the
+    /// lowest k' set bits of one word are peeled off.
+    pub fn select(&self, k: usize) -> Option<usize> {
+        if k >= self.ones() {
+            return None;
+        }
+        // the last superblock starting at or below rank k
+        let superblock = self.superblocks.partition_point(|&r| r as usize <= k) - 1;
+        let mut k = k - self.superblocks[superblock] as usize;
+
+        let first = superblock * SUPERBLOCK / WORD;
+        let last = (first + SUPERBLOCK / WORD).min(self.blocks.len());
+        let word = (first..last)
+            .take_while(|&w| self.blocks[w] as usize <= k)
+            .last()
+            .expect("the superblock holds bit k");
+        k -= self.blocks[word] as usize;
+
+        let mut bits = self.bits.words[word];
+        for _ in 0..k {
+            bits ^= lowest_set_bit(bits);
+        }
+        Some(word * WORD + bits.trailing_zeros() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    fn parse(bits: &str) -> BitVec {
+        bits.bytes().map(|b| b == b'1').collect()
+    }
+
+    fn random(len: usize, density: f64, rng: &mut StdRng) -> BitVec {
+        (0..len).map(|_| rng.random_bool(density)).collect()
+    }
+
+    #[test]
+    fn test_get_set_clear_flip() {
+        let mut bits = BitVec::new(130);
+        for i in [0, 63, 64, 129] {
+            bits.set(i);
+        }
+        bits.flip(1);
+        bits.flip(63);
+        bits.clear(64);
+        bits.assign(100, true);
+        let ones: Vec<usize> = (0..bits.len()).filter(|&i| bits.get(i)).collect();
+        assert_eq!(ones, [0, 1, 100, 129]);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(format!("{:?}", parse("0110")), "0110");
+    }
+
+    #[test]
+    #[should_panic(expected = "bit 10 of 10")]
+    fn test_out_of_bounds() {
+        BitVec::new(10).get(10);
+    }
+
+    #[test]
+    fn test_word_parallel_ops() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for len in [0, 1, 63, 64, 65, 200] {
+            let (a, b) = (random(len, 0.5, &mut rng), random(len, 0.5, &mut rng));
+            let each = |op: fn(bool, bool) -> bool| -> BitVec {
+                (0..len).map(|i| op(a.get(i), b.get(i))).collect()
+            };
+
+            let mut and = a.clone();
+            and &= &b;
+            assert_eq!(and, each(|x, y| x & y));
+            let mut or = a.clone();
+            or |= &b;
+            assert_eq!(or, each(|x, y| x | y));
+            let mut xor = a.clone();
+            xor ^= &b;
+            assert_eq!(xor, each(|x, y| x ^ y));
+            // the tail stays clear, or the count would include it
+            assert_eq!((!a.clone()).count_ones(), len - a.count_ones());
+            assert_eq!(!!a.clone(), a);
+        }
+    }
+
+    #[test]
+    fn test_iter_ones() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for density in [0.0, 0.01, 0.5, 1.0] {
+            let bits = random(1000, density, &mut rng);
+            let expected: Vec<usize> = (0..1000).filter(|&i| bits.get(i)).collect();
+            assert_eq!(bits.iter_ones().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(BitVec::new(0).iter_ones().next(), None);
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for (len, density) in [(0, 0.5), (1, 1.0), (512, 0.5), (3000, 0.02), (3000, 0.9)] {
+            let bits = random(len, density, &mut rng);
+            let index = bits.rank_index();
+            let ones: Vec<usize> = bits.iter_ones().collect();
+            assert_eq!(index.ones(), ones.len());
+
+            let mut rank = 0;
+            for i in 0..=len {
+                assert_eq!(index.rank(i), rank, "rank({}) of {} bits", i, len);
+                rank += (i < len && bits.get(i)) as usize;
+            }
+            for (k, &i) in ones.iter().enumerate() {
+                assert_eq!(index.select(k), Some(i), "select({})", k);
+            }
+            assert_eq!(index.select(ones.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_rotate_examples() {
+        // right moves bits up, toward the end of the string
+        let mut bits = parse("10010110");
+        bits.rotate_range(0..8, -1);
+        assert_eq!(bits, parse("00101101"));
+        bits.rotate_range(0..8, 1);
+        assert_eq!(bits, parse("10010110"));
+        bits.rotate_range(2..7, 2);
+        assert_eq!(bits, parse("10110100"));
+    }
+
+    #[test]
+    fn test_rotations_agree() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..500 {
+            let len = rng.random_range(0..150);
+            let bits = random(len, 0.5, &mut rng);
+            let start = rng.random_range(0..=len);
+            let end = rng.random_range(start..=len);
+            let by = rng.random_range(-200..200i64) as isize;
+
+            // rotating a Vec<bool> slice is the reference
+            let mut expected: Vec<bool> = (0..len).map(|i| bits.get(i)).collect();
+            let amount = if end > start {
+                by.rem_euclid((end - start) as isize) as usize
+            } else {
+                0
+            };
+            expected[start..end].rotate_right(amount);
+            let expected: BitVec = expected.into_iter().collect();
+
+            let (mut naive, mut reversal) = (bits.clone(), bits.clone());
+            naive.rotate_range_naive(start..end, by);
+            reversal.rotate_range(start..end, by);
+            assert_eq!(naive, expected, "naive {:?} by {}", start..end, by);
+            assert_eq!(reversal, expected, "reversal {:?} by {}", start..end, by);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitXor, Div, Not, Shl, Shr};
+
+// Bit hacks from the lecture, each one generic over the integer widths.
+//
+// The point of most of them is to replace a branch with arithmetic: a comparison result
+// becomes a mask of all ones or all zeros, and the mask selects between two values. That
+// only pays off when the branch is unpredictable, and modern compilers often emit cmov
+// for the plain version anyway, so every hack here is benchmarked against what the
+// standard library does (see src/bench/bit_hacks.rs).
+
+/// Operations the hacks need from any primitive integer, signed or not.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_bool(bit: bool) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_neg(self) -> Self;
+
+    /// All ones if `condition` holds, zero otherwise, without a branch.
+    #[inline(always)]
+    fn mask(condition: bool) -> Self {
+        Self::from_bool(condition).wrapping_neg()
+    }
+}
+
+/// Unsigned integers, where shifts are logical and every bit pattern is a count.
+pub trait Word: Integer + Div<Output = Self> {
+    /// Same width, two's complement.
+    type Signed: Integer;
+
+    const MAX: Self;
+    /// Sequence containing every log2(BITS)-bit pattern exactly once as a window.
+    const DE_BRUIJN: Self;
+    /// Position of each window of `DE_BRUIJN`, indexed by the window.
+    const DE_BRUIJN_TABLE: &'static [u8];
+
+    fn wrapping_mul(self, other: Self) -> Self;
+    fn low_byte(self) -> u8;
+    fn to_signed(self) -> Self::Signed;
+
+    /// Every other `shift`-bit group set, starting with the lowest: 0x55.. for 1, 0x33..
+    /// for 2, 0x0f0f.. for 4 and so on.
+    #[inline(always)]
+    fn alternating(shift: u32) -> Self {
+        Self::MAX / ((Self::ONE << shift).wrapping_add(Self::ONE))
+    }
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline(always)]
+                fn from_bool(bit: bool) -> Self {
+                    bit as $t
+                }
+
+                #[inline(always)]
+                fn wrapping_add(self, other: Self) -> Self {
+                    <$t>::wrapping_add(self, other)
+                }
+
+                #[inline(always)]
+                fn wrapping_sub(self, other: Self) -> Self {
+                    <$t>::wrapping_sub(self, other)
+                }
+
+                #[inline(always)]
+                fn wrapping_neg(self) -> Self {
+                    <$t>::wrapping_neg(self)
+                }
+            }
+        )*
+    };
+}
+
+integer!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+// The table inverts the de Bruijn sequence: window -> shift that produced it. Building it
+// in a const also checks the constant, a repeated window fails compilation.
+const fn de_bruijn_table<const BITS: usize>(sequence: u64) -> [u8; BITS] {
+    let window = BITS.trailing_zeros();
+    let mask = if BITS == 64 {
+        u64::MAX
+    } else {
+        (1 << BITS) - 1
+    };
+    let mut table = [u8::MAX; BITS];
+    let mut shift = 0;
+    while shift < BITS {
+        let index = (((sequence << shift) & mask) >> (BITS as u32 - window)) as usize;
+        assert!(table[index] == u8::MAX, "not a de Bruijn sequence");
+        table[index] = shift as u8;
+        shift += 1;
+    }
+    table
+}
+
+macro_rules! word {
+    ($($t:ty => $signed:ty, $de_bruijn:literal);*) => {
+        $(
+            impl Word for $t {
+                type Signed = $signed;
+
+                const MAX: Self = <$t>::MAX;
+                const DE_BRUIJN: Self = $de_bruijn;
+                const DE_BRUIJN_TABLE: &'static [u8] =
+                    &de_bruijn_table::<{ <$t>::BITS as usize }>($de_bruijn);
+
+                #[inline(always)]
+                fn wrapping_mul(self, other: Self) -> Self {
+                    <$t>::wrapping_mul(self, other)
+                }
+
+                #[inline(always)]
+                fn low_byte(self) -> u8 {
+                    self as u8
+                }
+
+                #[inline(always)]
+                fn to_signed(self) -> $signed {
+                    self as $signed
+                }
+            }
+        )*
+    };
+}
+
+word!(
+    u8 => i8, 0x17;
+    u16 => i16, 0x0f65;
+    u32 => i32, 0x077c_b531;
+    u64 => i64, 0x03f7_9d71_b4cb_0a89
+);
+
+/// Swaps without a temporary: each XOR folds one value into the other.
+///
+/// Slower than `std::mem::swap` on anything with registers to spare, since the three
+/// XORs depend on each other and a register rename costs nothing.
+#[inline]
+pub fn xor_swap<T: Integer>(x: &mut T, y: &mut T) {
+    *x = *x ^ *y; // x ^ y
+    *y = *x ^ *y; // (x ^ y) ^ y = x
+    *x = *x ^ *y; // (x ^ y) ^ x = y
+}
+
+/// `min(x, y)`: the mask is all ones when x < y and picks x.
+#[inline]
+pub fn branchless_min<T: Integer>(x: T, y: T) -> T {
+    y ^ ((x ^ y) & T::mask(x < y))
+}
+
+#[inline]
+pub fn branchless_max<T: Integer>(x: T, y: T) -> T {
+    x ^ ((x ^ y) & T::mask(x < y))
+}
+
+/// `(x + y) % n` for `x, y < n`, with a subtraction instead of a division.
+///
+/// The sum is below 2n, so subtracting n at most once is enough. When x + y wraps past
+/// `MAX` the true sum is even larger than n, which the carry accounts for.
+#[inline]
+pub fn add_mod<T: Word>(x: T, y: T, n: T) -> T {
+    debug_assert!(x < n && y < n, "operands must be reduced mod n");
+    let sum = x.wrapping_add(y);
+    let too_big = (sum < x) | (sum >= n);
+    sum.wrapping_sub(n & T::mask(too_big))
+}
+
+/// Smallest power of two that is at least `x`, 0 for 0 and for results past `MAX`.
+///
+/// Decrementing first keeps powers of two where they are. Then every bit below the
+/// highest set one gets set by smearing it right, which leaves 2^k - 1.
+#[inline]
+pub fn round_up_pow2<T: Word>(x: T) -> T {
+    let mut x = x.wrapping_sub(T::ONE);
+    let mut shift = 1;
+    while shift < T::BITS {
+        x = x | (x >> shift);
+        shift *= 2;
+    }
+    x.wrapping_add(T::ONE)
+}
+
+/// Lowest set bit of `x` as a mask, 0 for 0.
+///
+/// -x = !x + 1: the +1 carries through the trailing zeros of x (ones in !x) and stops at
+/// its lowest set bit, the only position where x and -x agree.
+#[inline]
+pub fn lowest_set_bit<T: Integer>(x: T) -> T {
+    x & x.wrapping_neg()
+}
+
+/// log2 of a power of two by de Bruijn multiplication.
+///
+/// Multiplying by 2^k shifts the de Bruijn sequence left by k, and since every window of
+/// log2(BITS) bits appears once in the sequence, the top window names k.
+#[inline]
+pub fn log2_pow2<T: Word>(x: T) -> u32 {
+    debug_assert!(
+        x != T::ZERO && lowest_set_bit(x) == x,
+        "{:?} isn't a power of two",
+        x
+    );
+    let window = T::BITS.trailing_zeros();
+    let index = x.wrapping_mul(T::DE_BRUIJN) >> (T::BITS - window);
+    T::DE_BRUIJN_TABLE[index.low_byte() as usize] as u32
+}
+
+/// Index of the lowest set bit, like `trailing_zeros`. Panics for 0.
+#[inline]
+pub fn trailing_zeros<T: Word>(x: T) -> u32 {
+    assert!(x != T::ZERO, "0 has no set bit");
+    log2_pow2(lowest_set_bit(x))
+}
+
+/// floor(log2 x), like `BITS - 1 - leading_zeros`. Panics for 0.
+///
+/// Smearing the highest set bit right and clearing everything below it leaves a power of
+/// two for the de Bruijn lookup.
+#[inline]
+pub fn log2<T: Word>(x: T) -> u32 {
+    assert!(x != T::ZERO, "log2 of 0");
+    let mut x = x;
+    let mut shift = 1;
+    while shift < T::BITS {
+        x = x | (x >> shift);
+        shift *= 2;
+    }
+    log2_pow2(x ^ (x >> 1))
+}
+
+// Set bits of every byte value
+const POPCOUNT_TABLE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 1;
+    while byte < 256 {
+        // the byte without its lowest bit is already in the table
+        table[byte] = table[byte >> 1] + (byte & 1) as u8;
+        byte += 1;
+    }
+    table
+};
+
+/// Set bits counted one byte at a time through a 256-entry table.
+#[inline]
+pub fn popcount_table<T: Word>(x: T) -> u32 {
+    let mut x = x;
+    let mut count = 0;
+    for _ in 0..T::BITS / 8 {
+        count += POPCOUNT_TABLE[x.low_byte() as usize] as u32;
+        x = x >> 7 >> 1; // a single shift by 8 overflows u8
+    }
+    count
+}
+
+/// Set bits counted in parallel: sum adjacent bits into 2-bit counts, those into 4-bit
+/// counts, those into bytes, then add all bytes up with one multiplication.
+#[inline]
+pub fn popcount_parallel<T: Word>(x: T) -> u32 {
+    let mut x = x;
+    let mut shift = 1;
+    while shift < 8 {
+        let mask = T::alternating(shift);
+        x = (x & mask).wrapping_add((x >> shift) & mask);
+        shift *= 2;
+    }
+    // 0x0101.. sums every byte into the top one, no byte count exceeds 64
+    let ones = T::MAX / (T::MAX >> (T::BITS - 8));
+    (x.wrapping_mul(ones) >> (T::BITS - 8)).low_byte() as u32
+}
+
+/// Bits of `x` in reverse order.
+///
+/// Swap adjacent bits, then adjacent pairs, nibbles and so on, log2(BITS) steps of two
+/// masks and two shifts each.
+#[inline]
+pub fn reverse_bits<T: Word>(x: T) -> T {
+    let mut x = x;
+    let mut shift = 1;
+    while shift < T::BITS {
+        let mask = T::alternating(shift);
+        x = ((x >> shift) & mask) | ((x & mask) << shift);
+        shift *= 2;
+    }
+    x
+}
+
+/// Reads the low `bits` bits of `x` as a two's complement number.
+///
+/// With m the sign bit, x ^ m maps 0..2^(bits-1) up by m and the negative half down by m,
+/// and subtracting m moves both into place: ((x ^ m) - m).
+#[inline]
+pub fn sign_extend<T: Word>(x: T, bits: u32) -> T::Signed {
+    assert!(0 < bits && bits <= T::BITS, "can't extend {} bits", bits);
+    let sign = T::ONE << (bits - 1);
+    let value = x & (T::MAX >> (T::BITS - bits));
+    (value ^ sign).wrapping_sub(sign).to_signed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    // Checks every hack against the standard library for one unsigned value
+    macro_rules! check_word {
+        ($t:ty, $x:expr) => {{
+            let x: $t = $x;
+            assert_eq!(popcount_table(x), x.count_ones(), "popcount_table({})", x);
+            assert_eq!(
+                popcount_parallel(x),
+                x.count_ones(),
+                "popcount_parallel({})",
+                x
+            );
+            assert_eq!(reverse_bits(x), x.reverse_bits(), "reverse_bits({})", x);
+            let lowest = if x == 0 { 0 } else { 1 << x.trailing_zeros() };
+            assert_eq!(lowest_set_bit(x), lowest, "lowest_set_bit({})", x);
+            // std rounds 0 up to 1
+            let expected = x
+                .checked_next_power_of_two()
+                .filter(|_| x != 0)
+                .unwrap_or(0);
+            assert_eq!(round_up_pow2(x), expected, "round_up_pow2({})", x);
+            if x != 0 {
+                assert_eq!(
+                    trailing_zeros(x),
+                    x.trailing_zeros(),
+                    "trailing_zeros({})",
+                    x
+                );
+                assert_eq!(log2(x), x.ilog2(), "log2({})", x);
+            }
+            for bits in 1..=<$t>::BITS {
+                let shift = <$t>::BITS - bits;
+                let expected = ((x << shift).to_signed()) >> shift;
+                assert_eq!(
+                    sign_extend(x, bits),
+                    expected,
+                    "sign_extend({}, {})",
+                    x,
+                    bits
+                );
+            }
+        }};
+    }
+
+    macro_rules! check_pair {
+        ($t:ty, $x:expr, $y:expr) => {{
+            let (x, y): ($t, $t) = ($x, $y);
+            assert_eq!(branchless_min(x, y), x.min(y), "min({}, {})", x, y);
+            assert_eq!(branchless_max(x, y), x.max(y), "max({}, {})", x, y);
+            let (mut a, mut b) = (x, y);
+            xor_swap(&mut a, &mut b);
+            assert_eq!((a, b), (y, x));
+        }};
+    }
+
+    #[test]
+    fn test_exhaustive_8_bit() {
+        for x in 0..=u8::MAX {
+            check_word!(u8, x);
+            for y in 0..=u8::MAX {
+                check_pair!(u8, x, y);
+                check_pair!(i8, x as i8, y as i8);
+                for n in (x.max(y)..u8::MAX).map(|m| m + 1) {
+                    assert_eq!(add_mod(x, y, n) as u32, (x as u32 + y as u32) % n as u32);
+                }
+            }
+        }
+    }
+
+    // Every value once; the pairs would take 2^32 runs, so one side sweeps the edges
+    #[test]
+    fn test_exhaustive_16_bit() {
+        let edges = [0, 1, 2, 0x7fff, 0x8000, 0x8001, 0xfffe, u16::MAX];
+        for x in 0..=u16::MAX {
+            check_word!(u16, x);
+            for y in edges {
+                check_pair!(u16, x, y);
+                check_pair!(i16, x as i16, y as i16);
+                for n in [x.max(y).saturating_add(1), u16::MAX] {
+                    if x < n && y < n {
+                        assert_eq!(add_mod(x, y, n) as u32, (x as u32 + y as u32) % n as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    quickcheck! {
+        fn prop_u32(x: u32, y: u32) -> bool {
+            check_word!(u32, x);
+            check_pair!(u32, x, y);
+            check_pair!(i32, x as i32, y as i32);
+            let n = x.max(y).saturating_add(1);
+            let (x, y) = (x % n, y % n);
+            add_mod(x, y, n) as u64 == (x as u64 + y as u64) % n as u64
+        }
+
+        fn prop_u64(x: u64, y: u64) -> bool {
+            check_word!(u64, x);
+            check_pair!(u64, x, y);
+            check_pair!(i64, x as i64, y as i64);
+            let n = x.max(y).saturating_add(1);
+            let (x, y) = (x % n, y % n);
+            add_mod(x, y, n) as u128 == (x as u128 + y as u128) % n as u128
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "log2 of 0")]
+    fn test_log2_of_zero() {
+        log2(0u32);
+    }
+}
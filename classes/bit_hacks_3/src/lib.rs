@@ -0,0 +1,4 @@
+pub mod bitvec;
+pub mod hacks;
+pub mod merge;
+pub mod queens;
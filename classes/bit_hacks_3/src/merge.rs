@@ -0,0 +1,314 @@
+use std::hint::select_unpredictable;
+use utils::simd::{I32x4, Simd};
+
+// Merging two sorted runs is one comparison per output element, and on random data the
+// comparison is a coin flip: a branch on it mispredicts half the time, about 15 cycles
+// each. The branchless merge computes both outcomes and selects with a conditional move,
+// so the cost is the same whatever the data. On data where one run wins long stretches
+// (already sorted, few distinct values) the branch predicts well and the plain merge
+// wins instead.
+//
+// All merges write into a caller-provided slice of exactly a.len() + b.len() elements and
+// take from `a` on ties, which keeps the merge stable.
+
+fn check_output_len(a: usize, b: usize, out: usize) {
+    assert_eq!(
+        out,
+        a + b,
+        "merging {} and {} elements into {} slots",
+        a,
+        b,
+        out
+    );
+}
+
+/// Merges sorted `a` and `b` into `out`, branching on every comparison.
+pub fn merge_into<T: Ord + Copy>(a: &[T], b: &[T], out: &mut [T]) {
+    check_output_len(a.len(), b.len(), out.len());
+    let (mut i, mut j) = (0, 0);
+
+    for slot in out.iter_mut() {
+        if j == b.len() || (i < a.len() && a[i] <= b[j]) {
+            *slot = a[i];
+            i += 1;
+        } else {
+            *slot = b[j];
+            j += 1;
+        }
+    }
+}
+
+/// Merges sorted `a` and `b` into `out` with conditional moves instead of branches.
+///
+/// Both candidates are loaded, the comparison selects one and advances one index by
+/// adding the comparison result. The only branches left are the loop conditions, which
+/// predict well. The tails are plain copies.
+pub fn merge_branchless_into<T: Ord + Copy>(a: &[T], b: &[T], out: &mut [T]) {
+    check_output_len(a.len(), b.len(), out.len());
+    let (mut i, mut j, mut k) = (0, 0, 0);
+
+    while i < a.len() && j < b.len() {
+        let (x, y) = (a[i], b[j]);
+        let take_a = x <= y;
+        // select_unpredictable asks for a cmov, a plain if may be turned into a branch
+        out[k] = select_unpredictable(take_a, x, y);
+        i += take_a as usize;
+        j += !take_a as usize;
+        k += 1;
+    }
+
+    out[k..k + a.len() - i].copy_from_slice(&a[i..]);
+    out[k + a.len() - i..].copy_from_slice(&b[j..]);
+}
+
+// Sorts the 8 values of two sorted vectors with a bitonic network: reverse one to get a
+// bitonic sequence, then min/max at distance 4, 2 and 1. Returns (lowest 4, highest 4).
+#[inline(always)]
+fn bitonic_merge(a: I32x4, b: I32x4) -> (I32x4, I32x4) {
+    let [b0, b1, b2, b3] = b.to_array();
+    let b = I32x4::from_array([b3, b2, b1, b0]);
+    let [l0, l1, l2, l3] = a.min(b).to_array();
+    let [h0, h1, h2, h3] = a.max(b).to_array();
+
+    let u = I32x4::from_array([l0, l1, h0, h1]);
+    let v = I32x4::from_array([l2, l3, h2, h3]);
+    let [n0, n1, n2, n3] = u.min(v).to_array();
+    let [x0, x1, x2, x3] = u.max(v).to_array();
+
+    let u = I32x4::from_array([n0, x0, n2, x2]);
+    let v = I32x4::from_array([n1, x1, n3, x3]);
+    let [n0, n1, n2, n3] = u.min(v).to_array();
+    let [x0, x1, x2, x3] = u.max(v).to_array();
+
+    (
+        I32x4::from_array([n0, x0, n1, x1]),
+        I32x4::from_array([n2, x2, n3, x3]),
+    )
+}
+
+/// Merges sorted `a` and `b` into `out` four elements at a time with a bitonic network.
+///
+/// Only one comparison per four outputs decides which input the next chunk comes from;
+/// the network itself is min/max instructions.
+pub fn merge_simd_into(a: &[i32], b: &[i32], out: &mut [i32]) {
+    const LANES: usize = I32x4::LANES;
+
+    check_output_len(a.len(), b.len(), out.len());
+    if a.len() < LANES || b.len() < LANES {
+        return merge_into(a, b, out);
+    }
+
+    let mut low = I32x4::load(a);
+    let mut high = I32x4::load(b);
+    let (mut i, mut j, mut k) = (LANES, LANES, 0);
+
+    loop {
+        (low, high) = bitonic_merge(low, high);
+        low.store(&mut out[k..]);
+        k += LANES;
+
+        // Everything stored is at most `high` and both remaining inputs, as long as the
+        // next chunk comes from the input with the smaller head
+        let take_a = j == b.len() || (i < a.len() && a[i] <= b[j]);
+        let next = if take_a { &a[i..] } else { &b[j..] };
+        if next.len() < LANES {
+            break;
+        }
+
+        low = I32x4::load(next);
+        if take_a {
+            i += LANES;
+        } else {
+            j += LANES;
+        }
+    }
+
+    // Merge what's left of the inputs behind a gap of LANES slots, then merge `high`
+    // into the gap. Writes trail reads by the number of `high` elements still pending,
+    // so nothing gets overwritten before it's read.
+    merge_into(&a[i..], &b[j..], &mut out[k + LANES..]);
+    let high = high.to_array();
+    let (mut h, mut r) = (0, k + LANES);
+    while h < LANES {
+        if r == out.len() || high[h] <= out[r] {
+            out[k] = high[h];
+            h += 1;
+        } else {
+            out[k] = out[r];
+            r += 1;
+        }
+        k += 1;
+    }
+}
+
+// Runs this short are insertion sorted before merging starts, the first few merge passes
+// would cost more than they save
+const RUN: usize = 16;
+
+/// Stable bottom-up merge sort, allocating one scratch buffer the size of `data`.
+pub fn merge_sort<T: Ord + Copy>(data: &mut [T]) {
+    let mut scratch = data.to_vec();
+    merge_sort_with(data, &mut scratch);
+}
+
+/// Stable bottom-up merge sort with caller-provided scratch space of the same length.
+///
+/// Each pass merges pairs of sorted runs of width w from one buffer into the other, then
+/// the buffers swap roles (ping-pong) and w doubles. Nothing is copied back until the
+/// end, and only if the last pass landed in `scratch`.
+pub fn merge_sort_with<T: Ord + Copy>(data: &mut [T], scratch: &mut [T]) {
+    assert_eq!(data.len(), scratch.len(), "scratch must match the data");
+    let n = data.len();
+
+    for run in data.chunks_mut(RUN) {
+        insertion_sort(run);
+    }
+
+    let (mut from, mut to) = (&mut *data, &mut *scratch);
+    let mut in_scratch = false;
+    let mut width = RUN;
+    while width < n {
+        for (start, out) in (0..n).step_by(2 * width).zip(to.chunks_mut(2 * width)) {
+            let middle = (start + width).min(n);
+            let end = (start + 2 * width).min(n);
+            merge_branchless_into(&from[start..middle], &from[middle..end], out);
+        }
+        (from, to) = (to, from);
+        in_scratch = !in_scratch;
+        width *= 2;
+    }
+
+    if in_scratch {
+        data.copy_from_slice(scratch);
+    }
+}
+
+fn insertion_sort<T: Ord + Copy>(run: &mut [T]) {
+    for i in 1..run.len() {
+        let value = run[i];
+        let mut j = i;
+        while j > 0 && run[j - 1] > value {
+            run[j] = run[j - 1];
+            j -= 1;
+        }
+        run[j] = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    // short runs with a random number of duplicates
+    fn sorted(rng: &mut StdRng) -> Vec<i32> {
+        let (len, range) = (rng.random_range(0..40), rng.random_range(1..50));
+        let mut values: Vec<i32> = (0..len).map(|_| rng.random_range(-range..range)).collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_merges_match_sorting() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..2000 {
+            let (a, b) = (sorted(&mut rng), sorted(&mut rng));
+            let mut expected = [a.clone(), b.clone()].concat();
+            expected.sort();
+
+            type Merge = fn(&[i32], &[i32], &mut [i32]);
+            let merges: [(&str, Merge); 3] = [
+                ("merge_into", merge_into),
+                ("merge_branchless_into", merge_branchless_into),
+                ("merge_simd_into", merge_simd_into),
+            ];
+            for (name, merge) in merges {
+                let mut out = vec![0; a.len() + b.len()];
+                merge(&a, &b, &mut out);
+                assert_eq!(out, expected, "{} of {:?} and {:?}", name, a, b);
+            }
+        }
+    }
+
+    // Compared by key only, `index` records where an element started out
+    #[derive(Clone, Copy, Debug)]
+    struct Keyed {
+        key: u8,
+        index: usize,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for Keyed {}
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    fn is_stable(sorted: &[Keyed]) -> bool {
+        sorted
+            .windows(2)
+            .all(|pair| pair[0].key < pair[1].key || pair[0].index < pair[1].index)
+    }
+
+    #[test]
+    fn test_merges_are_generic_and_stable() {
+        let keyed = |keys: &[u8], first: usize| -> Vec<Keyed> {
+            (first..)
+                .zip(keys)
+                .map(|(index, &key)| Keyed { key, index })
+                .collect()
+        };
+        let a = keyed(&[1, 2, 2, 5], 0);
+        let b = keyed(&[1, 2, 3], 4);
+        let mut out = vec![Keyed { key: 0, index: 0 }; 7];
+        merge_into(&a, &b, &mut out);
+        assert!(is_stable(&out), "{:?}", out);
+        merge_branchless_into(&a, &b, &mut out);
+        assert!(is_stable(&out), "{:?}", out);
+
+        let mut words = [""; 4];
+        merge_branchless_into(&["apple", "cherry"], &["banana", "date"], &mut words);
+        assert_eq!(words, ["apple", "banana", "cherry", "date"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "into 2 slots")]
+    fn test_output_length_is_checked() {
+        merge_into(&[1, 2], &[3], &mut [0; 2]);
+    }
+
+    #[test]
+    fn test_merge_sort() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for len in (0..200).chain([1000, 4099]) {
+            let mut data: Vec<u16> = (0..len).map(|_| rng.random_range(0..100)).collect();
+            let mut expected = data.clone();
+            expected.sort();
+            merge_sort(&mut data);
+            assert_eq!(data, expected, "length {}", len);
+        }
+
+        let mut keyed: Vec<Keyed> = (0..1000)
+            .map(|index| Keyed {
+                key: rng.random_range(0..10),
+                index,
+            })
+            .collect();
+        merge_sort(&mut keyed);
+        assert!(keyed.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(is_stable(&keyed));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::hacks::lowest_set_bit;
+use rayon::prelude::*;
+
+// N-Queens: place n queens on an n x n board so that no two share a row, column or
+// diagonal, and count the ways.
+//
+// All three solvers place one queen per row, top to bottom. The naive one keeps the
+// column of every placed queen and checks a candidate square against each of them. The
+// bitboard one keeps three masks of attacked columns in the current row instead:
+//
+//   columns  every column with a queen
+//   left     squares attacked along down-left diagonals, shifted left once per row
+//   right    squares attacked along down-right diagonals, shifted right once per row
+//
+// so the free squares of a row are !(columns | left | right), and the loop over them
+// peels off the lowest set bit until nothing is left. No square is ever tested and
+// rejected.
+
+/// Solutions for n = 0, 1, 2, ... (OEIS A000170).
+pub const KNOWN_COUNTS: [u64; 24] = [
+    1,
+    1,
+    0,
+    0,
+    2,
+    10,
+    4,
+    40,
+    92,
+    352,
+    724,
+    2_680,
+    14_200,
+    73_712,
+    365_596,
+    2_279_184,
+    14_772_512,
+    95_815_104,
+    666_090_624,
+    4_968_057_848,
+    39_029_188_884,
+    314_666_222_712,
+    2_691_008_701_644,
+    24_233_937_684_440,
+];
+
+/// Largest board the bitboard solvers handle, one bit per column.
+pub const MAX_N: usize = u32::BITS as usize;
+
+/// Backtracking over an array of queen columns, O(row) work per candidate square.
+pub fn count_naive(n: usize) -> u64 {
+    fn place(queens: &mut Vec<usize>, n: usize) -> u64 {
+        let row = queens.len();
+        if row == n {
+            return 1;
+        }
+
+        let mut count = 0;
+        for col in 0..n {
+            let attacked = queens
+                .iter()
+                .enumerate()
+                .any(|(r, &c)| c == col || row - r == col.abs_diff(c));
+            if !attacked {
+                queens.push(col);
+                count += place(queens, n);
+                queens.pop();
+            }
+        }
+        count
+    }
+
+    place(&mut Vec::with_capacity(n), n)
+}
+
+// Solutions below one partial placement
+fn count_below(all: u32, columns: u32, left: u32, right: u32) -> u64 {
+    if columns == all {
+        return 1;
+    }
+
+    let mut free = all & !(columns | left | right);
+    let mut count = 0;
+    while free != 0 {
+        let queen = lowest_set_bit(free);
+        free ^= queen;
+        count += count_below(
+            all,
+            columns | queen,
+            (left | queen) << 1,
+            (right | queen) >> 1,
+        );
+    }
+    count
+}
+
+fn board(n: usize) -> u32 {
+    assert!(n <= MAX_N, "{}x{} doesn't fit a u32 bitboard", n, n);
+    // the lowest n bits; shifting by 32 would overflow for n = 0
+    u32::MAX.checked_shr((MAX_N - n) as u32).unwrap_or(0)
+}
+
+/// Backtracking over three attack masks, O(1) work per placed queen.
+pub fn count_bitboard(n: usize) -> u64 {
+    count_below(board(n), 0, 0, 0)
+}
+
+/// The bitboard search with the first `split_depth` rows expanded in parallel.
+///
+/// Every placement at the split depth becomes a rayon task. Deeper splits make more,
+/// smaller tasks: enough of them keeps every core busy even though subtrees differ a
+/// lot in size, too many and the task overhead shows. Depth 0 is the sequential search.
+pub fn count_parallel(n: usize, split_depth: usize) -> u64 {
+    fn expand(all: u32, columns: u32, left: u32, right: u32, depth: usize) -> u64 {
+        if depth == 0 || columns == all {
+            return count_below(all, columns, left, right);
+        }
+
+        let mut free = all & !(columns | left | right);
+        let mut children = Vec::with_capacity(free.count_ones() as usize);
+        while free != 0 {
+            let queen = lowest_set_bit(free);
+            free ^= queen;
+            children.push((columns | queen, (left | queen) << 1, (right | queen) >> 1));
+        }
+        children
+            .into_par_iter()
+            .map(|(columns, left, right)| expand(all, columns, left, right, depth - 1))
+            .sum()
+    }
+
+    expand(board(n), 0, 0, 0, split_depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solvers_match_known_counts() {
+        for (n, &known) in KNOWN_COUNTS.iter().enumerate().take(13) {
+            if n <= 10 {
+                assert_eq!(count_naive(n), known, "naive, n = {}", n);
+            }
+            assert_eq!(count_bitboard(n), known, "bitboard, n = {}", n);
+            for split_depth in [0, 1, 3, n + 1] {
+                assert_eq!(
+                    count_parallel(n, split_depth),
+                    known,
+                    "parallel split at {}, n = {}",
+                    split_depth,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_width_board() {
+        assert_eq!(board(MAX_N), u32::MAX);
+        assert_eq!(board(0), 0);
+    }
+}
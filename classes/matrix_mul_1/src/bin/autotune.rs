@@ -0,0 +1,41 @@
+use matrix_mul_1::blis::{autotune, tuning_file};
+use matrix_mul_1::kernels::Backend;
+
+// Searches BLIS block sizes for the fastest backend of this machine and saves the winner,
+// which `blis::matrix_multiply_blis` picks up from then on. The file is blis.conf in the
+// working directory, or wherever BLIS_TUNING_FILE points.
+//
+//   cargo run --release --bin autotune -- 1024
+
+const N: usize = 768;
+
+fn main() {
+    let n = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("N must be a number"))
+        .unwrap_or(N);
+    let backend = Backend::detect();
+
+    let (sizes, gflops) = autotune(
+        backend,
+        n,
+        &[24, 48, 96, 192],
+        &[64, 128, 256, 512],
+        &[512, 2048, 8192],
+    )
+    .expect("no candidate block sizes produced a correct product");
+    println!(
+        "{}: mc = {}, kc = {}, nc = {} at {:.2} GFLOP/s",
+        backend.name(),
+        sizes.mc,
+        sizes.kc,
+        sizes.nc,
+        gflops
+    );
+
+    let path = tuning_file();
+    sizes
+        .save(&path, backend)
+        .unwrap_or_else(|err| panic!("can't write {}: {}", path.display(), err));
+    println!("saved to {}", path.display());
+}
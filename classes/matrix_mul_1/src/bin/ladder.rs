@@ -0,0 +1,71 @@
+use matrix_mul_1::ladder::{ladder, peak_gflops};
+use matrix_mul_1::verify::{random_matrix, verify_sampled};
+use std::time::Instant;
+use utils::DenseMatrix;
+
+// Prints the lecture-1 table for an N x N multiply. Each rung is checked against the
+// reference GEMM before its time is printed.
+//
+//   cargo run --release --bin ladder -- 2048
+//   PEAK_GFLOPS=150 cargo run --release --bin ladder
+//
+// Without PEAK_GFLOPS the peak is estimated from /proc/cpuinfo and marked with a ~.
+
+const N: usize = 1024;
+const SAMPLED_ROWS: usize = 8;
+
+fn main() {
+    let n = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("N must be a number"))
+        .unwrap_or(N);
+    let a = random_matrix(n, n, 1);
+    let b = random_matrix(n, n, 2);
+    let flops = 2.0 * (n * n * n) as f64;
+    let peak = peak_gflops();
+
+    println!(
+        "{} x {}, {} build, peak {}",
+        n,
+        n,
+        if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+        peak.map_or("unknown".to_string(), |p| format!(
+            "{}{:.1} GFLOP/s",
+            if p.estimated { "~" } else { "" },
+            p.gflops
+        ))
+    );
+    println!(
+        "{:<28} {:>10} {:>9} {:>10} {:>8}",
+        "rung", "seconds", "GFLOP/s", "speedup", "% peak"
+    );
+
+    let mut naive = None;
+    for rung in ladder() {
+        let mut c = DenseMatrix::new(n, n);
+        let start = Instant::now();
+        (rung.multiply)(&a, &b, &mut c);
+        let seconds = start.elapsed().as_secs_f64();
+
+        if let Err(mismatch) = verify_sampled(&a, &b, &c, SAMPLED_ROWS, 3) {
+            panic!("{} computed a wrong product: {}", rung.name, mismatch);
+        }
+        let naive = *naive.get_or_insert(seconds);
+        let gflops = flops / seconds / 1e9;
+        println!(
+            "{:<28} {:>10.4} {:>9.2} {:>9.1}x {:>8}",
+            rung.name,
+            seconds,
+            gflops,
+            naive / seconds,
+            peak.map_or("-".to_string(), |p| format!(
+                "{:.2}",
+                100.0 * gflops / p.gflops
+            ))
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use matrix_mul_1::blis::matrix_multiply_blis;
+use matrix_mul_1::kernels::Backend;
+use matrix_mul_1::ladder::parallel_tiled;
+use matrix_mul_1::oblivious::matrix_multiply_oblivious;
+use matrix_mul_1::verify::{random_matrix, verify_sampled};
+use std::time::Instant;
+use utils::DenseMatrix;
+
+// Cache-oblivious recursion against the two cache-aware versions, on sizes at and around
+// powers of two. Powers of two are where fixed tiles and the recursion line up exactly and
+// where set-associative caches suffer most from the row stride.
+//
+//   cargo run --release --bin oblivious
+
+const SIZES: [usize; 9] = [255, 256, 257, 511, 512, 513, 768, 1000, 1024];
+
+fn main() {
+    let backend = Backend::detect();
+    type Multiply = fn(&DenseMatrix<f64>, &DenseMatrix<f64>, &mut DenseMatrix<f64>);
+    let variants: [(&str, Multiply); 3] = [
+        ("tiled", parallel_tiled),
+        ("oblivious", matrix_multiply_oblivious),
+        ("blis", |a, b, c| {
+            matrix_multiply_blis(Backend::detect(), a, b, c)
+        }),
+    ];
+
+    print!("{:>6}", "n");
+    for (name, _) in variants {
+        print!(" {:>18}", format!("{} GFLOP/s", name));
+    }
+    println!("   ({:?})", backend);
+
+    for n in SIZES {
+        let a = random_matrix(n, n, 1);
+        let b = random_matrix(n, n, 2);
+        print!("{:>6}", n);
+        for (name, multiply) in variants {
+            let mut c = DenseMatrix::new(n, n);
+            let start = Instant::now();
+            multiply(&a, &b, &mut c);
+            let seconds = start.elapsed().as_secs_f64();
+            if let Err(mismatch) = verify_sampled(&a, &b, &c, 4, 3) {
+                panic!("{} computed a wrong product: {}", name, mismatch);
+            }
+            print!(" {:>18.2}", 2.0 * (n * n * n) as f64 / seconds / 1e9);
+        }
+        println!();
+    }
+}
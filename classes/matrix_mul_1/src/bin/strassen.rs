@@ -0,0 +1,61 @@
+use matrix_mul_1::blis::matrix_multiply_blis;
+use matrix_mul_1::kernels::Backend;
+use matrix_mul_1::strassen::{matrix_multiply_fast, relative_error, Algorithm, FastConfig};
+use matrix_mul_1::verify::random_matrix;
+use std::time::Instant;
+use utils::DenseMatrix;
+
+// Strassen and Winograd against the classic blocked GEMM they bottom out in, for a range
+// of crossovers. The error column is max |C_fast - C_classic| / max |C_classic|; the
+// classic product itself is within a few k·u of the exact one.
+//
+//   cargo run --release --bin strassen [n] [crossover...]
+
+const CROSSOVERS: [usize; 4] = [128, 256, 512, 1024];
+
+fn main() {
+    let mut args = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("not a number"));
+    let n: usize = args.next().unwrap_or(2048);
+    let crossovers: Vec<usize> = match args.collect::<Vec<_>>() {
+        given if given.is_empty() => CROSSOVERS.to_vec(),
+        given => given,
+    };
+
+    let a = random_matrix(n, n, 1);
+    let b = random_matrix(n, n, 2);
+    let mut classic = DenseMatrix::new(n, n);
+    let start = Instant::now();
+    matrix_multiply_blis(Backend::detect(), &a, &b, &mut classic);
+    let classic_seconds = start.elapsed().as_secs_f64();
+
+    println!(
+        "{:<10} {:>9} {:>10} {:>8} {:>10}",
+        "algorithm", "crossover", "seconds", "speedup", "rel error"
+    );
+    println!(
+        "{:<10} {:>9} {:>10.3} {:>8.2} {:>10}",
+        "classic", "-", classic_seconds, 1.0, "-"
+    );
+    for algorithm in [Algorithm::Strassen, Algorithm::Winograd] {
+        for &crossover in &crossovers {
+            let config = FastConfig {
+                crossover,
+                ..FastConfig::default()
+            };
+            let mut c = DenseMatrix::new(n, n);
+            let start = Instant::now();
+            matrix_multiply_fast(algorithm, config, &a, &b, &mut c);
+            let seconds = start.elapsed().as_secs_f64();
+            println!(
+                "{:<10} {:>9} {:>10.3} {:>8.2} {:>10.1e}",
+                format!("{:?}", algorithm).to_lowercase(),
+                crossover,
+                seconds,
+                classic_seconds / seconds,
+                relative_error(&c, &classic)
+            );
+        }
+    }
+}
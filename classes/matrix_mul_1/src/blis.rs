@@ -0,0 +1,377 @@
+use crate::kernels::{Backend, MicroKernel};
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use utils::aligned::{AlignedVec, CACHE_LINE};
+use utils::{DenseMatrix, MatrixView, MatrixViewMut};
+
+// BLIS-style GEMM (Van Zee and van de Geijn, "BLIS: A Framework for Rapidly Instantiating
+// BLAS Functionality", 2015). Five loops around a micro-kernel:
+//
+//   for jc in 0..n step NC                 B panel KC x NC lives in L3
+//     for pc in 0..k step KC               pack B[pc.., jc..] into NR-wide micro-panels
+//       for ic in 0..m step MC (parallel)  pack A[ic.., pc..] into MR-tall micro-panels,
+//                                          the MC x KC block lives in L2
+//         for jr in 0..NC step NR          one KC x NR micro-panel of B lives in L1
+//           for ir in 0..MC step MR        MR x NR block of C in registers
+//
+// Packing copies each block once into the exact order the micro-kernel reads it: unit
+// stride, aligned, no TLB misses, whatever the strides of the original matrix. Edges are
+// zero-padded so the micro-kernel always computes a full tile.
+
+/// MC, KC and NC of the blocking. MC is rounded up to a multiple of MR and NC of NR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSizes {
+    pub mc: usize,
+    pub kc: usize,
+    pub nc: usize,
+}
+
+impl Default for BlockSizes {
+    /// Reasonable for 32 KB L1, 256 KB-1 MB L2: a 96 x 256 block of A is 192 KB.
+    fn default() -> Self {
+        Self {
+            mc: 96,
+            kc: 256,
+            nc: 4096,
+        }
+    }
+}
+
+/// Where `autotune` results are kept unless `TUNING_FILE_VAR` says otherwise, relative to
+/// the working directory.
+pub const TUNING_FILE: &str = "blis.conf";
+pub const TUNING_FILE_VAR: &str = "BLIS_TUNING_FILE";
+
+/// `$BLIS_TUNING_FILE` if set, `TUNING_FILE` otherwise.
+pub fn tuning_file() -> PathBuf {
+    std::env::var_os(TUNING_FILE_VAR).map_or_else(|| TUNING_FILE.into(), PathBuf::from)
+}
+
+impl BlockSizes {
+    /// The autotuned sizes for `backend` if `tuning_file()` has them, the defaults otherwise.
+    ///
+    /// The file is read on the first call only, every GEMM after that reuses the result.
+    pub fn tuned(backend: Backend) -> Self {
+        static TUNED: OnceLock<Option<(Backend, BlockSizes)>> = OnceLock::new();
+        TUNED
+            .get_or_init(|| Self::read(tuning_file()))
+            .filter(|&(tuned_for, _)| tuned_for == backend)
+            .map_or_else(Self::default, |(_, sizes)| sizes)
+    }
+
+    // No larger than the problem, so small products don't pay for full-size buffers
+    fn clamped(self, m: usize, k: usize, n: usize) -> Self {
+        Self {
+            mc: self.mc.min(m),
+            kc: self.kc.min(k),
+            nc: self.nc.min(n),
+        }
+    }
+
+    fn fitted(self, kernel: &MicroKernel) -> Self {
+        Self {
+            mc: self.mc.max(1).div_ceil(kernel.mr) * kernel.mr,
+            kc: self.kc.max(1),
+            nc: self.nc.max(1).div_ceil(kernel.nr) * kernel.nr,
+        }
+    }
+
+    /// Reads a configuration written by `save` for the same backend. None if the file is
+    /// missing, malformed, or was tuned for another backend.
+    pub fn load(path: impl AsRef<Path>, backend: Backend) -> Option<Self> {
+        Self::read(path)
+            .filter(|&(tuned_for, _)| tuned_for == backend)
+            .map(|(_, sizes)| sizes)
+    }
+
+    fn read(path: impl AsRef<Path>) -> Option<(Backend, Self)> {
+        let text = fs::read_to_string(path).ok()?;
+        let mut fields = text.lines().filter_map(|line| line.split_once('='));
+        let mut next = |key: &str| {
+            let (name, value) = fields.next()?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        };
+
+        let backend = Backend::from_name(&next("backend")?)?;
+        let sizes = Self {
+            mc: next("mc")?.parse().ok()?,
+            kc: next("kc")?.parse().ok()?,
+            nc: next("nc")?.parse().ok()?,
+        };
+        Some((backend, sizes))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>, backend: Backend) -> io::Result<()> {
+        fs::write(
+            path,
+            format!(
+                "backend = {}\nmc = {}\nkc = {}\nnc = {}\n",
+                backend.name(),
+                self.mc,
+                self.kc,
+                self.nc
+            ),
+        )
+    }
+}
+
+/// C += A·B with the blocking and micro-kernel of `backend`, for matrices of any strides.
+pub fn gemm(
+    backend: Backend,
+    sizes: BlockSizes,
+    a: MatrixView<'_, f64>,
+    b: MatrixView<'_, f64>,
+    c: MatrixViewMut<'_, f64>,
+) {
+    gemm_scaled(backend, sizes, 1.0, a, b, c)
+}
+
+/// C += alpha·A·B. alpha is applied while packing A, so it costs nothing in the kernel.
+pub fn gemm_scaled(
+    backend: Backend,
+    sizes: BlockSizes,
+    alpha: f64,
+    a: MatrixView<'_, f64>,
+    b: MatrixView<'_, f64>,
+    mut c: MatrixViewMut<'_, f64>,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(k, b.rows(), "inner dimensions of A and B differ");
+    assert_eq!((c.rows(), c.cols()), (m, n), "C must be {}x{}", m, n);
+
+    let kernel = backend.micro_kernel();
+    let BlockSizes { mc, kc, nc } = sizes.clamped(m, k, n).fitted(&kernel);
+    let mut packed_b = AlignedVec::from_elem(0.0, kc * nc, CACHE_LINE);
+
+    for jc in (0..n).step_by(nc) {
+        let nc_cur = nc.min(n - jc);
+        for pc in (0..k).step_by(kc) {
+            let kc_cur = kc.min(k - pc);
+            pack_b(
+                b.view(pc..pc + kc_cur, jc..jc + nc_cur),
+                kernel.nr,
+                &mut packed_b,
+            );
+
+            // disjoint MC-row blocks of this column panel of C
+            let mut blocks = Vec::with_capacity(m.div_ceil(mc));
+            let mut rest = c.view_mut(0..m, jc..jc + nc_cur);
+            while rest.rows() > mc {
+                let (block, tail) = rest.split_at_row(mc);
+                blocks.push(block);
+                rest = tail;
+            }
+            blocks.push(rest);
+
+            let packed_b = &packed_b[..];
+            blocks.into_par_iter().enumerate().for_each_init(
+                || AlignedVec::from_elem(0.0, mc * kc, CACHE_LINE),
+                |packed_a, (block, c_block)| {
+                    let ic = block * mc;
+                    let a_block = a.view(ic..ic + c_block.rows(), pc..pc + kc_cur);
+                    pack_a(a_block, alpha, kernel.mr, packed_a);
+                    macro_kernel(&kernel, kc_cur, packed_a, packed_b, c_block);
+                },
+            );
+        }
+    }
+}
+
+/// `gemm` on dense matrices, with the tuned block sizes.
+pub fn matrix_multiply_blis(
+    backend: Backend,
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    gemm(
+        backend,
+        BlockSizes::tuned(backend),
+        a.as_view(),
+        b.as_view(),
+        c.as_view_mut(),
+    )
+}
+
+// MR-tall micro-panels of alpha·A: panel r holds rows r*MR.., column by column, zero-padded
+// rows
+fn pack_a(a: MatrixView<'_, f64>, alpha: f64, mr: usize, packed: &mut [f64]) {
+    let kc = a.cols();
+    for (panel, out) in packed
+        .chunks_exact_mut(mr * kc)
+        .take(a.rows().div_ceil(mr))
+        .enumerate()
+    {
+        let first = panel * mr;
+        let rows = mr.min(a.rows() - first);
+        for (p, column) in out.chunks_exact_mut(mr).enumerate() {
+            for (i, value) in column.iter_mut().enumerate() {
+                *value = if i < rows {
+                    alpha * a.get(first + i, p)
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+}
+
+// NR-wide micro-panels: panel r holds columns r*NR.., row by row, zero-padded columns
+fn pack_b(b: MatrixView<'_, f64>, nr: usize, packed: &mut [f64]) {
+    let kc = b.rows();
+    for (panel, out) in packed
+        .chunks_exact_mut(nr * kc)
+        .take(b.cols().div_ceil(nr))
+        .enumerate()
+    {
+        let first = panel * nr;
+        let cols = nr.min(b.cols() - first);
+        for (p, row) in out.chunks_exact_mut(nr).enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = if j < cols { b.get(p, first + j) } else { 0.0 };
+            }
+        }
+    }
+}
+
+fn macro_kernel(
+    kernel: &MicroKernel,
+    kc: usize,
+    packed_a: &[f64],
+    packed_b: &[f64],
+    mut c: MatrixViewMut<'_, f64>,
+) {
+    let (mr, nr) = (kernel.mr, kernel.nr);
+    let mut tile = [0.0; MAX_TILE];
+    for jr in (0..c.cols()).step_by(nr) {
+        let b_panel = &packed_b[jr * kc..(jr + nr) * kc];
+        for ir in (0..c.rows()).step_by(mr) {
+            kernel.run(kc, &packed_a[ir * kc..(ir + mr) * kc], b_panel, &mut tile);
+
+            // only the part of the tile that lies inside C
+            let rows = mr.min(c.rows() - ir);
+            let cols = nr.min(c.cols() - jr);
+            for i in 0..rows {
+                for j in 0..cols {
+                    c[(ir + i, jr + j)] += tile[i * nr + j];
+                }
+            }
+        }
+    }
+}
+
+// Largest MR x NR of any backend
+const MAX_TILE: usize = 64;
+
+/// Times `gemm` on an n x n product for every combination of candidate sizes and returns
+/// the fastest, with its GFLOP/s. A candidate only counts once its product passes
+/// `verify_sampled`, None if none does.
+pub fn autotune(
+    backend: Backend,
+    n: usize,
+    mcs: &[usize],
+    kcs: &[usize],
+    ncs: &[usize],
+) -> Option<(BlockSizes, f64)> {
+    let a = crate::verify::random_matrix(n, n, 1);
+    let b = crate::verify::random_matrix(n, n, 2);
+    let mut c = DenseMatrix::new(n, n);
+    let flops = 2.0 * (n * n * n) as f64;
+
+    let mut best = None;
+    for &mc in mcs {
+        for &kc in kcs {
+            for &nc in ncs {
+                let sizes = BlockSizes { mc, kc, nc };
+                let seconds = (0..3)
+                    .map(|_| {
+                        // C += A·B, so start from zero for the check below
+                        c.fill(0.0);
+                        let start = Instant::now();
+                        gemm(backend, sizes, a.as_view(), b.as_view(), c.as_view_mut());
+                        start.elapsed().as_secs_f64()
+                    })
+                    .fold(f64::INFINITY, f64::min);
+                if crate::verify::verify_sampled(&a, &b, &c, 8, 3).is_err() {
+                    continue;
+                }
+                let gflops = flops / seconds / 1e9;
+                if best.is_none_or(|(_, fastest)| gflops > fastest) {
+                    best = Some((sizes, gflops));
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, verify};
+
+    #[test]
+    fn test_blis_matches_reference() {
+        // tiny blocks so that every loop runs several times and ends on a partial block
+        let sizes = BlockSizes {
+            mc: 7,
+            kc: 5,
+            nc: 9,
+        };
+        for backend in Backend::available() {
+            for &(m, k, n) in &[(1, 1, 1), (3, 2, 5), (13, 11, 17), (40, 33, 29)] {
+                let a = random_matrix(m, k, 1);
+                let b = random_matrix(k, n, 2);
+                let mut c = DenseMatrix::new(m, n);
+                gemm(backend, sizes, a.as_view(), b.as_view(), c.as_view_mut());
+                if let Err(mismatch) = verify(&a, &b, &c) {
+                    panic!("{:?} on {}x{}x{}: {}", backend, m, k, n, mismatch);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_blis_on_strided_views() {
+        let backend = Backend::detect();
+        // Aᵀ from a column-major matrix and a sub-block of B
+        let a_t = random_matrix(30, 20, 3).to_layout(utils::Layout::ColMajor);
+        let b_big = random_matrix(40, 50, 4);
+        let (a, b) = (a_t.as_view().t(), b_big.view(5..35, 3..28));
+
+        let mut c = DenseMatrix::new(20, 25);
+        gemm(backend, BlockSizes::default(), a, b, c.as_view_mut());
+        let (a, b) = (
+            a.to_matrix(utils::Layout::RowMajor),
+            b.to_matrix(utils::Layout::RowMajor),
+        );
+        assert!(verify(&a, &b, &c).is_ok());
+    }
+
+    #[test]
+    fn test_block_sizes_round_trip() {
+        let path = std::env::temp_dir().join(format!("blis_{}.conf", std::process::id()));
+        let sizes = BlockSizes {
+            mc: 48,
+            kc: 128,
+            nc: 1024,
+        };
+        sizes.save(&path, Backend::Scalar).unwrap();
+        assert_eq!(BlockSizes::load(&path, Backend::Scalar), Some(sizes));
+        assert_eq!(BlockSizes::load(&path, Backend::Avx2Fma), None);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(BlockSizes::load(&path, Backend::Scalar), None);
+    }
+
+    #[test]
+    fn test_autotune_returns_a_candidate() {
+        let backend = Backend::detect();
+        let (sizes, gflops) = autotune(backend, 48, &[8, 16], &[16], &[24, 48]).unwrap();
+        assert!([8, 16].contains(&sizes.mc) && [24, 48].contains(&sizes.nc));
+        assert!(gflops > 0.0);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::blis::{gemm_scaled, BlockSizes};
+use crate::kernels::Backend;
+use rayon::prelude::*;
+use std::fmt;
+use std::num::{Saturating, Wrapping};
+use std::ops::{Add, Mul};
+use utils::{DenseMatrix, MatrixView, MatrixViewMut};
+
+// The BLAS xGEMM interface,
+//
+//   C = alpha·op(A)·op(B) + beta·C,    op(X) = X or Xᵀ
+//
+// on matrices of any layout and strides. Transposes are views, nothing is copied. f64 runs
+// on the packed kernels of the detected backend; f32 and integers on a parallel row loop
+// the compiler vectorizes. Integers come wrapped in `Wrapping` or `Saturating`, which
+// decides what happens on overflow; with `Saturating` the sum over k is taken in order, so
+// the point where it clamps is well defined.
+
+/// Whether an operand is used as stored or transposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transpose {
+    No,
+    Yes,
+}
+
+impl Transpose {
+    /// op(x), as a view.
+    pub fn apply<'a, T: Copy>(self, x: MatrixView<'a, T>) -> MatrixView<'a, T> {
+        match self {
+            Transpose::No => x,
+            Transpose::Yes => x.t(),
+        }
+    }
+}
+
+/// Shapes that don't make a product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GemmError {
+    /// op(A) is `a` and op(B) is `b`, and the columns of one aren't the rows of the other.
+    InnerDimensions {
+        a: (usize, usize),
+        b: (usize, usize),
+    },
+    /// C is `found` but op(A)·op(B) is `expected`.
+    OutputShape {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl fmt::Display for GemmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GemmError::InnerDimensions { a, b } => {
+                write!(f, "op(A) is {}x{} but op(B) is {}x{}", a.0, a.1, b.0, b.1)
+            }
+            GemmError::OutputShape { expected, found } => write!(
+                f,
+                "C is {}x{} but op(A)·op(B) is {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GemmError {}
+
+/// Types `gemm` works on.
+pub trait Element:
+    Copy + Default + PartialEq + Send + Sync + Add<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// C += alpha·A·B with the fastest kernel for the type.
+    fn multiply_add(
+        alpha: Self,
+        a: MatrixView<'_, Self>,
+        b: MatrixView<'_, Self>,
+        c: MatrixViewMut<'_, Self>,
+    ) {
+        rows_parallel(alpha, a, b, c)
+    }
+}
+
+impl Element for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn multiply_add(
+        alpha: Self,
+        a: MatrixView<'_, Self>,
+        b: MatrixView<'_, Self>,
+        c: MatrixViewMut<'_, Self>,
+    ) {
+        let backend = Backend::detect();
+        gemm_scaled(backend, BlockSizes::tuned(backend), alpha, a, b, c)
+    }
+}
+
+impl Element for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+}
+
+macro_rules! integer_elements {
+    ($($int:ty),*) => {
+        $(
+            impl Element for Wrapping<$int> {
+                const ZERO: Self = Wrapping(0);
+                const ONE: Self = Wrapping(1);
+            }
+
+            impl Element for Saturating<$int> {
+                const ZERO: Self = Saturating(0);
+                const ONE: Self = Saturating(1);
+            }
+        )*
+    };
+}
+
+integer_elements!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+/// C = alpha·op(A)·op(B) + beta·C on views of any strides. C is not read when beta is
+/// zero, so it may hold NaN.
+pub fn gemm_view<T: Element>(
+    transa: Transpose,
+    transb: Transpose,
+    alpha: T,
+    a: MatrixView<'_, T>,
+    b: MatrixView<'_, T>,
+    beta: T,
+    mut c: MatrixViewMut<'_, T>,
+) -> Result<(), GemmError> {
+    let (a, b) = (transa.apply(a), transb.apply(b));
+    if a.cols() != b.rows() {
+        return Err(GemmError::InnerDimensions {
+            a: (a.rows(), a.cols()),
+            b: (b.rows(), b.cols()),
+        });
+    }
+    if (c.rows(), c.cols()) != (a.rows(), b.cols()) {
+        return Err(GemmError::OutputShape {
+            expected: (a.rows(), b.cols()),
+            found: (c.rows(), c.cols()),
+        });
+    }
+
+    if beta == T::ZERO {
+        c.fill(T::ZERO);
+    } else if beta != T::ONE {
+        for i in 0..c.rows() {
+            c.row_mut(i).for_each(|c_ij| *c_ij = beta * *c_ij);
+        }
+    }
+    if alpha != T::ZERO && a.cols() > 0 {
+        T::multiply_add(alpha, a, b, c);
+    }
+    Ok(())
+}
+
+/// `gemm_view` on dense matrices.
+pub fn gemm<T: Element>(
+    transa: Transpose,
+    transb: Transpose,
+    alpha: T,
+    a: &DenseMatrix<T>,
+    b: &DenseMatrix<T>,
+    beta: T,
+    c: &mut DenseMatrix<T>,
+) -> Result<(), GemmError> {
+    gemm_view(
+        transa,
+        transb,
+        alpha,
+        a.as_view(),
+        b.as_view(),
+        beta,
+        c.as_view_mut(),
+    )
+}
+
+/// Rows of C per parallel task of the generic kernel.
+const ROWS_PER_TASK: usize = 16;
+
+// C += alpha·A·B in ikj order, blocks of rows of C in parallel. B is copied to row-major
+// first if its rows aren't contiguous, so that the inner loop is over slices.
+fn rows_parallel<T: Element>(
+    alpha: T,
+    a: MatrixView<'_, T>,
+    b: MatrixView<'_, T>,
+    c: MatrixViewMut<'_, T>,
+) {
+    let b_rows;
+    let b = if b.col_stride() == 1 || b.cols() <= 1 {
+        b
+    } else {
+        b_rows = b.to_matrix(utils::Layout::RowMajor);
+        b_rows.as_view()
+    };
+
+    let mut blocks = Vec::with_capacity(c.rows().div_ceil(ROWS_PER_TASK));
+    let mut rest = c;
+    while rest.rows() > ROWS_PER_TASK {
+        let (block, tail) = rest.split_at_row(ROWS_PER_TASK);
+        blocks.push(block);
+        rest = tail;
+    }
+    blocks.push(rest);
+
+    blocks
+        .into_par_iter()
+        .enumerate()
+        .for_each(|(block, mut c_block)| {
+            let unit = c_block.col_stride() == 1 || c_block.cols() <= 1;
+            for di in 0..c_block.rows() {
+                let i = block * ROWS_PER_TASK + di;
+                for (k, &a_ik) in a.row(i).enumerate() {
+                    let (a_ik, b_row) = (alpha * a_ik, b.row_slice(k));
+                    if unit {
+                        for (c_ij, &b_kj) in c_block.row_slice_mut(di).iter_mut().zip(b_row) {
+                            *c_ij = *c_ij + a_ik * b_kj;
+                        }
+                    } else {
+                        for (c_ij, &b_kj) in c_block.row_mut(di).zip(b_row) {
+                            *c_ij = *c_ij + a_ik * b_kj;
+                        }
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, reference_gemm, verify};
+    use utils::Layout;
+
+    fn op(x: &DenseMatrix<f64>, trans: Transpose) -> DenseMatrix<f64> {
+        match trans {
+            Transpose::No => x.clone(),
+            Transpose::Yes => x.as_view().t().to_matrix(Layout::RowMajor),
+        }
+    }
+
+    #[test]
+    fn test_f64_transposes_alpha_beta() {
+        let (m, k, n) = (23, 17, 29);
+        for transa in [Transpose::No, Transpose::Yes] {
+            for transb in [Transpose::No, Transpose::Yes] {
+                // stored so that op() gives m x k and k x n, in the other layout for variety
+                let a = match transa {
+                    Transpose::No => random_matrix(m, k, 1),
+                    Transpose::Yes => random_matrix(k, m, 1).to_layout(Layout::ColMajor),
+                };
+                let b = match transb {
+                    Transpose::No => random_matrix(k, n, 2).to_layout(Layout::ColMajor),
+                    Transpose::Yes => random_matrix(n, k, 2),
+                };
+                let c0 = random_matrix(m, n, 3);
+                let mut c = c0.clone();
+                gemm(transa, transb, 0.5, &a, &b, -2.0, &mut c).unwrap();
+
+                // alpha = 0.5 and beta = -2 are exact, so undo them and compare the product
+                let (a, b) = (op(&a, transa), op(&b, transb));
+                let product =
+                    DenseMatrix::from_fn(m, n, |i, j| 2.0 * (c[(i, j)] + 2.0 * c0[(i, j)]));
+                if let Err(mismatch) = verify(&a, &b, &product) {
+                    panic!("{:?} {:?}: {}", transa, transb, mismatch);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_beta_zero_ignores_nan() {
+        let a = random_matrix(5, 4, 1);
+        let b = random_matrix(4, 6, 2);
+        let mut c = DenseMatrix::from_fn(5, 6, |_, _| f64::NAN);
+        gemm(Transpose::No, Transpose::No, 1.0, &a, &b, 0.0, &mut c).unwrap();
+        assert!(verify(&a, &b, &c).is_ok());
+    }
+
+    #[test]
+    fn test_f32() {
+        let a64 = random_matrix(40, 33, 1);
+        let b64 = random_matrix(33, 20, 2);
+        let to_f32 = |x: &DenseMatrix<f64>| {
+            DenseMatrix::from_fn(x.rows(), x.cols(), |i, j| x[(i, j)] as f32)
+        };
+        let mut c = DenseMatrix::new(40, 20);
+        gemm(
+            Transpose::No,
+            Transpose::No,
+            1.0f32,
+            &to_f32(&a64),
+            &to_f32(&b64),
+            0.0,
+            &mut c,
+        )
+        .unwrap();
+        let expected = reference_gemm(&a64, &b64);
+        for i in 0..40 {
+            for j in 0..20 {
+                assert!((c[(i, j)] as f64 - expected[(i, j)]).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_integers_wrap_or_saturate() {
+        let a = DenseMatrix::from_fn(3, 4, |i, j| (i * 4 + j) as i32 * 1_000_000);
+        let b = DenseMatrix::from_fn(4, 2, |i, j| (i + j) as i32 * 1_000);
+        let naive = |i: usize, j: usize| {
+            (0..4).fold(0i64, |sum, k| sum + a[(i, k)] as i64 * b[(k, j)] as i64)
+        };
+
+        let wrap = |x: &DenseMatrix<i32>| {
+            DenseMatrix::from_fn(x.rows(), x.cols(), |i, j| Wrapping(x[(i, j)]))
+        };
+        let mut c = DenseMatrix::new(3, 2);
+        gemm(
+            Transpose::No,
+            Transpose::No,
+            Wrapping(1),
+            &wrap(&a),
+            &wrap(&b),
+            Wrapping(0),
+            &mut c,
+        )
+        .unwrap();
+        for i in 0..3 {
+            for j in 0..2 {
+                assert_eq!(c[(i, j)].0, naive(i, j) as i32);
+            }
+        }
+
+        let saturate = |x: &DenseMatrix<i32>| {
+            DenseMatrix::from_fn(x.rows(), x.cols(), |i, j| Saturating(x[(i, j)]))
+        };
+        let mut c = DenseMatrix::new(3, 2);
+        gemm(
+            Transpose::No,
+            Transpose::No,
+            Saturating(1),
+            &saturate(&a),
+            &saturate(&b),
+            Saturating(0),
+            &mut c,
+        )
+        .unwrap();
+        for i in 0..3 {
+            for j in 0..2 {
+                // all terms are non-negative, so clamping once clamps the whole sum
+                assert_eq!(c[(i, j)].0 as i64, naive(i, j).min(i32::MAX as i64));
+            }
+        }
+        assert_eq!(c[(2, 1)].0, i32::MAX);
+    }
+
+    #[test]
+    fn test_dimension_errors() {
+        let a = random_matrix(3, 4, 1);
+        let b = random_matrix(5, 6, 2);
+        let mut c = DenseMatrix::new(3, 6);
+        assert_eq!(
+            gemm(Transpose::No, Transpose::No, 1.0, &a, &b, 0.0, &mut c),
+            Err(GemmError::InnerDimensions {
+                a: (3, 4),
+                b: (5, 6)
+            })
+        );
+        let b = random_matrix(6, 4, 2);
+        assert_eq!(
+            gemm(Transpose::No, Transpose::Yes, 1.0, &a, &b, 0.0, &mut c),
+            Ok(())
+        );
+        let mut c = DenseMatrix::new(6, 3);
+        assert_eq!(
+            gemm(Transpose::No, Transpose::Yes, 1.0, &a, &b, 0.0, &mut c),
+            Err(GemmError::OutputShape {
+                expected: (3, 6),
+                found: (6, 3),
+            })
+        );
+    }
+}
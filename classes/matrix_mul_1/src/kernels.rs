@@ -0,0 +1,283 @@
+use rayon::prelude::*;
+use utils::{DenseMatrix, Layout};
+
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+mod vector;
+
+#[cfg(target_arch = "x86_64")]
+pub use avx2::matrix_multiply_avx2;
+pub use vector::matrix_multiply_vector;
+
+// Matrix multiply kernels: a portable scalar one, a vector one written once with
+// `utils::simd`, and an AVX2/FMA one with intrinsics.
+//
+// `utils::simd` picks its instructions when the crate is compiled, so the vector kernel
+// only uses what the build targets. That is all of the host with the workspace's
+// `-C target-cpu=native`, but a binary built for another target CPU and copied around
+// would never reach AVX2. The AVX2 kernel is compiled for AVX2 and FMA under
+// `#[target_feature]` whatever the target, and only called once the running CPU has been
+// checked. NEON is part of every aarch64 CPU, so the vector kernel needs no such check
+// there.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Scalar,
+    /// The vector kernel on NEON, part of every aarch64 CPU.
+    Neon,
+    /// The vector kernel on AVX2 with fused multiply-add, Haswell and later.
+    Avx2Fma,
+}
+
+impl Backend {
+    /// Fastest backend the running CPU supports.
+    pub fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            return Backend::Avx2Fma;
+        }
+        if cfg!(target_arch = "aarch64") {
+            Backend::Neon
+        } else {
+            Backend::Scalar
+        }
+    }
+
+    /// Every backend the running CPU supports, slowest first.
+    pub fn available() -> Vec<Self> {
+        let mut backends = vec![Backend::Scalar];
+        if Backend::detect() != Backend::Scalar {
+            backends.push(Backend::detect());
+        }
+        backends
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Scalar => "scalar",
+            Backend::Neon => "neon",
+            Backend::Avx2Fma => "avx2-fma",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Backend::Scalar, Backend::Neon, Backend::Avx2Fma]
+            .into_iter()
+            .find(|backend| backend.name() == name)
+    }
+
+    /// Register-tile kernel for `blis::gemm`. Panics if the CPU doesn't support the backend.
+    pub fn micro_kernel(self) -> MicroKernel {
+        assert_supported(self);
+        match self {
+            Backend::Scalar => MicroKernel {
+                mr: SCALAR_MR,
+                nr: SCALAR_NR,
+                run: micro_kernel_scalar,
+            },
+            Backend::Neon => MicroKernel {
+                mr: vector::MR,
+                nr: vector::NR,
+                run: vector::micro_kernel_vector,
+            },
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2Fma => MicroKernel {
+                mr: avx2::MR,
+                nr: avx2::NR,
+                run: avx2::micro_kernel_avx2,
+            },
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::Avx2Fma => unreachable!("detect() never picks AVX2 off x86_64"),
+        }
+    }
+}
+
+/// Computes an MR x NR block of C entirely in registers.
+#[derive(Clone, Copy)]
+pub struct MicroKernel {
+    pub mr: usize,
+    pub nr: usize,
+    run: unsafe fn(usize, &[f64], &[f64], &mut [f64]),
+}
+
+impl MicroKernel {
+    /// tile = A_panel · B_panel, where the A panel holds `kc` columns of `mr` values, the
+    /// B panel `kc` rows of `nr` values, and the tile is mr x nr row-major.
+    #[inline(always)]
+    pub fn run(&self, kc: usize, a: &[f64], b: &[f64], tile: &mut [f64]) {
+        assert!(a.len() >= kc * self.mr && b.len() >= kc * self.nr);
+        assert!(tile.len() >= self.mr * self.nr);
+        // Safety: only Backend::micro_kernel builds these, after checking the CPU
+        unsafe { (self.run)(kc, a, b, tile) }
+    }
+}
+
+const SCALAR_MR: usize = 4;
+const SCALAR_NR: usize = 4;
+
+fn micro_kernel_scalar(kc: usize, a: &[f64], b: &[f64], tile: &mut [f64]) {
+    let mut c = [[0.0; SCALAR_NR]; SCALAR_MR];
+    let panels = a.chunks_exact(SCALAR_MR).zip(b.chunks_exact(SCALAR_NR));
+    for (a, b) in panels.take(kc) {
+        for (row, &a_val) in c.iter_mut().zip(a) {
+            for (c_val, &b_val) in row.iter_mut().zip(b) {
+                *c_val += a_val * b_val;
+            }
+        }
+    }
+    for (out, row) in tile.chunks_exact_mut(SCALAR_NR).zip(&c) {
+        out.copy_from_slice(row);
+    }
+}
+
+fn assert_supported(backend: Backend) {
+    assert!(
+        backend == Backend::Scalar || backend == Backend::detect(),
+        "{:?} is not supported on this CPU",
+        backend
+    );
+}
+
+/// C += A·B with the fastest available backend.
+pub fn matrix_multiply(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    matrix_multiply_with(Backend::detect(), a, b, c)
+}
+
+/// C += A·B for row-major A (m x k), B (k x n) and C (m x n).
+///
+/// Panics if the backend isn't supported by this CPU or a matrix is column-major.
+pub fn matrix_multiply_with(
+    backend: Backend,
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    assert_supported(backend);
+    match backend {
+        Backend::Scalar => matrix_multiply_scalar(a, b, c),
+        Backend::Neon => matrix_multiply_vector(a, b, c),
+        #[cfg(target_arch = "x86_64")]
+        // Safety: detect() checked for AVX2 and FMA
+        Backend::Avx2Fma => unsafe { matrix_multiply_avx2(a, b, c) },
+        #[cfg(not(target_arch = "x86_64"))]
+        Backend::Avx2Fma => unreachable!("detect() never picks AVX2 off x86_64"),
+    }
+}
+
+/// Portable C += A·B without intrinsics. The compiler still vectorizes it for whatever
+/// CPU the build targets, the host's with the workspace's `-C target-cpu=native`.
+pub fn matrix_multiply_scalar(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    if !check_shapes(a, b, c) {
+        return;
+    }
+    let (ld, n) = (c.leading_dimension(), c.cols());
+    c.as_mut_slice()
+        .par_chunks_mut(ld)
+        .enumerate()
+        .for_each(|(i, row)| {
+            let row = &mut row[..n];
+            for (k, &a_ik) in a.row_slice(i).iter().enumerate() {
+                for (c_ij, &b_kj) in row.iter_mut().zip(b.row_slice(k)) {
+                    *c_ij += a_ik * b_kj;
+                }
+            }
+        });
+}
+
+// Panics unless C = A·B fits and all three are row-major, the kernels walk rows as slices.
+// False when C is empty, where there is nothing to do and chunking C by rows would panic.
+pub(crate) fn check_shapes(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &DenseMatrix<f64>,
+) -> bool {
+    assert_eq!(a.cols(), b.rows(), "inner dimensions of A and B differ");
+    assert_eq!(
+        (c.rows(), c.cols()),
+        (a.rows(), b.cols()),
+        "C must be {}x{}",
+        a.rows(),
+        b.cols()
+    );
+    for (name, matrix) in [("A", a), ("B", b), ("C", c)] {
+        assert_eq!(
+            matrix.layout(),
+            Layout::RowMajor,
+            "{} must be row-major",
+            name
+        );
+    }
+    c.rows() > 0 && c.cols() > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, verify};
+
+    #[test]
+    fn test_backends_match_reference() {
+        // odd sizes exercise the tail loops of every vector width
+        let shapes = [
+            (0, 3, 4),
+            (3, 0, 4),
+            (3, 4, 0),
+            (1, 1, 1),
+            (2, 3, 5),
+            (7, 1, 9),
+            (17, 33, 13),
+            (64, 64, 64),
+            (65, 63, 67),
+        ];
+        let check = |name: &str, multiply: &dyn Fn(&_, &_, &mut _)| {
+            for (seed, &(m, k, n)) in shapes.iter().enumerate() {
+                let a = random_matrix(m, k, seed as u64);
+                let b = random_matrix(k, n, seed as u64 + 100);
+                let mut c = DenseMatrix::new(m, n);
+                multiply(&a, &b, &mut c);
+                if let Err(mismatch) = verify(&a, &b, &c) {
+                    panic!("{} on {}x{}x{}: {}", name, m, k, n, mismatch);
+                }
+            }
+        };
+        for backend in Backend::available() {
+            check(backend.name(), &|a, b, c| {
+                matrix_multiply_with(backend, a, b, c)
+            });
+        }
+        // the portable kernel on whatever this build targets, dispatched to or not
+        check("vector", &matrix_multiply_vector);
+    }
+
+    #[test]
+    fn test_accumulates_into_c() {
+        let a = random_matrix(5, 6, 1);
+        let b = random_matrix(6, 7, 2);
+        for backend in Backend::available() {
+            let mut c = DenseMatrix::from_fn(5, 7, |_, _| 1.0);
+            matrix_multiply_with(backend, &a, &b, &mut c);
+            let mut expected = crate::verify::reference_gemm(&a, &b);
+            expected.as_mut_slice().iter_mut().for_each(|x| *x += 1.0);
+            assert!(
+                c.as_slice()
+                    .iter()
+                    .zip(expected.as_slice())
+                    .all(|(x, y)| (x - y).abs() < 1e-12),
+                "{:?}",
+                backend
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "B must be row-major")]
+    fn test_rejects_column_major() {
+        let a = random_matrix(3, 4, 1);
+        let b = random_matrix(4, 5, 2).to_layout(Layout::ColMajor);
+        matrix_multiply_with(Backend::Scalar, &a, &b, &mut DenseMatrix::new(3, 5));
+    }
+}
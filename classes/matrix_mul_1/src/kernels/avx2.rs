@@ -0,0 +1,84 @@
+use rayon::prelude::*;
+use std::arch::x86_64::*;
+use utils::DenseMatrix;
+
+// https://www.intel.com/content/www/us/en/docs/intrinsics-guide/index.html
+//
+// The vector kernel again, with the intrinsics spelled out so that it can be compiled for
+// AVX2 and FMA under `#[target_feature]` whatever the crate's target CPU: row i of C
+// accumulates a[i][k] times row k of B, so both rows stream with unit stride.
+
+/// C += A·B for row-major matrices.
+///
+/// # Safety
+/// The CPU must support AVX2 and FMA, check with `is_x86_feature_detected!`.
+pub unsafe fn matrix_multiply_avx2(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    if !super::check_shapes(a, b, c) {
+        return;
+    }
+    let (ld, n) = (c.leading_dimension(), c.cols());
+    c.as_mut_slice()
+        .par_chunks_mut(ld)
+        .enumerate()
+        .for_each(|(i, row)| unsafe { row_avx2(a.row_slice(i), b, &mut row[..n]) });
+}
+
+// Target features don't carry over into closures, so the per-row work is its own function.
+#[target_feature(enable = "avx2,fma")]
+unsafe fn row_avx2(a_row: &[f64], b: &DenseMatrix<f64>, c_row: &mut [f64]) {
+    let n = c_row.len();
+    for (k, &a_ik) in a_row.iter().enumerate() {
+        let b_row = b.row_slice(k);
+        // Load and broadcast the scalar value from matrix A
+        let a_val = _mm256_set1_pd(a_ik);
+
+        let mut j = 0;
+        while j + 4 <= n {
+            // rows are only 64-byte aligned when the leading dimension allows it
+            let b_vals = _mm256_loadu_pd(b_row.as_ptr().add(j));
+            let c_vals = _mm256_loadu_pd(c_row.as_ptr().add(j));
+            _mm256_storeu_pd(
+                c_row.as_mut_ptr().add(j),
+                _mm256_fmadd_pd(a_val, b_vals, c_vals),
+            );
+            j += 4;
+        }
+
+        // Handle remaining elements
+        while j < n {
+            c_row[j] = a_ik.mul_add(b_row[j], c_row[j]);
+            j += 1;
+        }
+    }
+}
+
+/// Rows of the BLIS register tile: 4 x 8 doubles in 8 of the 16 ymm registers, leaving
+/// room for two rows of B and the broadcast of A.
+pub(super) const MR: usize = 4;
+pub(super) const NR: usize = 8;
+
+/// tile = A_panel · B_panel over `kc` packed columns of A (MR each) and rows of B (NR each).
+#[target_feature(enable = "avx2,fma")]
+pub(super) unsafe fn micro_kernel_avx2(kc: usize, a: &[f64], b: &[f64], tile: &mut [f64]) {
+    let mut c = [[_mm256_setzero_pd(); 2]; MR];
+    let (mut a, mut b) = (a.as_ptr(), b.as_ptr());
+    for _ in 0..kc {
+        let b0 = _mm256_loadu_pd(b);
+        let b1 = _mm256_loadu_pd(b.add(4));
+        for row in c.iter_mut() {
+            let a_val = _mm256_broadcast_sd(&*a);
+            row[0] = _mm256_fmadd_pd(a_val, b0, row[0]);
+            row[1] = _mm256_fmadd_pd(a_val, b1, row[1]);
+            a = a.add(1);
+        }
+        b = b.add(NR);
+    }
+    for (i, row) in c.iter().enumerate() {
+        _mm256_storeu_pd(tile.as_mut_ptr().add(i * NR), row[0]);
+        _mm256_storeu_pd(tile.as_mut_ptr().add(i * NR + 4), row[1]);
+    }
+}
@@ -0,0 +1,71 @@
+use rayon::prelude::*;
+use utils::simd::{F64x4, Simd};
+use utils::DenseMatrix;
+
+// One kernel for every vector instruction set, written with `utils::simd`: F64x4 is two q
+// registers with NEON, and one ymm register when the build targets AVX2 and FMA. Row i
+// of C accumulates a[i][k] times row k of B, so both rows stream with unit stride.
+
+const LANES: usize = F64x4::LANES;
+
+/// C += A·B for row-major matrices.
+pub fn matrix_multiply_vector(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    if !super::check_shapes(a, b, c) {
+        return;
+    }
+    let (ld, n) = (c.leading_dimension(), c.cols());
+    c.as_mut_slice()
+        .par_chunks_mut(ld)
+        .enumerate()
+        .for_each(|(i, row)| row_vector(a.row_slice(i), b, &mut row[..n]));
+}
+
+fn row_vector(a_row: &[f64], b: &DenseMatrix<f64>, c_row: &mut [f64]) {
+    let split = c_row.len() - c_row.len() % LANES;
+    for (k, &a_ik) in a_row.iter().enumerate() {
+        let b_row = b.row_slice(k);
+        let a_val = F64x4::splat(a_ik);
+        // rows are only 64-byte aligned when the leading dimension allows it, loads are
+        // unaligned
+        for (c_vals, b_vals) in c_row[..split]
+            .chunks_exact_mut(LANES)
+            .zip(b_row.chunks_exact(LANES))
+        {
+            a_val
+                .mul_add(F64x4::load(b_vals), F64x4::load(c_vals))
+                .store(c_vals);
+        }
+        for (c_ij, &b_kj) in c_row[split..].iter_mut().zip(&b_row[split..]) {
+            *c_ij = a_ik.mul_add(b_kj, *c_ij);
+        }
+    }
+}
+
+// The BLIS tile is only dispatched here on aarch64, x86_64 has its own in `avx2`
+
+/// Rows of the BLIS register tile: 4 x 8 doubles, 8 accumulators, which fills half the
+/// q registers on aarch64.
+pub(super) const MR: usize = 4;
+pub(super) const NR: usize = 2 * LANES;
+
+/// tile = A_panel · B_panel over `kc` packed columns of A (MR each) and rows of B (NR each).
+pub(super) fn micro_kernel_vector(kc: usize, a: &[f64], b: &[f64], tile: &mut [f64]) {
+    let mut c = [[F64x4::splat(0.0); 2]; MR];
+    let panels = a.chunks_exact(MR).zip(b.chunks_exact(NR));
+    for (a, b) in panels.take(kc) {
+        let (b0, b1) = (F64x4::load(&b[..LANES]), F64x4::load(&b[LANES..]));
+        for (row, &a_val) in c.iter_mut().zip(a) {
+            let a_val = F64x4::splat(a_val);
+            row[0] = a_val.mul_add(b0, row[0]);
+            row[1] = a_val.mul_add(b1, row[1]);
+        }
+    }
+    for (out, row) in tile.chunks_exact_mut(NR).zip(&c) {
+        row[0].store(&mut out[..LANES]);
+        row[1].store(&mut out[LANES..]);
+    }
+}
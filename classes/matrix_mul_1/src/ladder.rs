@@ -0,0 +1,343 @@
+use crate::blis::matrix_multiply_blis;
+use crate::kernels::{check_shapes, matrix_multiply_with, Backend};
+use crate::oblivious::matrix_multiply_oblivious;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use utils::DenseMatrix;
+
+// The lecture-1 table of MIT 6.172: one matrix multiply, rewritten step by step from the
+// textbook triple loop to a packed vector micro-kernel. Every rung computes C += A·B for
+// row-major matrices and changes one thing relative to the rung it is compared with.
+
+pub type Multiply = fn(&DenseMatrix<f64>, &DenseMatrix<f64>, &mut DenseMatrix<f64>);
+
+#[derive(Clone, Copy)]
+pub struct Rung {
+    pub name: &'static str,
+    pub multiply: Multiply,
+}
+
+/// Every rung the running CPU supports, naive first.
+pub fn ladder() -> Vec<Rung> {
+    let mut rungs = vec![
+        Rung {
+            name: "ijk",
+            multiply: ijk,
+        },
+        Rung {
+            name: "ikj",
+            multiply: ikj,
+        },
+        Rung {
+            name: "jik",
+            multiply: jik,
+        },
+        Rung {
+            name: "jki",
+            multiply: jki,
+        },
+        Rung {
+            name: "kij",
+            multiply: kij,
+        },
+        Rung {
+            name: "kji",
+            multiply: kji,
+        },
+        Rung {
+            name: "ikj, slices",
+            multiply: ikj_slices,
+        },
+    ];
+    #[cfg(target_arch = "x86_64")]
+    if Backend::detect() == Backend::Avx2Fma {
+        rungs.push(Rung {
+            name: "ikj, slices, avx2+fma",
+            multiply: ikj_slices_avx2,
+        });
+    }
+    rungs.extend([
+        Rung {
+            name: "parallel rows",
+            multiply: parallel_rows,
+        },
+        Rung {
+            name: "parallel tiled",
+            multiply: parallel_tiled,
+        },
+        Rung {
+            name: "parallel divide & conquer",
+            multiply: matrix_multiply_oblivious,
+        },
+        Rung {
+            name: "vector intrinsics",
+            multiply: |a, b, c| matrix_multiply_with(Backend::detect(), a, b, c),
+        },
+        Rung {
+            name: "packed micro-kernel",
+            multiply: |a, b, c| matrix_multiply_blis(Backend::detect(), a, b, c),
+        },
+    ]);
+    rungs
+}
+
+// Six loop orders. Only the innermost index matters much: j walks rows of B and C with
+// unit stride, i walks columns of A and C and k a row of A and a column of B, a cache miss
+// per iteration once a column no longer fits in cache.
+
+pub fn ijk(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for i in 0..a.rows() {
+        for j in 0..b.cols() {
+            for k in 0..a.cols() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+pub fn ikj(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for i in 0..a.rows() {
+        for k in 0..a.cols() {
+            for j in 0..b.cols() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+pub fn jik(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for j in 0..b.cols() {
+        for i in 0..a.rows() {
+            for k in 0..a.cols() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+pub fn jki(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for j in 0..b.cols() {
+        for k in 0..a.cols() {
+            for i in 0..a.rows() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+pub fn kij(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for k in 0..a.cols() {
+        for i in 0..a.rows() {
+            for j in 0..b.cols() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+pub fn kji(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    for k in 0..a.cols() {
+        for j in 0..b.cols() {
+            for i in 0..a.rows() {
+                c[(i, j)] += a[(i, k)] * b[(k, j)];
+            }
+        }
+    }
+}
+
+// The compiler-flag rungs. Zipped row slices show what the optimizer needs: without the
+// bounds checks that keep `ikj` scalar it vectorizes, for whatever instruction set the
+// crate is built for. `ikj_slices_avx2` is the same loop compiled for AVX2 and FMA under
+// `#[target_feature]`, the per-function equivalent of the flag, and only offered once the
+// running CPU has them. With the workspace's `-C target-cpu=native` both already get the
+// host's instructions and tie; drop the flag from .cargo/config.toml to see the baseline
+// SSE2 build fall behind. Build in debug for the -O0 column of the table.
+
+pub fn ikj_slices(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    check_shapes(a, b, c);
+    ikj_slices_loop(a, b, c);
+}
+
+/// `ikj_slices` compiled for AVX2 and FMA. Panics if the CPU doesn't have them.
+#[cfg(target_arch = "x86_64")]
+pub fn ikj_slices_avx2(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    assert_eq!(
+        Backend::detect(),
+        Backend::Avx2Fma,
+        "AVX2 and FMA are not supported on this CPU"
+    );
+    check_shapes(a, b, c);
+    // Safety: checked just above
+    unsafe { ikj_slices_loop_avx2(a, b, c) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn ikj_slices_loop_avx2(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    ikj_slices_loop(a, b, c);
+}
+
+// Inlined into both callers, so each gets its own codegen of the loop
+#[inline(always)]
+fn ikj_slices_loop(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    for i in 0..a.rows() {
+        let c_row = c.row_slice_mut(i);
+        for (k, &a_ik) in a.row_slice(i).iter().enumerate() {
+            for (c_ij, &b_kj) in c_row.iter_mut().zip(b.row_slice(k)) {
+                *c_ij += a_ik * b_kj;
+            }
+        }
+    }
+}
+
+pub fn parallel_rows(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    if !check_shapes(a, b, c) {
+        return;
+    }
+    let (ld, n) = (c.leading_dimension(), c.cols());
+    c.as_mut_slice()
+        .par_chunks_mut(ld)
+        .enumerate()
+        .for_each(|(i, c_row)| {
+            for (k, &a_ik) in a.row_slice(i).iter().enumerate() {
+                for (c_ij, &b_kj) in c_row[..n].iter_mut().zip(b.row_slice(k)) {
+                    *c_ij += a_ik * b_kj;
+                }
+            }
+        });
+}
+
+/// Side of the tiles of `parallel_tiled`, three 64 x 64 doubles tiles fit a 256 KB L2.
+pub const TILE: usize = 64;
+
+pub fn parallel_tiled(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>, c: &mut DenseMatrix<f64>) {
+    if !check_shapes(a, b, c) {
+        return;
+    }
+    let (ld, k, n) = (c.leading_dimension(), a.cols(), c.cols());
+    c.as_mut_slice()
+        .par_chunks_mut(TILE * ld)
+        .enumerate()
+        .for_each(|(block, c_rows)| {
+            let i0 = block * TILE;
+            for k0 in (0..k).step_by(TILE) {
+                for j0 in (0..n).step_by(TILE) {
+                    let j1 = (j0 + TILE).min(n);
+                    for (di, c_row) in c_rows.chunks_mut(ld).enumerate() {
+                        let a_row = &a.row_slice(i0 + di)[k0..(k0 + TILE).min(k)];
+                        for (dk, &a_ik) in a_row.iter().enumerate() {
+                            let b_row = &b.row_slice(k0 + dk)[j0..j1];
+                            for (c_ij, &b_kj) in c_row[j0..j1].iter_mut().zip(b_row) {
+                                *c_ij += a_ik * b_kj;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+}
+
+/// Double-precision peak in GFLOP/s.
+#[derive(Debug, Clone, Copy)]
+pub struct Peak {
+    pub gflops: f64,
+    pub estimated: bool, // Worked out from /proc and /sys rather than given
+}
+
+/// `PEAK_GFLOPS` if set, otherwise an estimate: physical cores x maximum clock x flops
+/// per cycle of the detected backend.
+///
+/// The estimate assumes two FMA pipes and ignores turbo and AVX clock offsets, so it can
+/// be off either way; set `PEAK_GFLOPS` from the datasheet for an exact % of peak.
+pub fn peak_gflops() -> Option<Peak> {
+    if let Some(gflops) = std::env::var("PEAK_GFLOPS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+    {
+        return Some(Peak {
+            gflops,
+            estimated: false,
+        });
+    }
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let field = |line: &str, name: &str| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == name).then(|| value.trim().to_string())
+    };
+
+    // cpuinfo_max_freq is in kHz, the current "cpu MHz" is a fallback that reads low on an
+    // idle core
+    let max_khz = std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
+        .ok()
+        .and_then(|khz| khz.trim().parse::<f64>().ok());
+    let ghz = match max_khz {
+        Some(khz) => khz / 1e6,
+        None => {
+            let mhz: f64 = cpuinfo
+                .lines()
+                .find_map(|line| field(line, "cpu MHz"))?
+                .parse()
+                .ok()?;
+            mhz / 1e3
+        }
+    };
+
+    // SMT siblings share the FMA pipes, so count (package, core) pairs, not logical CPUs
+    let mut cores = HashSet::new();
+    let mut package = None;
+    for line in cpuinfo.lines() {
+        if let Some(id) = field(line, "physical id") {
+            package = Some(id);
+        } else if let Some(id) = field(line, "core id") {
+            cores.insert((package.clone(), id));
+        }
+    }
+    let cores = match cores.len() {
+        0 => std::thread::available_parallelism().ok()?.get(),
+        cores => cores,
+    };
+
+    // two vector FMA pipes on the cores we target, a scalar build still gets SSE2
+    let flops_per_cycle = match Backend::detect() {
+        Backend::Avx2Fma => 2.0 * 4.0 * 2.0,
+        Backend::Neon => 2.0 * 2.0 * 2.0,
+        Backend::Scalar => 2.0 * 2.0,
+    };
+    Some(Peak {
+        gflops: cores as f64 * ghz * flops_per_cycle,
+        estimated: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, verify};
+
+    #[test]
+    fn test_every_rung_matches_reference() {
+        // rectangular, odd and larger than one tile and the recursion base, and empty
+        // products, where chunking C by rows would panic
+        for &(m, k, n) in &[(5, 3, 7), (70, 65, 67), (0, 3, 4), (3, 0, 4), (3, 4, 0)] {
+            let a = random_matrix(m, k, 1);
+            let b = random_matrix(k, n, 2);
+            for rung in ladder() {
+                let mut c = DenseMatrix::new(m, n);
+                (rung.multiply)(&a, &b, &mut c);
+                if let Err(mismatch) = verify(&a, &b, &c) {
+                    panic!("{} on {}x{}x{}: {}", rung.name, m, k, n, mismatch);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod blis;
+pub mod gemm;
+pub mod kernels;
+pub mod ladder;
+pub mod oblivious;
+pub mod strassen;
+pub mod verify;
@@ -1,74 +1,74 @@
-use rayon::prelude::*;
-use rand::random;
+use matrix_mul_1::blis::matrix_multiply_blis;
+use matrix_mul_1::kernels::{matrix_multiply_with, Backend};
+use matrix_mul_1::verify::{random_matrix, verify_sampled};
 use std::time::Instant;
-use std::arch::aarch64::*;
+use utils::aligned::{page_backing, Pages};
+use utils::DenseMatrix;
 
-// https://developer.arm.com/documentation/102159/0400/Overview
-// https://developer.arm.com/architectures/instruction-sets/intrinsics/
-#[inline(always)]
-unsafe fn matrix_multiply_neon(a: &[Vec<f64>], b: &[Vec<f64>], c: &mut [Vec<f64>], n: usize) {
-    c.par_iter_mut().enumerate().for_each(|(k, row)| {
-        // Process 2 elements at a time using NEON
-        for i in 0..n {
-            let mut j = 0;
-            while j + 2 <= n {
-                // Load 2 elements from matrix B
-                let b_ptr = b[k][j..].as_ptr();
-                let b_vals = vld1q_f64(b_ptr);
+// Multiplies two random N x N matrices with every backend this CPU supports. A result is
+// only reported after sampled rows match the reference GEMM.
+//
+//   cargo run --release --bin matrix_mul_1 -- 1024
+//
+// With --huge-pages the matrices live on 2 MB pages, which takes TLB misses out of the
+// column walks. The page size the kernel actually used is printed first.
+//
+//   cargo run --release --bin matrix_mul_1 -- 4096 --huge-pages
 
-                // Load and duplicate the scalar value from matrix A
-                let a_val = vdupq_n_f64(a[i][k]);
+const N: usize = 4096;
+const SAMPLED_ROWS: usize = 8;
 
-                // Load current values from matrix C
-                let c_ptr = &row[j] as *const f64;
-                let c_vals = vld1q_f64(c_ptr);
-
-                let result = vfmaq_f64(c_vals, a_val, b_vals);
-
-                // Store the result back to matrix C
-                let c_ptr_mut = &mut row[j] as *mut f64;
-                vst1q_f64(c_ptr_mut, result);
+fn main() {
+    let (flags, args): (Vec<String>, Vec<String>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let n = args
+        .first()
+        .map(|arg| arg.parse().expect("N must be a number"))
+        .unwrap_or(N);
+    let pages = if flags.iter().any(|flag| flag == "--huge-pages") {
+        Pages::Huge
+    } else {
+        Pages::Base
+    };
 
-                j += 2;
-            }
+    let a = random_matrix(n, n, 1).into_pages(pages);
+    let b = random_matrix(n, n, 2).into_pages(pages);
+    match page_backing(a.as_slice()) {
+        Ok(backing) => println!("{:?} pages requested, got {}", pages, backing),
+        Err(error) => println!(
+            "{:?} pages requested, can't tell what backs them: {}",
+            pages, error
+        ),
+    }
 
-            // Handle remaining elements
-            while j < n {
-                row[j] += a[i][k] * b[k][j];
-                j += 1;
-            }
-        }
-    });
-}
+    type Variant = fn(Backend, &DenseMatrix<f64>, &DenseMatrix<f64>, &mut DenseMatrix<f64>);
+    let variants: [(&str, Variant); 2] = [
+        ("rows", matrix_multiply_with),
+        ("blis", matrix_multiply_blis),
+    ];
 
-#[cfg(target_arch = "aarch64")]
-fn main() {
-    const N: usize = 4096;
+    for backend in Backend::available() {
+        for (name, multiply) in variants {
+            let mut c = DenseMatrix::new(n, n).into_pages(pages);
+            let start = Instant::now();
 
-    let mut a = vec![vec![0.0; N]; N];
-    let mut b = vec![vec![0.0; N]; N];
-    let mut c = vec![vec![0.0; N]; N];
+            multiply(backend, &a, &b, &mut c);
 
-    // Initialize matrices with random values
-    for i in 0..N {
-        for j in 0..N {
-            a[i][j] = random::<f64>();
-            b[i][j] = random::<f64>();
-            c[i][j] = 0.0;
+            let duration = start.elapsed();
+            if let Err(mismatch) = verify_sampled(&a, &b, &c, SAMPLED_ROWS, 3) {
+                panic!(
+                    "{:?} {} computed a wrong product: {}",
+                    backend, name, mismatch
+                );
+            }
+            println!(
+                "{:?} {}: {} seconds, {:.2} GFLOP/s",
+                backend,
+                name,
+                duration.as_secs_f64(),
+                2.0 * (n * n * n) as f64 / duration.as_secs_f64() / 1e9
+            );
         }
     }
-
-    let start = Instant::now();
-
-    unsafe {
-        matrix_multiply_neon(&a, &b, &mut c, N);
-    }
-
-    let duration = start.elapsed();
-    println!("Time taken: {} seconds", duration.as_secs_f64());
 }
-
-#[cfg(not(target_arch = "aarch64"))]
-fn main() {
-    println!("This code requires an ARM64 processor");
-}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use utils::{DenseMatrix, MatrixView, MatrixViewMut};
+
+// Cache-oblivious matrix multiply (Frigo, Leiserson, Prokop and Ramachandran, 1999).
+// Halving the largest of m, n and k keeps every sub-problem close to a cube, so at some
+// depth its three operands fit in L1, at a shallower one in L2 and so on, without the code
+// knowing any cache size. Compare `blis`, which has to be told (or tuned) for each level.
+//
+// Splits of m and n give independent halves of C and run in parallel; the two halves of a
+// split of k add into the same C and run one after the other.
+
+/// Sub-problems with at most this many multiply-adds run as plain loops, deep enough that
+/// the recursion overhead is noise and shallow enough to stay in L1.
+pub const BASE_CASE: usize = 32 * 32 * 32;
+
+/// C += A·B on views of any strides.
+pub fn gemm_oblivious(a: MatrixView<'_, f64>, b: MatrixView<'_, f64>, c: MatrixViewMut<'_, f64>) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(k, b.rows(), "inner dimensions of A and B differ");
+    assert_eq!((c.rows(), c.cols()), (m, n), "C must be {}x{}", m, n);
+    recurse(a, b, c);
+}
+
+pub fn matrix_multiply_oblivious(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    gemm_oblivious(a.as_view(), b.as_view(), c.as_view_mut())
+}
+
+fn recurse(a: MatrixView<'_, f64>, b: MatrixView<'_, f64>, mut c: MatrixViewMut<'_, f64>) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    if m * k * n <= BASE_CASE {
+        return base_case(a, b, c);
+    }
+
+    if m >= n && m >= k {
+        let (a_top, a_bottom) = a.split_at_row(m / 2);
+        let (c_top, c_bottom) = c.split_at_row(m / 2);
+        rayon::join(
+            || recurse(a_top, b, c_top),
+            || recurse(a_bottom, b, c_bottom),
+        );
+    } else if n >= k {
+        let (b_left, b_right) = b.split_at_col(n / 2);
+        let (c_left, c_right) = c.split_at_col(n / 2);
+        rayon::join(
+            || recurse(a, b_left, c_left),
+            || recurse(a, b_right, c_right),
+        );
+    } else {
+        let (a_left, a_right) = a.split_at_col(k / 2);
+        let (b_top, b_bottom) = b.split_at_row(k / 2);
+        recurse(a_left, b_top, c.reborrow());
+        recurse(a_right, b_bottom, c);
+    }
+}
+
+fn base_case(a: MatrixView<'_, f64>, b: MatrixView<'_, f64>, mut c: MatrixViewMut<'_, f64>) {
+    let contiguous_rows = b.col_stride() == 1 && c.col_stride() == 1;
+    for i in 0..a.rows() {
+        for k in 0..a.cols() {
+            let a_ik = a.get(i, k);
+            if contiguous_rows {
+                for (c_ij, &b_kj) in c.row_slice_mut(i).iter_mut().zip(b.row_slice(k)) {
+                    *c_ij += a_ik * b_kj;
+                }
+            } else {
+                for (c_ij, &b_kj) in c.row_mut(i).zip(b.row(k)) {
+                    *c_ij += a_ik * b_kj;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, verify};
+    use utils::Layout;
+
+    #[test]
+    fn test_matches_reference() {
+        for &(m, k, n) in &[(1, 1, 1), (3, 200, 2), (65, 31, 90), (128, 128, 128)] {
+            let a = random_matrix(m, k, 1);
+            let b = random_matrix(k, n, 2);
+            let mut c = DenseMatrix::new(m, n);
+            matrix_multiply_oblivious(&a, &b, &mut c);
+            if let Err(mismatch) = verify(&a, &b, &c) {
+                panic!("{}x{}x{}: {}", m, k, n, mismatch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_strided_views() {
+        // Bᵀ of a row-major matrix has unit row stride, C is a window of a column-major one
+        let a = random_matrix(70, 50, 3);
+        let b_t = random_matrix(60, 50, 4);
+        let mut c_big = DenseMatrix::with_layout(80, 90, Layout::ColMajor);
+        gemm_oblivious(
+            a.as_view(),
+            b_t.as_view().t(),
+            c_big.view_mut(5..75, 10..70),
+        );
+
+        let b = b_t.as_view().t().to_matrix(Layout::RowMajor);
+        let c = c_big.view(5..75, 10..70).to_matrix(Layout::RowMajor);
+        assert!(verify(&a, &b, &c).is_ok());
+        assert_eq!(c_big[(4, 10)], 0.0);
+    }
+}
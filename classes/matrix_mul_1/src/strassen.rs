@@ -0,0 +1,516 @@
+use crate::blis::{self, BlockSizes};
+use crate::kernels::Backend;
+use rayon::prelude::*;
+use utils::{DenseMatrix, MatrixView, MatrixViewMut};
+
+// Fast matrix multiplication: 7 half-size products instead of 8, O(n^2.81) instead of
+// O(n^3). Each level pays Θ(n²) extra additions and its error bound grows by a constant
+// factor (Higham, §23.2.2), so the recursion stops at a crossover and hands the blocks to
+// the classic blocked kernel.
+//
+// Odd dimensions are peeled: the even part recurses and the leftover row, column and
+// rank-1 update of k go to the classic kernel. All functions compute C += A·B.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// 7 products, 18 additions per level (Strassen, 1969).
+    Strassen,
+    /// 7 products, 15 additions per level (Winograd's variant).
+    Winograd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FastConfig {
+    /// Problems whose smallest dimension is at most this use the classic kernel. A 1024
+    /// product runs at close to peak already, so only the levels above it pay off.
+    pub crossover: usize,
+    /// Recursion levels whose 7 products run in parallel. Each parallel level holds all
+    /// 7 products at once, sequential levels reuse one set of buffers for all of them.
+    pub parallel_levels: usize,
+}
+
+impl Default for FastConfig {
+    fn default() -> Self {
+        Self {
+            crossover: 1024,
+            parallel_levels: 1,
+        }
+    }
+}
+
+/// C += A·B by `algorithm`, on views of any strides.
+pub fn gemm_fast(
+    algorithm: Algorithm,
+    config: FastConfig,
+    a: MatrixView<'_, f64>,
+    b: MatrixView<'_, f64>,
+    c: MatrixViewMut<'_, f64>,
+) {
+    let (m, k, n) = (a.rows(), a.cols(), b.cols());
+    assert_eq!(k, b.rows(), "inner dimensions of A and B differ");
+    assert_eq!((c.rows(), c.cols()), (m, n), "C must be {}x{}", m, n);
+
+    let fast = Fast {
+        algorithm,
+        config,
+        backend: Backend::detect(),
+        sizes: BlockSizes::tuned(Backend::detect()),
+    };
+    fast.recurse(a, b, c, 0, &mut []);
+}
+
+pub fn matrix_multiply_fast(
+    algorithm: Algorithm,
+    config: FastConfig,
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &mut DenseMatrix<f64>,
+) {
+    gemm_fast(algorithm, config, a.as_view(), b.as_view(), c.as_view_mut())
+}
+
+// Scratch space of one sequential level: operand sums s (m x k) and t (k x n), a product
+// p (m x n) and, for Winograd, a running sum u (m x n).
+struct Level {
+    s: DenseMatrix<f64>,
+    t: DenseMatrix<f64>,
+    p: DenseMatrix<f64>,
+    u: DenseMatrix<f64>,
+}
+
+struct Fast {
+    algorithm: Algorithm,
+    config: FastConfig,
+    backend: Backend,
+    sizes: BlockSizes,
+}
+
+impl Fast {
+    fn is_base(&self, m: usize, k: usize, n: usize) -> bool {
+        let smallest = m.min(k).min(n);
+        smallest < 2 || smallest <= self.config.crossover
+    }
+
+    // All products of a level have the same shape, so one Level per depth serves a whole
+    // sequential subtree.
+    fn workspace(&self, mut m: usize, mut k: usize, mut n: usize) -> Vec<Level> {
+        let mut levels = Vec::new();
+        while !self.is_base(m, k, n) {
+            (m, k, n) = (m / 2, k / 2, n / 2);
+            let u = match self.algorithm {
+                Algorithm::Strassen => (0, 0),
+                Algorithm::Winograd => (m, n),
+            };
+            levels.push(Level {
+                s: DenseMatrix::new(m, k),
+                t: DenseMatrix::new(k, n),
+                p: DenseMatrix::new(m, n),
+                u: DenseMatrix::new(u.0, u.1),
+            });
+        }
+        levels
+    }
+
+    fn classic(&self, a: MatrixView<'_, f64>, b: MatrixView<'_, f64>, c: MatrixViewMut<'_, f64>) {
+        blis::gemm(self.backend, self.sizes, a, b, c)
+    }
+
+    fn recurse(
+        &self,
+        a: MatrixView<'_, f64>,
+        b: MatrixView<'_, f64>,
+        c: MatrixViewMut<'_, f64>,
+        depth: usize,
+        workspace: &mut [Level],
+    ) {
+        let (m, k, n) = (a.rows(), a.cols(), b.cols());
+        if self.is_base(m, k, n) {
+            return self.classic(a, b, c);
+        }
+
+        // peel the odd row, column and inner index
+        let (me, ke, ne) = (m & !1, k & !1, n & !1);
+        let (c_top, c_last_row) = c.split_at_row(me);
+        let (mut c_even, c_last_col) = c_top.split_at_col(ne);
+        let (a_even, b_even) = (a.view(0..me, 0..ke), b.view(0..ke, 0..ne));
+
+        if depth < self.config.parallel_levels {
+            self.parallel_level(a_even, b_even, c_even.reborrow(), depth);
+        } else if workspace.is_empty() {
+            // first sequential level of this subtree
+            let mut levels = self.workspace(m, k, n);
+            self.sequential_level(a_even, b_even, c_even.reborrow(), depth, &mut levels);
+        } else {
+            self.sequential_level(a_even, b_even, c_even.reborrow(), depth, workspace);
+        }
+
+        if ke < k {
+            self.classic(a.view(0..me, ke..k), b.view(ke..k, 0..ne), c_even);
+        }
+        if ne < n {
+            self.classic(a.view(0..me, 0..k), b.view(0..k, ne..n), c_last_col);
+        }
+        if me < m {
+            self.classic(a.view(me..m, 0..k), b, c_last_row);
+        }
+    }
+
+    // Every product in its own buffers, computed concurrently
+    fn parallel_level(
+        &self,
+        a: MatrixView<'_, f64>,
+        b: MatrixView<'_, f64>,
+        c: MatrixViewMut<'_, f64>,
+        depth: usize,
+    ) {
+        let [a11, a12, a21, a22] = quadrants(a);
+        let [b11, b12, b21, b22] = quadrants(b);
+        let (m, n) = (a11.rows(), b11.cols());
+
+        // (left operand, right operand, sign of the product in C11, C12, C21, C22)
+        let products: Vec<(Operand<'_>, Operand<'_>, [f64; 4])> = match self.algorithm {
+            Algorithm::Strassen => vec![
+                (sum(a11, a22, 1.0), sum(b11, b22, 1.0), [1.0, 0.0, 0.0, 1.0]),
+                (sum(a21, a22, 1.0), b11.into(), [0.0, 0.0, 1.0, -1.0]),
+                (a11.into(), sum(b12, b22, -1.0), [0.0, 1.0, 0.0, 1.0]),
+                (a22.into(), sum(b21, b11, -1.0), [1.0, 0.0, 1.0, 0.0]),
+                (sum(a11, a12, 1.0), b22.into(), [-1.0, 1.0, 0.0, 0.0]),
+                (
+                    sum(a21, a11, -1.0),
+                    sum(b11, b12, 1.0),
+                    [0.0, 0.0, 0.0, 1.0],
+                ),
+                (
+                    sum(a12, a22, -1.0),
+                    sum(b21, b22, 1.0),
+                    [1.0, 0.0, 0.0, 0.0],
+                ),
+            ],
+            Algorithm::Winograd => {
+                let s1 = sum(a21, a22, 1.0);
+                let s2 = sum(s1.view(), a11, -1.0);
+                let s3 = sum(a11, a21, -1.0);
+                let s4 = sum(a12, s2.view(), -1.0);
+                let t1 = sum(b12, b11, -1.0);
+                let t2 = sum(b22, t1.view(), -1.0);
+                let t3 = sum(b22, b12, -1.0);
+                let t4 = sum(t2.view(), b21, -1.0);
+                vec![
+                    (a11.into(), b11.into(), [1.0, 1.0, 1.0, 1.0]),
+                    (a12.into(), b21.into(), [1.0, 0.0, 0.0, 0.0]),
+                    (s4, b22.into(), [0.0, 1.0, 0.0, 0.0]),
+                    (a22.into(), t4, [0.0, 0.0, -1.0, 0.0]),
+                    (s1, t1, [0.0, 1.0, 0.0, 1.0]),
+                    (s2, t2, [0.0, 1.0, 1.0, 1.0]),
+                    (s3, t3, [0.0, 0.0, 1.0, 1.0]),
+                ]
+            }
+        };
+
+        let results: Vec<(DenseMatrix<f64>, [f64; 4])> = products
+            .into_par_iter()
+            .map(|(x, y, signs)| {
+                let mut p = DenseMatrix::new(m, n);
+                self.recurse(x.view(), y.view(), p.as_view_mut(), depth + 1, &mut []);
+                (p, signs)
+            })
+            .collect();
+
+        quadrants_mut(c)
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(q, mut c_q)| {
+                for (p, signs) in &results {
+                    if signs[q] != 0.0 {
+                        update(c_q.reborrow(), 1.0, p.as_view(), signs[q]);
+                    }
+                }
+            });
+    }
+
+    // One product at a time through the buffers of this level. Each product is added
+    // into C as soon as it is known, so s, t and p are reused by all seven.
+    fn sequential_level(
+        &self,
+        a: MatrixView<'_, f64>,
+        b: MatrixView<'_, f64>,
+        c: MatrixViewMut<'_, f64>,
+        depth: usize,
+        workspace: &mut [Level],
+    ) {
+        let (level, deeper) = workspace.split_first_mut().expect("workspace too shallow");
+        let Level { s, t, p, u } = level;
+        let [a11, a12, a21, a22] = quadrants(a);
+        let [b11, b12, b21, b22] = quadrants(b);
+        let mut c = quadrants_mut(c);
+
+        // p = x·y
+        let mut product =
+            |x: MatrixView<'_, f64>, y: MatrixView<'_, f64>, p: &mut DenseMatrix<f64>| {
+                p.fill(0.0);
+                self.recurse(x, y, p.as_view_mut(), depth + 1, deeper);
+            };
+
+        match self.algorithm {
+            Algorithm::Strassen => {
+                combine(s.as_view_mut(), a11, a22, 1.0);
+                combine(t.as_view_mut(), b11, b22, 1.0);
+                product(s.as_view(), t.as_view(), p);
+                distribute(&mut c, p.as_view(), [1.0, 0.0, 0.0, 1.0]);
+
+                combine(s.as_view_mut(), a21, a22, 1.0);
+                product(s.as_view(), b11, p);
+                distribute(&mut c, p.as_view(), [0.0, 0.0, 1.0, -1.0]);
+
+                combine(t.as_view_mut(), b12, b22, -1.0);
+                product(a11, t.as_view(), p);
+                distribute(&mut c, p.as_view(), [0.0, 1.0, 0.0, 1.0]);
+
+                combine(t.as_view_mut(), b21, b11, -1.0);
+                product(a22, t.as_view(), p);
+                distribute(&mut c, p.as_view(), [1.0, 0.0, 1.0, 0.0]);
+
+                combine(s.as_view_mut(), a11, a12, 1.0);
+                product(s.as_view(), b22, p);
+                distribute(&mut c, p.as_view(), [-1.0, 1.0, 0.0, 0.0]);
+
+                combine(s.as_view_mut(), a21, a11, -1.0);
+                combine(t.as_view_mut(), b11, b12, 1.0);
+                product(s.as_view(), t.as_view(), p);
+                distribute(&mut c, p.as_view(), [0.0, 0.0, 0.0, 1.0]);
+
+                combine(s.as_view_mut(), a12, a22, -1.0);
+                combine(t.as_view_mut(), b21, b22, 1.0);
+                product(s.as_view(), t.as_view(), p);
+                distribute(&mut c, p.as_view(), [1.0, 0.0, 0.0, 0.0]);
+            }
+            Algorithm::Winograd => {
+                // S1..S4 and T1..T4 are built in place in s and t, u carries the shared
+                // partial sums U2 = P1 + P6 and U3 = U2 + P7
+                product(a11, b11, p);
+                distribute(&mut c, p.as_view(), [1.0, 0.0, 0.0, 0.0]);
+                u.as_view_mut().copy_from(&p.as_view());
+
+                product(a12, b21, p);
+                distribute(&mut c, p.as_view(), [1.0, 0.0, 0.0, 0.0]);
+
+                combine(s.as_view_mut(), a21, a22, 1.0); // S1
+                combine(t.as_view_mut(), b12, b11, -1.0); // T1
+                product(s.as_view(), t.as_view(), p); // P5
+                distribute(&mut c, p.as_view(), [0.0, 1.0, 0.0, 1.0]);
+
+                update(s.as_view_mut(), 1.0, a11, -1.0); // S2 = S1 - A11
+                update(t.as_view_mut(), -1.0, b22, 1.0); // T2 = B22 - T1
+                product(s.as_view(), t.as_view(), p); // P6
+                update(u.as_view_mut(), 1.0, p.as_view(), 1.0);
+
+                update(s.as_view_mut(), -1.0, a12, 1.0); // S4 = A12 - S2
+                product(s.as_view(), b22, p); // P3
+                distribute(&mut c, p.as_view(), [0.0, 1.0, 0.0, 0.0]);
+                distribute(&mut c, u.as_view(), [0.0, 1.0, 0.0, 0.0]);
+
+                update(t.as_view_mut(), 1.0, b21, -1.0); // T4 = T2 - B21
+                product(a22, t.as_view(), p); // P4
+                distribute(&mut c, p.as_view(), [0.0, 0.0, -1.0, 0.0]);
+
+                combine(s.as_view_mut(), a11, a21, -1.0); // S3
+                combine(t.as_view_mut(), b22, b12, -1.0); // T3
+                product(s.as_view(), t.as_view(), p); // P7
+                update(u.as_view_mut(), 1.0, p.as_view(), 1.0);
+                distribute(&mut c, u.as_view(), [0.0, 0.0, 1.0, 1.0]);
+            }
+        }
+    }
+}
+
+// Either a quadrant of an input or a sum of two quadrants
+enum Operand<'a> {
+    Quadrant(MatrixView<'a, f64>),
+    Sum(DenseMatrix<f64>),
+}
+
+impl Operand<'_> {
+    fn view(&self) -> MatrixView<'_, f64> {
+        match self {
+            Operand::Quadrant(view) => *view,
+            Operand::Sum(matrix) => matrix.as_view(),
+        }
+    }
+}
+
+impl<'a> From<MatrixView<'a, f64>> for Operand<'a> {
+    fn from(view: MatrixView<'a, f64>) -> Self {
+        Operand::Quadrant(view)
+    }
+}
+
+// x + sign·y as a new matrix
+fn sum<'a>(x: MatrixView<'_, f64>, y: MatrixView<'_, f64>, sign: f64) -> Operand<'a> {
+    let mut out = DenseMatrix::new(x.rows(), x.cols());
+    combine(out.as_view_mut(), x, y, sign);
+    Operand::Sum(out)
+}
+
+// out = x + sign·y
+fn combine(
+    mut out: MatrixViewMut<'_, f64>,
+    x: MatrixView<'_, f64>,
+    y: MatrixView<'_, f64>,
+    sign: f64,
+) {
+    let unit = out.col_stride() == 1 && x.col_stride() == 1 && y.col_stride() == 1;
+    for i in 0..out.rows() {
+        if unit {
+            // slices vectorize, the strided iterators don't
+            let out = out.row_slice_mut(i);
+            for (o, (&x, &y)) in out
+                .iter_mut()
+                .zip(x.row_slice(i).iter().zip(y.row_slice(i)))
+            {
+                *o = x + sign * y;
+            }
+        } else {
+            for (o, (&x, &y)) in out.row_mut(i).zip(x.row(i).zip(y.row(i))) {
+                *o = x + sign * y;
+            }
+        }
+    }
+}
+
+// out = scale·out + sign·y
+fn update(mut out: MatrixViewMut<'_, f64>, scale: f64, y: MatrixView<'_, f64>, sign: f64) {
+    let unit = out.col_stride() == 1 && y.col_stride() == 1;
+    for i in 0..out.rows() {
+        if unit {
+            for (o, &y) in out.row_slice_mut(i).iter_mut().zip(y.row_slice(i)) {
+                *o = scale * *o + sign * y;
+            }
+        } else {
+            for (o, &y) in out.row_mut(i).zip(y.row(i)) {
+                *o = scale * *o + sign * y;
+            }
+        }
+    }
+}
+
+// C_q += signs[q]·p for the four quadrants of C
+fn distribute(c: &mut [MatrixViewMut<'_, f64>; 4], p: MatrixView<'_, f64>, signs: [f64; 4]) {
+    for (c_q, sign) in c.iter_mut().zip(signs) {
+        if sign != 0.0 {
+            update(c_q.reborrow(), 1.0, p, sign);
+        }
+    }
+}
+
+// [11, 12, 21, 22] of a matrix with even dimensions
+fn quadrants(x: MatrixView<'_, f64>) -> [MatrixView<'_, f64>; 4] {
+    let (top, bottom) = x.split_at_row(x.rows() / 2);
+    let (x11, x12) = top.split_at_col(x.cols() / 2);
+    let (x21, x22) = bottom.split_at_col(x.cols() / 2);
+    [x11, x12, x21, x22]
+}
+
+fn quadrants_mut(x: MatrixViewMut<'_, f64>) -> [MatrixViewMut<'_, f64>; 4] {
+    let (rows, cols) = (x.rows(), x.cols());
+    let (top, bottom) = x.split_at_row(rows / 2);
+    let (x11, x12) = top.split_at_col(cols / 2);
+    let (x21, x22) = bottom.split_at_col(cols / 2);
+    [x11, x12, x21, x22]
+}
+
+/// max |x - y| / max |y|: the normwise difference of a fast product from the classic one.
+pub fn relative_error(x: &DenseMatrix<f64>, y: &DenseMatrix<f64>) -> f64 {
+    assert_eq!((x.rows(), x.cols()), (y.rows(), y.cols()), "shapes differ");
+    let mut diff: f64 = 0.0;
+    let mut norm: f64 = 0.0;
+    for (x_row, y_row) in x.iter_rows().zip(y.iter_rows()) {
+        for (&x, &y) in x_row.zip(y_row) {
+            diff = diff.max((x - y).abs());
+            norm = norm.max(y.abs());
+        }
+    }
+    if norm == 0.0 {
+        diff
+    } else {
+        diff / norm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify::{random_matrix, reference_gemm};
+
+    // Strassen's error is normwise, not elementwise: bounded by a few levels' worth of
+    // growth times k·u relative to max |C|
+    fn check(algorithm: Algorithm, config: FastConfig, m: usize, k: usize, n: usize) {
+        let a = random_matrix(m, k, 1);
+        let b = random_matrix(k, n, 2);
+        let mut c = DenseMatrix::new(m, n);
+        matrix_multiply_fast(algorithm, config, &a, &b, &mut c);
+        let error = relative_error(&c, &reference_gemm(&a, &b));
+        assert!(
+            error < 1e-12,
+            "{:?} {:?} on {}x{}x{}: relative error {:e}",
+            algorithm,
+            config,
+            m,
+            k,
+            n,
+            error
+        );
+    }
+
+    #[test]
+    fn test_fast_matches_reference() {
+        // tiny crossover so that shapes recurse several levels and peel odd dimensions
+        for algorithm in [Algorithm::Strassen, Algorithm::Winograd] {
+            for parallel_levels in [0, 1, 2] {
+                let config = FastConfig {
+                    crossover: 3,
+                    parallel_levels,
+                };
+                for &(m, k, n) in &[
+                    (1, 1, 1),
+                    (4, 4, 4),
+                    (17, 9, 13),
+                    (64, 64, 64),
+                    (71, 50, 33),
+                ] {
+                    check(algorithm, config, m, k, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fast_accumulates_into_strided_c() {
+        let a = random_matrix(40, 30, 3);
+        let b = random_matrix(30, 20, 4);
+        let mut expected = random_matrix(20, 40, 5);
+        let mut c = expected.to_layout(utils::Layout::ColMajor);
+
+        let config = FastConfig {
+            crossover: 4,
+            parallel_levels: 1,
+        };
+        // Bᵀ·Aᵀ through transposed views, into a column-major C
+        gemm_fast(
+            Algorithm::Winograd,
+            config,
+            b.as_view().t(),
+            a.as_view().t(),
+            c.as_view_mut(),
+        );
+        let product = reference_gemm(
+            &b.as_view().t().to_matrix(utils::Layout::RowMajor),
+            &a.as_view().t().to_matrix(utils::Layout::RowMajor),
+        );
+        for i in 0..20 {
+            for j in 0..40 {
+                expected[(i, j)] += product[(i, j)];
+            }
+        }
+        assert!(relative_error(&c, &expected) < 1e-12);
+    }
+}
@@ -0,0 +1,205 @@
+use rand::prelude::*;
+use std::fmt;
+use utils::DenseMatrix;
+
+// Checking an optimized GEMM against a naive one can't demand equal bits: blocking,
+// vector lanes and FMA all change the order and number of roundings. What every order
+// guarantees is the classical bound (Higham, Accuracy and Stability of Numerical
+// Algorithms, §3.5)
+//
+//   |ĉ_ij - c_ij| <= γ_k · (|A|·|B|)_ij,    γ_k = k·u / (1 - k·u),  u = 2^-53
+//
+// The reference carries the same kind of error, so a result is accepted when it lies
+// within twice the bound of the reference, plus a few ULPs of slack. Scaling by |A|·|B|
+// instead of |C| keeps cancellation (a tiny c_ij from large terms) from failing a correct
+// kernel.
+
+/// ULPs of slack on top of the error bound.
+pub const SLACK_ULPS: u64 = 4;
+
+/// First element of a result outside the tolerance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mismatch {
+    pub row: usize,
+    pub col: usize,
+    pub got: f64,
+    pub expected: f64,
+    pub tolerance: f64,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "C[{}][{}] = {:e}, expected {:e} ± {:e} ({} ulps away)",
+            self.row,
+            self.col,
+            self.got,
+            self.expected,
+            self.tolerance,
+            ulp_distance(self.got, self.expected)
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// C = A·B with the textbook triple loop, summing each dot product in order without FMA.
+pub fn reference_gemm(a: &DenseMatrix<f64>, b: &DenseMatrix<f64>) -> DenseMatrix<f64> {
+    assert_eq!(a.cols(), b.rows(), "inner dimensions of A and B differ");
+    let mut c = DenseMatrix::new(a.rows(), b.cols());
+    for i in 0..a.rows() {
+        for j in 0..b.cols() {
+            let mut sum = 0.0;
+            for k in 0..a.cols() {
+                sum += a[(i, k)] * b[(k, j)];
+            }
+            c[(i, j)] = sum;
+        }
+    }
+    c
+}
+
+/// Seeded matrix with entries uniform in [-1, 1).
+pub fn random_matrix(rows: usize, cols: usize, seed: u64) -> DenseMatrix<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    DenseMatrix::from_fn(rows, cols, |_, _| rng.random_range(-1.0..1.0))
+}
+
+/// Number of representable doubles between x and y, u64::MAX if either is NaN.
+pub fn ulp_distance(x: f64, y: f64) -> u64 {
+    if x.is_nan() || y.is_nan() {
+        return u64::MAX;
+    }
+    // map the bit patterns onto a line where adjacent doubles are adjacent integers
+    let ordered = |v: f64| {
+        let bits = v.to_bits() as i64;
+        if bits < 0 {
+            i64::MIN - bits
+        } else {
+            bits
+        }
+    };
+    ordered(x).abs_diff(ordered(y))
+}
+
+/// Checks C = A·B for every element.
+pub fn verify(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &DenseMatrix<f64>,
+) -> Result<(), Mismatch> {
+    verify_rows(a, b, c, 0..a.rows())
+}
+
+/// Checks `samples` random rows of C = A·B, for results too large to recompute naively.
+/// Whole rows, so a kernel writing the wrong row or dropping a tail is still caught.
+pub fn verify_sampled(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &DenseMatrix<f64>,
+    samples: usize,
+    seed: u64,
+) -> Result<(), Mismatch> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let samples = samples.max(1);
+    let rows: Vec<usize> = if samples >= a.rows() {
+        (0..a.rows()).collect()
+    } else {
+        // always include the last row, where tail handling in M goes wrong
+        (0..samples - 1)
+            .map(|_| rng.random_range(0..a.rows()))
+            .chain([a.rows() - 1])
+            .collect()
+    };
+    verify_rows(a, b, c, rows)
+}
+
+fn verify_rows(
+    a: &DenseMatrix<f64>,
+    b: &DenseMatrix<f64>,
+    c: &DenseMatrix<f64>,
+    rows: impl IntoIterator<Item = usize>,
+) -> Result<(), Mismatch> {
+    assert_eq!(a.cols(), b.rows(), "inner dimensions of A and B differ");
+    assert_eq!(
+        (c.rows(), c.cols()),
+        (a.rows(), b.cols()),
+        "C has the wrong shape"
+    );
+
+    let k = a.cols() as f64;
+    let u = f64::EPSILON / 2.0;
+    let gamma = k * u / (1.0 - k * u);
+
+    for i in rows {
+        for j in 0..b.cols() {
+            let (mut expected, mut magnitude) = (0.0, 0.0);
+            for p in 0..a.cols() {
+                let term = a[(i, p)] * b[(p, j)];
+                expected += term;
+                magnitude += term.abs();
+            }
+
+            let got = c[(i, j)];
+            let tolerance = 2.0 * gamma * magnitude;
+            let within_bound = (got - expected).abs() <= tolerance;
+            if !(within_bound || ulp_distance(got, expected) <= SLACK_ULPS) {
+                return Err(Mismatch {
+                    row: i,
+                    col: j,
+                    got,
+                    expected,
+                    tolerance,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_gemm() {
+        let a = DenseMatrix::from(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        let b = DenseMatrix::from(vec![vec![7.0, 8.0], vec![9.0, 10.0], vec![11.0, 12.0]]);
+        let c = reference_gemm(&a, &b);
+        assert_eq!(c.to_vecs(), vec![vec![58.0, 64.0], vec![139.0, 154.0]]);
+        assert!(verify(&a, &b, &c).is_ok());
+    }
+
+    #[test]
+    fn test_ulp_distance() {
+        assert_eq!(ulp_distance(1.0, 1.0), 0);
+        assert_eq!(ulp_distance(1.0, 1.0 + f64::EPSILON), 1);
+        assert_eq!(ulp_distance(-0.0, 0.0), 0);
+        assert_eq!(ulp_distance(-f64::MIN_POSITIVE, f64::MIN_POSITIVE), 2 << 52);
+        assert_eq!(ulp_distance(f64::NAN, 1.0), u64::MAX);
+    }
+
+    #[test]
+    fn test_accepts_reordered_sums_and_rejects_errors() {
+        let (a, b) = (random_matrix(20, 300, 1), random_matrix(300, 20, 2));
+
+        // backwards with FMA: different roundings, still correct
+        let mut c = DenseMatrix::new(20, 20);
+        for i in 0..20 {
+            for j in 0..20 {
+                c[(i, j)] = (0..300)
+                    .rev()
+                    .fold(0.0, |sum: f64, p| a[(i, p)].mul_add(b[(p, j)], sum));
+            }
+        }
+        assert!(verify(&a, &b, &c).is_ok());
+
+        let mut wrong = c.clone();
+        wrong[(19, 7)] += 1e-6;
+        let mismatch = verify(&a, &b, &wrong).unwrap_err();
+        assert_eq!((mismatch.row, mismatch.col), (19, 7));
+        // the last row is always sampled
+        assert!(verify_sampled(&a, &b, &wrong, 2, 3).is_err());
+    }
+}
@@ -0,0 +1,435 @@
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// Cache line size on x86_64 and most ARM cores.
+pub const CACHE_LINE: usize = 64;
+
+/// Size of a regular page on x86_64 and most ARM Linux kernels.
+pub const BASE_PAGE: usize = 4 << 10;
+
+/// Size of an x86_64 huge page, one page directory entry covering 512 base pages.
+pub const HUGE_PAGE: usize = 2 << 20;
+
+/// Which pages to ask for, and which a buffer got.
+///
+/// A 4096x4096 matrix of f64 spans 32768 base pages, far more than the TLB holds, so
+/// walking it column-wise misses the TLB on nearly every access. On 2 MB pages it needs
+/// 64 entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pages {
+    /// Regular pages, the buffer aligned to a cache line.
+    #[default]
+    Base,
+    /// 2 MB aligned, on huge pages if the kernel hands them out.
+    Huge,
+}
+
+// How the memory was obtained, which decides how it's released and which pages it has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Heap,
+    HugeTlb,   // MAP_HUGETLB, reserved huge pages
+    Advised,   // Mapped and madvise(MADV_HUGEPAGE) accepted
+    Unadvised, // Mapped but the advice was refused, base pages
+}
+
+/// Fixed-size heap buffer whose first element sits on an `align`-byte boundary.
+///
+/// `Vec<T>` only guarantees `align_of::<T>()`, so a `Vec<f64>` may start in the middle of
+/// a cache line: every row then straddles one more line than needed and aligned SIMD
+/// loads are off the table.
+pub struct AlignedVec<T: Copy> {
+    ptr: NonNull<T>,
+    len: usize,
+    layout: Layout,
+    source: Source,
+}
+
+// Same ownership semantics as Vec<T>
+unsafe impl<T: Copy + Send> Send for AlignedVec<T> {}
+unsafe impl<T: Copy + Sync> Sync for AlignedVec<T> {}
+
+impl<T: Copy> AlignedVec<T> {
+    /// `len` copies of `value`, aligned to `align` bytes (a power of two, at least
+    /// `align_of::<T>()` is used either way).
+    pub fn from_elem(value: T, len: usize, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "alignment {} is not a power of two",
+            align
+        );
+        let align = align.max(std::mem::align_of::<T>());
+        let layout = Layout::array::<T>(len)
+            .and_then(|layout| layout.align_to(align))
+            .expect("allocation too large");
+        let ptr = Self::allocate(layout);
+
+        // Safety: fresh allocation of `len` elements
+        unsafe { Self::initialize(ptr, len, value, layout, Source::Heap) }
+    }
+
+    /// `len` copies of `value` on `pages`.
+    pub fn with_pages(value: T, len: usize, pages: Pages) -> Self {
+        match pages {
+            Pages::Base => Self::from_elem(value, len, CACHE_LINE),
+            Pages::Huge => Self::huge(value, len),
+        }
+    }
+
+    /// `len` copies of `value` in whole 2 MB pages.
+    ///
+    /// Explicit huge pages (`MAP_HUGETLB`) are only there if an administrator reserved
+    /// them in `/proc/sys/vm/nr_hugepages`. Otherwise the buffer asks for transparent huge
+    /// pages with `madvise(MADV_HUGEPAGE)`, which the kernel may or may not honour. The
+    /// first write faults the pages in, so that happens here, after the advice.
+    /// [`pages`](Self::pages) tells which of the two worked, [`page_backing`] what the
+    /// kernel did with the advice.
+    pub fn huge(value: T, len: usize) -> Self {
+        let size = Layout::array::<T>(len)
+            .expect("allocation too large")
+            .size()
+            .next_multiple_of(HUGE_PAGE);
+        let align = HUGE_PAGE.max(std::mem::align_of::<T>());
+        let layout = Layout::from_size_align(size, align).expect("allocation too large");
+        if size == 0 {
+            return Self::from_elem(value, len, align);
+        }
+
+        if let Some((ptr, source)) = map_huge(size) {
+            // Safety: fresh mapping of `size` bytes, enough for `len` elements
+            return unsafe { Self::initialize(ptr.cast(), len, value, layout, source) };
+        }
+
+        // Aligned but on base pages
+        let ptr = Self::allocate(layout);
+        // Safety: fresh allocation of `len` elements
+        unsafe { Self::initialize(ptr, len, value, layout, Source::Heap) }
+    }
+
+    pub fn from_slice(values: &[T], align: usize) -> Self
+    where
+        T: Default,
+    {
+        let mut vec = Self::from_elem(T::default(), values.len(), align);
+        vec.copy_from_slice(values);
+        vec
+    }
+
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Pages the buffer got: `Huge` on reserved huge pages or once the kernel accepted the
+    /// advice, `Base` when both failed, None for an empty buffer, which has no pages.
+    pub fn pages(&self) -> Option<Pages> {
+        if self.layout.size() == 0 {
+            return None;
+        }
+        match self.source {
+            Source::HugeTlb | Source::Advised => Some(Pages::Huge),
+            Source::Heap | Source::Unadvised => Some(Pages::Base),
+        }
+    }
+
+    fn allocate(layout: Layout) -> NonNull<T> {
+        if layout.size() == 0 {
+            // still aligned as asked, which NonNull::dangling() only is for align_of::<T>()
+            let aligned = std::ptr::without_provenance_mut(layout.align());
+            return NonNull::new(aligned).expect("alignment is non-zero");
+        }
+        // Safety: the layout has a non-zero size
+        let raw = unsafe { alloc::alloc(layout) } as *mut T;
+        let Some(ptr) = NonNull::new(raw) else {
+            alloc::handle_alloc_error(layout)
+        };
+        ptr
+    }
+
+    // Safety: `ptr` must have room for `len` elements and be released as `source` says
+    unsafe fn initialize(
+        ptr: NonNull<T>,
+        len: usize,
+        value: T,
+        layout: Layout,
+        source: Source,
+    ) -> Self {
+        for i in 0..len {
+            // Safety: in bounds of the allocation, T is Copy so nothing needs dropping
+            unsafe { ptr.as_ptr().add(i).write(value) };
+        }
+        Self {
+            ptr,
+            len,
+            layout,
+            source,
+        }
+    }
+}
+
+impl<T: Copy> Drop for AlignedVec<T> {
+    fn drop(&mut self) {
+        match self.source {
+            Source::Heap if self.layout.size() != 0 => {
+                // Safety: allocated in `allocate` with this exact layout
+                unsafe { alloc::dealloc(self.ptr.as_ptr() as *mut u8, self.layout) };
+            }
+            Source::Heap => {}
+            Source::HugeTlb | Source::Advised | Source::Unadvised => {
+                unmap(self.ptr.cast(), self.layout.size())
+            }
+        }
+    }
+}
+
+impl<T: Copy> Deref for AlignedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // Safety: ptr points to len initialized elements (or is dangling with len 0)
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> DerefMut for AlignedVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy + Default> Clone for AlignedVec<T> {
+    fn clone(&self) -> Self {
+        let mut vec = match self.pages() {
+            Some(Pages::Huge) => Self::huge(T::default(), self.len),
+            Some(Pages::Base) | None => Self::from_elem(T::default(), self.len, self.align()),
+        };
+        vec.copy_from_slice(self);
+        vec
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for AlignedVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Fresh memory straight from the kernel. Recycled allocator memory may already be
+// resident on base pages, and advice doesn't move pages that exist.
+#[cfg(target_os = "linux")]
+fn map_huge(size: usize) -> Option<(NonNull<u8>, Source)> {
+    // Safety: fresh anonymous mappings, no existing memory is touched
+    let map = |size, flags| unsafe {
+        let raw = libc::mmap(
+            std::ptr::null_mut(),
+            size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags,
+            -1,
+            0,
+        );
+        (raw != libc::MAP_FAILED).then_some(raw as usize)
+    };
+
+    // Fails with ENOMEM unless huge pages are reserved
+    if let Some(address) = map(size, libc::MAP_HUGETLB) {
+        return Some((NonNull::new(address as *mut u8)?, Source::HugeTlb));
+    }
+
+    // Over-allocate by a huge page, then cut the ends to a 2 MB boundary
+    let address = map(size + HUGE_PAGE, 0)?;
+    let start = address.next_multiple_of(HUGE_PAGE);
+    let head = start - address;
+    // Safety: both ranges lie in the mapping above and are page-aligned
+    let advised = unsafe {
+        if head > 0 {
+            libc::munmap(address as *mut libc::c_void, head);
+        }
+        libc::munmap((start + size) as *mut libc::c_void, HUGE_PAGE - head);
+        // Fails on kernels built without THP, the buffer then stays on base pages
+        libc::madvise(start as *mut libc::c_void, size, libc::MADV_HUGEPAGE) == 0
+    };
+    let source = if advised {
+        Source::Advised
+    } else {
+        Source::Unadvised
+    };
+    Some((NonNull::new(start as *mut u8)?, source))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn map_huge(_size: usize) -> Option<(NonNull<u8>, Source)> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn unmap(ptr: NonNull<u8>, size: usize) {
+    // Safety: mapped in map_huge with this size
+    unsafe { libc::munmap(ptr.as_ptr() as *mut libc::c_void, size) };
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unmap(_ptr: NonNull<u8>, _size: usize) {
+    unreachable!("only Linux maps huge pages");
+}
+
+/// Pages behind a buffer, as the kernel reports them in `/proc/self/smaps`.
+///
+/// The numbers are for the whole mapping the buffer lives in. That is exactly the buffer
+/// for [`AlignedVec::huge`]; other buffers may share it with neighbouring allocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageBacking {
+    /// Page size of the mapping, larger than 4 KB only for `MAP_HUGETLB`.
+    pub kernel_page_size: usize,
+    /// Bytes of the mapping in memory.
+    pub resident: usize,
+    /// Resident bytes on transparent huge pages.
+    pub transparent_huge: usize,
+}
+
+impl PageBacking {
+    /// Page size the bulk of the buffer got.
+    pub fn page_size(&self) -> usize {
+        if self.kernel_page_size > BASE_PAGE || 2 * self.transparent_huge < self.resident {
+            self.kernel_page_size
+        } else {
+            HUGE_PAGE
+        }
+    }
+}
+
+impl fmt::Display for PageBacking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kernel_page_size > BASE_PAGE {
+            write!(f, "{} KB pages (hugetlb)", self.kernel_page_size >> 10)
+        } else {
+            write!(
+                f,
+                "{} KB pages, {} of {} MB resident on transparent huge pages",
+                self.page_size() >> 10,
+                self.transparent_huge >> 20,
+                self.resident >> 20
+            )
+        }
+    }
+}
+
+/// Looks up the mapping holding `buffer` in `/proc/self/smaps`.
+pub fn page_backing<T>(buffer: &[T]) -> io::Result<PageBacking> {
+    let smaps = std::fs::read_to_string("/proc/self/smaps")?;
+    parse_smaps(&smaps, buffer.as_ptr() as usize).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "no mapping holds the buffer, is it empty?",
+        )
+    })
+}
+
+// Each mapping is a `start-end perms offset dev inode path` line followed by `Key: value kB`
+// lines.
+fn parse_smaps(smaps: &str, address: usize) -> Option<PageBacking> {
+    let mut lines = smaps.lines();
+    lines.find(|line| matches!(mapping_range(line), Some(range) if range.contains(&address)))?;
+
+    let mut backing = PageBacking {
+        kernel_page_size: BASE_PAGE,
+        resident: 0,
+        transparent_huge: 0,
+    };
+    for line in lines.take_while(|line| mapping_range(line).is_none()) {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(kb) = value.trim().strip_suffix(" kB") else {
+            // VmFlags, the last field of the mapping
+            continue;
+        };
+        let Ok(bytes) = kb.trim().parse::<usize>().map(|kb| kb << 10) else {
+            continue;
+        };
+        match key {
+            "KernelPageSize" => backing.kernel_page_size = bytes,
+            "Rss" => backing.resident = bytes,
+            "AnonHugePages" => backing.transparent_huge = bytes,
+            _ => {}
+        }
+    }
+    Some(backing)
+}
+
+fn mapping_range(line: &str) -> Option<std::ops::Range<usize>> {
+    let (start, end) = line.split_whitespace().next()?.split_once('-')?;
+    let start = usize::from_str_radix(start, 16).ok()?;
+    let end = usize::from_str_radix(end, 16).ok()?;
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_huge_buffer_alignment_and_clone() {
+        let buffer = AlignedVec::huge(1.5f64, 3 * HUGE_PAGE / 8 + 5);
+        assert_eq!(buffer.as_ptr() as usize % HUGE_PAGE, 0);
+        // the advice is accepted whenever the kernel has THP at all
+        if std::path::Path::new("/sys/kernel/mm/transparent_hugepage").exists() {
+            assert_eq!(buffer.pages(), Some(Pages::Huge));
+        }
+        assert!(buffer.iter().all(|&x| x == 1.5));
+
+        let copy = buffer.clone();
+        assert_eq!(copy.as_ptr() as usize % HUGE_PAGE, 0);
+        assert_eq!(copy.pages(), buffer.pages());
+        assert_eq!(&copy[..], &buffer[..]);
+
+        let small = AlignedVec::with_pages(7u8, 3, Pages::Base);
+        assert_eq!(small.as_ptr() as usize % CACHE_LINE, 0);
+        assert_eq!(small.pages(), Some(Pages::Base));
+    }
+
+    #[test]
+    fn test_empty_buffers_have_no_pages() {
+        for buffer in [
+            AlignedVec::huge(0u64, 0),
+            AlignedVec::from_elem(0u64, 0, CACHE_LINE),
+        ] {
+            assert_eq!(buffer.pages(), None);
+            assert_eq!(buffer.as_ptr() as usize % buffer.align(), 0);
+            assert_eq!(buffer.clone().pages(), None);
+        }
+        assert_eq!(AlignedVec::huge(0u64, 0).align(), HUGE_PAGE);
+    }
+
+    #[test]
+    #[should_panic(expected = "alignment 48 is not a power of two")]
+    fn test_rejects_non_power_of_two_alignment() {
+        AlignedVec::from_elem(0u8, 16, 48);
+    }
+
+    #[test]
+    fn test_parse_smaps() {
+        let smaps = "\
+7f0000000000-7f0000600000 rw-p 00000000 00:00 0
+Size:               6144 kB
+KernelPageSize:        4 kB
+Rss:                6144 kB
+AnonHugePages:      4096 kB
+VmFlags: rd wr mr mw me ac hg
+7f0000600000-7f0000800000 rw-p 00000000 00:0f 1234   /anon_hugepage (deleted)
+KernelPageSize:     2048 kB
+Rss:                2048 kB
+AnonHugePages:         0 kB
+";
+        let thp = parse_smaps(smaps, 0x7f0000100000).unwrap();
+        assert_eq!(thp.transparent_huge, 4 << 20);
+        assert_eq!(thp.page_size(), HUGE_PAGE);
+
+        let hugetlb = parse_smaps(smaps, 0x7f0000600000).unwrap();
+        assert_eq!(hugetlb.kernel_page_size, HUGE_PAGE);
+        assert_eq!(hugetlb.resident, 2 << 20);
+        assert!(parse_smaps(smaps, 0x7f0000800000).is_none());
+    }
+}
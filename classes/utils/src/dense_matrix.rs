@@ -0,0 +1,903 @@
+use crate::aligned::{AlignedVec, Pages, CACHE_LINE};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut, Range};
+
+/// Which index moves fastest in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    RowMajor, // C, element (r, c) at r * ld + c
+    ColMajor, // Fortran and BLAS, element (r, c) at c * ld + r
+}
+
+/// Dense matrix in one cache-line aligned allocation.
+///
+/// `Vec<Vec<T>>` puts every row in its own allocation: walking down a column is a pointer
+/// chase per element and consecutive rows are nowhere near each other. Here element
+/// (r, c) is an index computation away.
+///
+/// The leading dimension `ld` is the distance between consecutive rows (row-major) or
+/// columns (column-major). It may exceed the logical width, which pads every line, e.g.
+/// to stop power-of-two matrices from mapping a whole column onto one cache set.
+pub struct DenseMatrix<T: Copy> {
+    data: AlignedVec<T>,
+    rows: usize,
+    cols: usize,
+    layout: Layout,
+    leading_dimension: usize,
+}
+
+impl<T: Copy + Default> DenseMatrix<T> {
+    /// rows x cols row-major matrix of `T::default()`.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self::with_layout(rows, cols, Layout::RowMajor)
+    }
+
+    pub fn with_layout(rows: usize, cols: usize, layout: Layout) -> Self {
+        let line = match layout {
+            Layout::RowMajor => cols,
+            Layout::ColMajor => rows,
+        };
+        Self::with_leading_dimension(rows, cols, layout, line)
+    }
+
+    pub fn with_leading_dimension(
+        rows: usize,
+        cols: usize,
+        layout: Layout,
+        leading_dimension: usize,
+    ) -> Self {
+        let (lines, line) = match layout {
+            Layout::RowMajor => (rows, cols),
+            Layout::ColMajor => (cols, rows),
+        };
+        assert!(
+            leading_dimension >= line,
+            "leading dimension {} is shorter than a line of {}",
+            leading_dimension,
+            line
+        );
+
+        Self {
+            data: AlignedVec::from_elem(T::default(), lines * leading_dimension, CACHE_LINE),
+            rows,
+            cols,
+            layout,
+            leading_dimension,
+        }
+    }
+
+    /// Row-major matrix with element (r, c) = f(r, c).
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let mut matrix = Self::new(rows, cols);
+        for r in 0..rows {
+            for (c, value) in matrix.row_slice_mut(r).iter_mut().enumerate() {
+                *value = f(r, c);
+            }
+        }
+        matrix
+    }
+
+    /// Copies into a fresh matrix with the given layout and no padding.
+    pub fn to_layout(&self, layout: Layout) -> Self {
+        self.as_view().to_matrix(layout)
+    }
+
+    /// Moves the buffer onto `pages`, padding and all. A no-op if it's there already.
+    pub fn into_pages(self, pages: Pages) -> Self {
+        if self.data.pages() == Some(pages) {
+            return self;
+        }
+        let mut data = AlignedVec::with_pages(T::default(), self.data.len(), pages);
+        data.copy_from_slice(&self.data);
+        Self { data, ..self }
+    }
+
+    pub fn to_vecs(&self) -> Vec<Vec<T>> {
+        self.iter_rows().map(|row| row.copied().collect()).collect()
+    }
+}
+
+impl<T: Copy> DenseMatrix<T> {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn leading_dimension(&self) -> usize {
+        self.leading_dimension
+    }
+
+    /// See [`AlignedVec::pages`], None for an empty matrix.
+    pub fn pages(&self) -> Option<Pages> {
+        self.data.pages()
+    }
+
+    /// The whole buffer in memory order, padding included.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+
+    // (row stride, column stride)
+    fn strides(&self) -> (usize, usize) {
+        match self.layout {
+            Layout::RowMajor => (self.leading_dimension, 1),
+            Layout::ColMajor => (1, self.leading_dimension),
+        }
+    }
+
+    pub fn as_view(&self) -> MatrixView<'_, T> {
+        let (row_stride, col_stride) = self.strides();
+        MatrixView::from_slice(&self.data, self.rows, self.cols, row_stride, col_stride)
+    }
+
+    pub fn as_view_mut(&mut self) -> MatrixViewMut<'_, T> {
+        let (row_stride, col_stride) = self.strides();
+        let (rows, cols) = (self.rows, self.cols);
+        MatrixViewMut::from_slice(&mut self.data, rows, cols, row_stride, col_stride)
+    }
+
+    /// Sub-matrix of rows `rows` and columns `cols`, sharing the buffer.
+    pub fn view(&self, rows: Range<usize>, cols: Range<usize>) -> MatrixView<'_, T> {
+        self.as_view().view(rows, cols)
+    }
+
+    pub fn view_mut(&mut self, rows: Range<usize>, cols: Range<usize>) -> MatrixViewMut<'_, T> {
+        self.as_view_mut().into_view(rows, cols)
+    }
+
+    pub fn row(&self, r: usize) -> Strided<'_, T> {
+        self.as_view().row(r)
+    }
+
+    pub fn col(&self, c: usize) -> Strided<'_, T> {
+        self.as_view().col(c)
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = Strided<'_, T>> + '_ {
+        (0..self.rows).map(|r| self.row(r))
+    }
+
+    pub fn iter_cols(&self) -> impl Iterator<Item = Strided<'_, T>> + '_ {
+        (0..self.cols).map(|c| self.col(c))
+    }
+
+    /// Row `r` as a slice, row-major only.
+    pub fn row_slice(&self, r: usize) -> &[T] {
+        assert_eq!(self.layout, Layout::RowMajor, "rows are strided");
+        assert!(r < self.rows, "row {} out of bounds", r);
+        let start = r * self.leading_dimension;
+        &self.data[start..start + self.cols]
+    }
+
+    pub fn row_slice_mut(&mut self, r: usize) -> &mut [T] {
+        assert_eq!(self.layout, Layout::RowMajor, "rows are strided");
+        assert!(r < self.rows, "row {} out of bounds", r);
+        let start = r * self.leading_dimension;
+        &mut self.data[start..start + self.cols]
+    }
+
+    /// Column `c` as a slice, column-major only.
+    pub fn col_slice(&self, c: usize) -> &[T] {
+        assert_eq!(self.layout, Layout::ColMajor, "columns are strided");
+        assert!(c < self.cols, "column {} out of bounds", c);
+        let start = c * self.leading_dimension;
+        &self.data[start..start + self.rows]
+    }
+
+    pub fn col_slice_mut(&mut self, c: usize) -> &mut [T] {
+        assert_eq!(self.layout, Layout::ColMajor, "columns are strided");
+        assert!(c < self.cols, "column {} out of bounds", c);
+        let start = c * self.leading_dimension;
+        &mut self.data[start..start + self.rows]
+    }
+
+    #[inline(always)]
+    fn offset(&self, r: usize, c: usize) -> usize {
+        assert!(
+            r < self.rows && c < self.cols,
+            "({}, {}) out of bounds for {}x{}",
+            r,
+            c,
+            self.rows,
+            self.cols
+        );
+        let (row_stride, col_stride) = self.strides();
+        r * row_stride + c * col_stride
+    }
+}
+
+impl<T: Copy> Index<(usize, usize)> for DenseMatrix<T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        &self.data[self.offset(r, c)]
+    }
+}
+
+impl<T: Copy> IndexMut<(usize, usize)> for DenseMatrix<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut T {
+        let offset = self.offset(r, c);
+        &mut self.data[offset]
+    }
+}
+
+impl<T: Copy + Default> Clone for DenseMatrix<T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            rows: self.rows,
+            cols: self.cols,
+            layout: self.layout,
+            leading_dimension: self.leading_dimension,
+        }
+    }
+}
+
+/// Equal when shapes and elements match, whatever the layout or padding.
+impl<T: Copy + PartialEq> PartialEq for DenseMatrix<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+            && self.cols == other.cols
+            && (0..self.rows).all(|r| self.row(r).eq(other.row(r)))
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for DenseMatrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.rows).map(|r| self.row(r).collect::<Vec<_>>()))
+            .finish()
+    }
+}
+
+impl<T: Copy + Default> From<&[Vec<T>]> for DenseMatrix<T> {
+    /// Panics on ragged rows.
+    fn from(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        let mut matrix = Self::new(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            assert_eq!(
+                row.len(),
+                cols,
+                "row {} has {} columns, expected {}",
+                r,
+                row.len(),
+                cols
+            );
+            matrix.row_slice_mut(r).copy_from_slice(row);
+        }
+        matrix
+    }
+}
+
+impl<T: Copy + Default> From<Vec<Vec<T>>> for DenseMatrix<T> {
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        Self::from(&rows[..])
+    }
+}
+
+impl<T: Copy + Default> From<&DenseMatrix<T>> for Vec<Vec<T>> {
+    fn from(matrix: &DenseMatrix<T>) -> Self {
+        matrix.to_vecs()
+    }
+}
+
+impl<T: Copy + Default> From<DenseMatrix<T>> for Vec<Vec<T>> {
+    fn from(matrix: DenseMatrix<T>) -> Self {
+        matrix.to_vecs()
+    }
+}
+
+// Views are a pointer plus independent row and column strides, which covers both layouts,
+// sub-matrices and transposes (swap the strides) with one type. Raw pointers rather than
+// slices because a column split of a row-major matrix interleaves in memory.
+
+// Smallest buffer length holding every element of a rows x cols view.
+fn extent(rows: usize, cols: usize, row_stride: usize, col_stride: usize) -> usize {
+    if rows == 0 || cols == 0 {
+        0
+    } else {
+        (rows - 1) * row_stride + (cols - 1) * col_stride + 1
+    }
+}
+
+/// Borrowed, possibly strided, read-only matrix.
+pub struct MatrixView<'a, T> {
+    ptr: *const T,
+    rows: usize,
+    cols: usize,
+    row_stride: usize,
+    col_stride: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+// Behaves like &'a [T]
+unsafe impl<T: Sync> Send for MatrixView<'_, T> {}
+unsafe impl<T: Sync> Sync for MatrixView<'_, T> {}
+
+impl<T> Clone for MatrixView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MatrixView<'_, T> {}
+
+impl<'a, T: Copy> MatrixView<'a, T> {
+    /// Element (r, c) of the view is `data[r * row_stride + c * col_stride]`.
+    pub fn from_slice(
+        data: &'a [T],
+        rows: usize,
+        cols: usize,
+        row_stride: usize,
+        col_stride: usize,
+    ) -> Self {
+        assert!(
+            extent(rows, cols, row_stride, col_stride) <= data.len(),
+            "{}x{} view does not fit in {} elements",
+            rows,
+            cols,
+            data.len()
+        );
+        Self {
+            ptr: data.as_ptr(),
+            rows,
+            cols,
+            row_stride,
+            col_stride,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    pub fn col_stride(&self) -> usize {
+        self.col_stride
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    #[inline(always)]
+    pub fn get(&self, r: usize, c: usize) -> T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "({}, {}) out of bounds",
+            r,
+            c
+        );
+        unsafe { self.get_unchecked(r, c) }
+    }
+
+    /// # Safety
+    /// `r < rows` and `c < cols`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked(&self, r: usize, c: usize) -> T {
+        *self.ptr.add(r * self.row_stride + c * self.col_stride)
+    }
+
+    pub fn view(&self, rows: Range<usize>, cols: Range<usize>) -> MatrixView<'a, T> {
+        assert!(
+            rows.start <= rows.end && rows.end <= self.rows,
+            "rows {:?} out of bounds",
+            rows
+        );
+        assert!(
+            cols.start <= cols.end && cols.end <= self.cols,
+            "cols {:?} out of bounds",
+            cols
+        );
+        Self {
+            // wrapping: an empty view may start one past the end
+            ptr: self
+                .ptr
+                .wrapping_add(rows.start * self.row_stride + cols.start * self.col_stride),
+            rows: rows.len(),
+            cols: cols.len(),
+            ..*self
+        }
+    }
+
+    /// Aᵀ without copying.
+    pub fn t(&self) -> MatrixView<'a, T> {
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            row_stride: self.col_stride,
+            col_stride: self.row_stride,
+            ..*self
+        }
+    }
+
+    pub fn split_at_row(&self, mid: usize) -> (MatrixView<'a, T>, MatrixView<'a, T>) {
+        (
+            self.view(0..mid, 0..self.cols),
+            self.view(mid..self.rows, 0..self.cols),
+        )
+    }
+
+    pub fn split_at_col(&self, mid: usize) -> (MatrixView<'a, T>, MatrixView<'a, T>) {
+        (
+            self.view(0..self.rows, 0..mid),
+            self.view(0..self.rows, mid..self.cols),
+        )
+    }
+
+    pub fn row(&self, r: usize) -> Strided<'a, T> {
+        assert!(r < self.rows, "row {} out of bounds", r);
+        Strided::new(
+            self.ptr.wrapping_add(r * self.row_stride),
+            self.cols,
+            self.col_stride,
+        )
+    }
+
+    pub fn col(&self, c: usize) -> Strided<'a, T> {
+        assert!(c < self.cols, "column {} out of bounds", c);
+        Strided::new(
+            self.ptr.wrapping_add(c * self.col_stride),
+            self.rows,
+            self.row_stride,
+        )
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = Strided<'a, T>> + '_ {
+        (0..self.rows).map(|r| self.row(r))
+    }
+
+    pub fn iter_cols(&self) -> impl Iterator<Item = Strided<'a, T>> + '_ {
+        (0..self.cols).map(|c| self.col(c))
+    }
+
+    /// Row `r` as a slice, needs unit column stride.
+    pub fn row_slice(&self, r: usize) -> &'a [T] {
+        assert!(self.col_stride == 1 || self.cols <= 1, "rows are strided");
+        assert!(r < self.rows, "row {} out of bounds", r);
+        unsafe { std::slice::from_raw_parts(self.ptr.add(r * self.row_stride), self.cols) }
+    }
+
+    /// Column `c` as a slice, needs unit row stride.
+    pub fn col_slice(&self, c: usize) -> &'a [T] {
+        assert!(
+            self.row_stride == 1 || self.rows <= 1,
+            "columns are strided"
+        );
+        assert!(c < self.cols, "column {} out of bounds", c);
+        unsafe { std::slice::from_raw_parts(self.ptr.add(c * self.col_stride), self.rows) }
+    }
+
+    pub fn to_matrix(&self, layout: Layout) -> DenseMatrix<T>
+    where
+        T: Default,
+    {
+        let mut matrix = DenseMatrix::with_layout(self.rows, self.cols, layout);
+        matrix.as_view_mut().copy_from(self);
+        matrix
+    }
+}
+
+impl<T: Copy> Index<(usize, usize)> for MatrixView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "({}, {}) out of bounds",
+            r,
+            c
+        );
+        unsafe { &*self.ptr.add(r * self.row_stride + c * self.col_stride) }
+    }
+}
+
+/// Borrowed, possibly strided, mutable matrix. Splitting consumes the view and hands out
+/// two disjoint ones, which is what lets recursive kernels write quadrants in parallel.
+pub struct MatrixViewMut<'a, T> {
+    ptr: *mut T,
+    rows: usize,
+    cols: usize,
+    row_stride: usize,
+    col_stride: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+// Behaves like &'a mut [T]
+unsafe impl<T: Send> Send for MatrixViewMut<'_, T> {}
+unsafe impl<T: Sync> Sync for MatrixViewMut<'_, T> {}
+
+impl<'a, T: Copy> MatrixViewMut<'a, T> {
+    /// Like `MatrixView::from_slice`, and the strides must not make two elements overlap.
+    pub fn from_slice(
+        data: &'a mut [T],
+        rows: usize,
+        cols: usize,
+        row_stride: usize,
+        col_stride: usize,
+    ) -> Self {
+        assert!(
+            extent(rows, cols, row_stride, col_stride) <= data.len(),
+            "{}x{} view does not fit in {} elements",
+            rows,
+            cols,
+            data.len()
+        );
+        let disjoint = rows <= 1 && (cols <= 1 || col_stride > 0)
+            || cols <= 1 && row_stride > 0
+            || col_stride > 0 && row_stride >= cols * col_stride
+            || row_stride > 0 && col_stride >= rows * row_stride;
+        assert!(disjoint, "strides make elements overlap");
+
+        Self {
+            ptr: data.as_mut_ptr(),
+            rows,
+            cols,
+            row_stride,
+            col_stride,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row_stride(&self) -> usize {
+        self.row_stride
+    }
+
+    pub fn col_stride(&self) -> usize {
+        self.col_stride
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
+    }
+
+    pub fn as_view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            ptr: self.ptr,
+            rows: self.rows,
+            cols: self.cols,
+            row_stride: self.row_stride,
+            col_stride: self.col_stride,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Shorter-lived copy of this view, for passing it on without giving it up.
+    pub fn reborrow(&mut self) -> MatrixViewMut<'_, T> {
+        MatrixViewMut {
+            _marker: PhantomData,
+            ..*self
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, r: usize, c: usize) -> T {
+        self.as_view().get(r, c)
+    }
+
+    /// # Safety
+    /// `r < rows` and `c < cols`.
+    #[inline(always)]
+    pub unsafe fn get_unchecked_mut(&mut self, r: usize, c: usize) -> &mut T {
+        &mut *self.ptr.add(r * self.row_stride + c * self.col_stride)
+    }
+
+    pub fn into_view(self, rows: Range<usize>, cols: Range<usize>) -> MatrixViewMut<'a, T> {
+        let view = self.as_view().view(rows, cols);
+        MatrixViewMut {
+            ptr: view.ptr as *mut T,
+            rows: view.rows,
+            cols: view.cols,
+            ..self
+        }
+    }
+
+    pub fn view_mut(&mut self, rows: Range<usize>, cols: Range<usize>) -> MatrixViewMut<'_, T> {
+        self.reborrow().into_view(rows, cols)
+    }
+
+    pub fn split_at_row(self, mid: usize) -> (MatrixViewMut<'a, T>, MatrixViewMut<'a, T>) {
+        let (rows, cols) = (self.rows, self.cols);
+        let bottom = MatrixViewMut {
+            _marker: PhantomData,
+            ..self
+        };
+        (
+            self.into_view(0..mid, 0..cols),
+            bottom.into_view(mid..rows, 0..cols),
+        )
+    }
+
+    pub fn split_at_col(self, mid: usize) -> (MatrixViewMut<'a, T>, MatrixViewMut<'a, T>) {
+        let (rows, cols) = (self.rows, self.cols);
+        let right = MatrixViewMut {
+            _marker: PhantomData,
+            ..self
+        };
+        (
+            self.into_view(0..rows, 0..mid),
+            right.into_view(0..rows, mid..cols),
+        )
+    }
+
+    /// Row `r` as a mutable slice, needs unit column stride.
+    pub fn row_slice_mut(&mut self, r: usize) -> &mut [T] {
+        assert!(self.col_stride == 1 || self.cols <= 1, "rows are strided");
+        assert!(r < self.rows, "row {} out of bounds", r);
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.add(r * self.row_stride), self.cols) }
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> StridedMut<'_, T> {
+        assert!(r < self.rows, "row {} out of bounds", r);
+        StridedMut::new(
+            self.ptr.wrapping_add(r * self.row_stride),
+            self.cols,
+            self.col_stride,
+        )
+    }
+
+    pub fn col_mut(&mut self, c: usize) -> StridedMut<'_, T> {
+        assert!(c < self.cols, "column {} out of bounds", c);
+        StridedMut::new(
+            self.ptr.wrapping_add(c * self.col_stride),
+            self.rows,
+            self.row_stride,
+        )
+    }
+
+    pub fn fill(&mut self, value: T) {
+        for r in 0..self.rows {
+            self.row_mut(r).for_each(|x| *x = value);
+        }
+    }
+
+    pub fn copy_from(&mut self, source: &MatrixView<'_, T>) {
+        assert_eq!(
+            (self.rows, self.cols),
+            (source.rows, source.cols),
+            "shapes differ"
+        );
+        for r in 0..self.rows {
+            for (x, y) in self.row_mut(r).zip(source.row(r)) {
+                *x = *y;
+            }
+        }
+    }
+}
+
+impl<T: Copy> Index<(usize, usize)> for MatrixViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "({}, {}) out of bounds",
+            r,
+            c
+        );
+        unsafe { &*self.ptr.add(r * self.row_stride + c * self.col_stride) }
+    }
+}
+
+impl<T: Copy> IndexMut<(usize, usize)> for MatrixViewMut<'_, T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "({}, {}) out of bounds",
+            r,
+            c
+        );
+        unsafe { self.get_unchecked_mut(r, c) }
+    }
+}
+
+/// Iterator over `len` elements `stride` apart.
+pub struct Strided<'a, T> {
+    ptr: *const T,
+    len: usize,
+    stride: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> Strided<'_, T> {
+    fn new(ptr: *const T, len: usize, stride: usize) -> Self {
+        Self {
+            ptr,
+            len,
+            stride,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Strided<'a, T> {
+    type Item = &'a T;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        // the view checked that all len elements are in bounds
+        let item = unsafe { &*self.ptr };
+        self.len -= 1;
+        self.ptr = self.ptr.wrapping_add(self.stride);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Strided<'_, T> {}
+
+pub struct StridedMut<'a, T> {
+    ptr: *mut T,
+    len: usize,
+    stride: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> StridedMut<'_, T> {
+    fn new(ptr: *mut T, len: usize, stride: usize) -> Self {
+        Self {
+            ptr,
+            len,
+            stride,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for StridedMut<'a, T> {
+    type Item = &'a mut T;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        // in bounds, and disjoint strides mean every element is handed out once
+        let item = unsafe { &mut *self.ptr };
+        self.len -= 1;
+        self.ptr = self.ptr.wrapping_add(self.stride);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for StridedMut<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligned::HUGE_PAGE;
+
+    fn sample(layout: Layout, leading_dimension: usize) -> DenseMatrix<i32> {
+        let mut m = DenseMatrix::with_leading_dimension(3, 4, layout, leading_dimension);
+        for r in 0..3 {
+            for c in 0..4 {
+                m[(r, c)] = (10 * r + c) as i32;
+            }
+        }
+        m
+    }
+
+    #[test]
+    fn test_layouts_agree() {
+        let row_major = sample(Layout::RowMajor, 4);
+        let padded = sample(Layout::RowMajor, 7);
+        let col_major = sample(Layout::ColMajor, 5);
+
+        assert_eq!(row_major, padded);
+        assert_eq!(row_major, col_major);
+        assert_eq!(row_major.as_slice().as_ptr() as usize % CACHE_LINE, 0);
+        assert_eq!(col_major.col_slice(2), &[2, 12, 22]);
+        assert_eq!(padded.row_slice(1), &[10, 11, 12, 13]);
+        assert_eq!(
+            col_major.row(2).copied().collect::<Vec<_>>(),
+            vec![20, 21, 22, 23]
+        );
+        assert_eq!(col_major.to_layout(Layout::RowMajor).as_slice().len(), 12);
+
+        let huge = sample(Layout::RowMajor, 7).into_pages(Pages::Huge);
+        assert!(huge.pages().is_some());
+        assert_eq!(huge.as_slice().as_ptr() as usize % HUGE_PAGE, 0);
+        assert_eq!(huge, padded);
+    }
+
+    #[test]
+    fn test_vec_round_trip() {
+        let rows = vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]];
+        let m = DenseMatrix::from(rows.clone());
+        assert_eq!((m.rows(), m.cols()), (3, 2));
+        assert_eq!(m.col(1).copied().collect::<Vec<_>>(), vec![2.0, 4.0, 6.0]);
+        assert_eq!(Vec::<Vec<f64>>::from(&m), rows);
+    }
+
+    #[test]
+    fn test_views() {
+        let m = sample(Layout::ColMajor, 3);
+        let v = m.view(1..3, 1..4);
+        assert_eq!((v.rows(), v.cols()), (2, 3));
+        assert_eq!(v.get(0, 0), 11);
+        assert_eq!(v.view(1..2, 2..3)[(0, 0)], 23);
+        assert_eq!(v.t().get(2, 1), 23);
+        assert_eq!(
+            v.iter_cols().map(|c| c.sum::<i32>()).collect::<Vec<_>>(),
+            vec![32, 34, 36]
+        );
+        assert_eq!(
+            v.to_matrix(Layout::RowMajor).to_vecs(),
+            vec![vec![11, 12, 13], vec![21, 22, 23]]
+        );
+    }
+
+    #[test]
+    fn test_mutable_splits_are_disjoint() {
+        let mut m = DenseMatrix::<i32>::new(4, 6);
+        let (top, bottom) = m.as_view_mut().split_at_row(1);
+        let (mut left, mut right) = bottom.split_at_col(2);
+        let mut top = top;
+        top.fill(1);
+        left.fill(2);
+        right.view_mut(1..3, 0..4).fill(3);
+        right[(0, 3)] = 4;
+
+        assert_eq!(
+            m.to_vecs(),
+            vec![
+                vec![1, 1, 1, 1, 1, 1],
+                vec![2, 2, 0, 0, 0, 4],
+                vec![2, 2, 3, 3, 3, 3],
+                vec![2, 2, 3, 3, 3, 3],
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_padding_is_not_addressable() {
+        let m = sample(Layout::RowMajor, 8);
+        let _ = m[(0, 4)];
+    }
+}
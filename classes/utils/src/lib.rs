@@ -7,4 +7,11 @@ macro_rules! time_it {
         println!("{}: {} seconds", $label, duration.as_secs_f64());
         result
     }};
-}
\ No newline at end of file
+}
+
+pub mod aligned;
+pub mod dense_matrix;
+pub mod perf;
+pub mod simd;
+
+pub use dense_matrix::{DenseMatrix, Layout, MatrixView, MatrixViewMut};
@@ -0,0 +1,374 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+// Fixed-width vectors written once for every instruction set. The implementation is picked
+// when the crate is compiled, from the enabled target features (the workspace builds with
+// `-C target-cpu=native`):
+//
+//   x86_64    SSE2 for the 128-bit types, AVX2 for the 256-bit ones when enabled, FMA
+//             when enabled, SSE4.1 for integer min/max/multiply when enabled
+//   aarch64   NEON for the 128-bit types
+//   other     arrays, left to the autovectorizer
+//
+// A 256-bit type without 256-bit registers is a pair of 128-bit halves. Everything is
+// `#[inline(always)]`, so a kernel written with these types compiles to the same code as
+// one written with the intrinsics directly.
+//
+// Integer lanes wrap on overflow. Floating-point min and max of a NaN lane return either
+// operand, whichever the instruction set does.
+
+#[cfg(target_arch = "aarch64")]
+mod neon;
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod scalar;
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
+#[cfg(target_arch = "aarch64")]
+pub use neon::{F64x2, I32x4};
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub use scalar::{F64x2, F64x4, I32x4, I32x8};
+#[cfg(target_arch = "x86_64")]
+pub use x86::{F64x2, I32x4};
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+pub use x86::{F64x4, I32x8};
+
+/// Instruction set behind the 128-bit and 256-bit types of this build.
+pub const BACKEND: (&str, &str) = if cfg!(target_arch = "aarch64") {
+    ("neon", "neon x 2")
+} else if cfg!(all(target_arch = "x86_64", target_feature = "avx2")) {
+    ("sse2", "avx2")
+} else if cfg!(target_arch = "x86_64") {
+    ("sse2", "sse2 x 2")
+} else {
+    ("scalar", "scalar")
+};
+
+/// Operations every vector type has.
+pub trait Simd:
+    Copy + fmt::Debug + Send + Sync + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    type Elem: Copy;
+    const LANES: usize;
+
+    fn splat(value: Self::Elem) -> Self;
+    /// The first `LANES` elements of `slice`, which needs no particular alignment.
+    /// Panics if it is shorter.
+    fn load(slice: &[Self::Elem]) -> Self;
+    /// Writes the lanes to the first `LANES` elements of `slice`. Panics if it is shorter.
+    fn store(self, slice: &mut [Self::Elem]);
+
+    /// self·b + c, with a single rounding when the target has FMA.
+    fn mul_add(self, b: Self, c: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+
+    fn simd_eq(self, other: Self) -> Mask<Self>;
+    fn simd_lt(self, other: Self) -> Mask<Self>;
+    fn simd_le(self, other: Self) -> Mask<Self>;
+    fn simd_gt(self, other: Self) -> Mask<Self> {
+        other.simd_lt(self)
+    }
+    fn simd_ge(self, other: Self) -> Mask<Self> {
+        other.simd_le(self)
+    }
+    /// Lanes of `if_true` where `mask` is set, of `if_false` elsewhere.
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self;
+    /// Bit i set when lane i of `mask` is.
+    fn bitmask(mask: Mask<Self>) -> u32;
+
+    /// Sum of the lanes, in no particular order.
+    fn reduce_sum(self) -> Self::Elem;
+    fn reduce_min(self) -> Self::Elem;
+    fn reduce_max(self) -> Self::Elem;
+}
+
+/// Result of a lane-wise comparison of two `V`s: every bit of a lane set or clear, the
+/// form `select` consumes directly.
+#[derive(Clone, Copy, Debug)]
+pub struct Mask<V>(V);
+
+impl<V: Simd> Mask<V> {
+    pub fn select(self, if_true: V, if_false: V) -> V {
+        V::select(self, if_true, if_false)
+    }
+
+    pub fn bitmask(self) -> u32 {
+        V::bitmask(self)
+    }
+
+    pub fn any(self) -> bool {
+        self.bitmask() != 0
+    }
+
+    pub fn all(self) -> bool {
+        self.bitmask() == (1 << V::LANES) - 1
+    }
+}
+
+// `$wide` as two `$half`s, low lanes first
+#[allow(unused_macros)]
+macro_rules! pair {
+    ($($wide:ident: $half:ident, $elem:ty);*) => {
+        $(
+            #[derive(Clone, Copy, Debug)]
+            pub struct $wide {
+                lo: $half,
+                hi: $half,
+            }
+
+            impl $wide {
+                const HALF: usize = <$half as Simd>::LANES;
+
+                #[inline(always)]
+                pub fn from_array(values: [$elem; 2 * <$half as Simd>::LANES]) -> Self {
+                    Self::load(&values)
+                }
+
+                #[inline(always)]
+                pub fn to_array(self) -> [$elem; 2 * <$half as Simd>::LANES] {
+                    let mut values = [<$elem>::default(); 2 * <$half as Simd>::LANES];
+                    self.store(&mut values);
+                    values
+                }
+
+                #[inline(always)]
+                fn halves(self, other: Self, f: impl Fn($half, $half) -> $half) -> Self {
+                    Self {
+                        lo: f(self.lo, other.lo),
+                        hi: f(self.hi, other.hi),
+                    }
+                }
+
+                #[inline(always)]
+                fn compare(self, other: Self, f: impl Fn($half, $half) -> Mask<$half>) -> Mask<Self> {
+                    Mask(Self {
+                        lo: f(self.lo, other.lo).0,
+                        hi: f(self.hi, other.hi).0,
+                    })
+                }
+            }
+
+            impl Simd for $wide {
+                type Elem = $elem;
+                const LANES: usize = 2 * Self::HALF;
+
+                #[inline(always)]
+                fn splat(value: $elem) -> Self {
+                    let half = <$half>::splat(value);
+                    Self { lo: half, hi: half }
+                }
+
+                #[inline(always)]
+                fn load(slice: &[$elem]) -> Self {
+                    let slice = &slice[..Self::LANES];
+                    Self {
+                        lo: <$half>::load(&slice[..Self::HALF]),
+                        hi: <$half>::load(&slice[Self::HALF..]),
+                    }
+                }
+
+                #[inline(always)]
+                fn store(self, slice: &mut [$elem]) {
+                    let slice = &mut slice[..Self::LANES];
+                    self.lo.store(&mut slice[..Self::HALF]);
+                    self.hi.store(&mut slice[Self::HALF..]);
+                }
+
+                #[inline(always)]
+                fn mul_add(self, b: Self, c: Self) -> Self {
+                    Self {
+                        lo: self.lo.mul_add(b.lo, c.lo),
+                        hi: self.hi.mul_add(b.hi, c.hi),
+                    }
+                }
+
+                #[inline(always)]
+                fn min(self, other: Self) -> Self {
+                    self.halves(other, <$half>::min)
+                }
+
+                #[inline(always)]
+                fn max(self, other: Self) -> Self {
+                    self.halves(other, <$half>::max)
+                }
+
+                #[inline(always)]
+                fn simd_eq(self, other: Self) -> Mask<Self> {
+                    self.compare(other, <$half>::simd_eq)
+                }
+
+                #[inline(always)]
+                fn simd_lt(self, other: Self) -> Mask<Self> {
+                    self.compare(other, <$half>::simd_lt)
+                }
+
+                #[inline(always)]
+                fn simd_le(self, other: Self) -> Mask<Self> {
+                    self.compare(other, <$half>::simd_le)
+                }
+
+                #[inline(always)]
+                fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+                    Self {
+                        lo: <$half>::select(Mask(mask.0.lo), if_true.lo, if_false.lo),
+                        hi: <$half>::select(Mask(mask.0.hi), if_true.hi, if_false.hi),
+                    }
+                }
+
+                #[inline(always)]
+                fn bitmask(mask: Mask<Self>) -> u32 {
+                    <$half>::bitmask(Mask(mask.0.lo)) | <$half>::bitmask(Mask(mask.0.hi)) << Self::HALF
+                }
+
+                #[inline(always)]
+                fn reduce_sum(self) -> $elem {
+                    (self.lo + self.hi).reduce_sum()
+                }
+
+                #[inline(always)]
+                fn reduce_min(self) -> $elem {
+                    self.lo.min(self.hi).reduce_min()
+                }
+
+                #[inline(always)]
+                fn reduce_max(self) -> $elem {
+                    self.lo.max(self.hi).reduce_max()
+                }
+            }
+
+            impl std::ops::Add for $wide {
+                type Output = Self;
+
+                #[inline(always)]
+                fn add(self, other: Self) -> Self {
+                    self.halves(other, |x, y| x + y)
+                }
+            }
+
+            impl std::ops::Sub for $wide {
+                type Output = Self;
+
+                #[inline(always)]
+                fn sub(self, other: Self) -> Self {
+                    self.halves(other, |x, y| x - y)
+                }
+            }
+
+            impl std::ops::Mul for $wide {
+                type Output = Self;
+
+                #[inline(always)]
+                fn mul(self, other: Self) -> Self {
+                    self.halves(other, |x, y| x * y)
+                }
+            }
+        )*
+    };
+}
+#[cfg(any(
+    target_arch = "aarch64",
+    all(target_arch = "x86_64", not(target_feature = "avx2"))
+))]
+pair!(F64x4: F64x2, f64; I32x8: I32x4, i32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every operation against the same operation on plain arrays
+    fn check<V, const N: usize>(
+        from_array: fn([V::Elem; N]) -> V,
+        to_array: fn(V) -> [V::Elem; N],
+        x: [V::Elem; N],
+        y: [V::Elem; N],
+        z: [V::Elem; N],
+    ) where
+        V: Simd,
+        V::Elem: PartialOrd + fmt::Debug + Add<Output = V::Elem> + Mul<Output = V::Elem>,
+    {
+        assert_eq!(V::LANES, N);
+        let (a, b, c) = (from_array(x), from_array(y), from_array(z));
+        let lanes = |f: &dyn Fn(usize) -> V::Elem| -> [V::Elem; N] { std::array::from_fn(f) };
+        let min = |p: V::Elem, q: V::Elem| if q < p { q } else { p };
+        let max = |p: V::Elem, q: V::Elem| if q > p { q } else { p };
+
+        let mut stored = z;
+        a.store(&mut stored);
+        assert_eq!(stored, x);
+        assert_eq!(to_array(V::load(&y)), y);
+        assert_eq!(to_array(V::splat(x[0])), [x[0]; N]);
+
+        assert_eq!(to_array(a + b), lanes(&|i| x[i] + y[i]));
+        assert_eq!(to_array(a * b), lanes(&|i| x[i] * y[i]));
+        // exact for the small integers and halves used below, fused or not
+        assert_eq!(to_array(a.mul_add(b, c)), lanes(&|i| x[i] * y[i] + z[i]));
+        assert_eq!(to_array(a.min(b)), lanes(&|i| min(x[i], y[i])));
+        assert_eq!(to_array(a.max(b)), lanes(&|i| max(x[i], y[i])));
+
+        let bits = |f: &dyn Fn(usize) -> bool| (0..N).fold(0, |m, i| m | (f(i) as u32) << i);
+        assert_eq!(a.simd_eq(b).bitmask(), bits(&|i| x[i] == y[i]));
+        assert_eq!(a.simd_lt(b).bitmask(), bits(&|i| x[i] < y[i]));
+        assert_eq!(a.simd_le(b).bitmask(), bits(&|i| x[i] <= y[i]));
+        assert_eq!(a.simd_gt(b).bitmask(), bits(&|i| x[i] > y[i]));
+        assert_eq!(a.simd_ge(b).bitmask(), bits(&|i| x[i] >= y[i]));
+        assert!(a.simd_eq(a).all() && !a.simd_lt(a).any());
+        assert_eq!(
+            to_array(a.simd_lt(b).select(a, b)),
+            lanes(&|i| if x[i] < y[i] { x[i] } else { y[i] })
+        );
+
+        assert_eq!(a.reduce_sum(), x.into_iter().reduce(|p, q| p + q).unwrap());
+        assert_eq!(a.reduce_min(), x.into_iter().reduce(min).unwrap());
+        assert_eq!(a.reduce_max(), x.into_iter().reduce(max).unwrap());
+    }
+
+    #[test]
+    fn test_f64() {
+        check(
+            F64x2::from_array,
+            F64x2::to_array,
+            [1.5, -2.0],
+            [1.5, 3.0],
+            [0.25, -8.0],
+        );
+        check(
+            F64x4::from_array,
+            F64x4::to_array,
+            [1.5, -2.0, 7.0, -0.5],
+            [1.5, 3.0, -7.0, 4.0],
+            [0.25, -8.0, 2.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn test_i32() {
+        check(
+            I32x4::from_array,
+            I32x4::to_array,
+            [3, -5, 7, 0],
+            [3, 9, -8, -1],
+            [1, 2, 3, 4],
+        );
+        check(
+            I32x8::from_array,
+            I32x8::to_array,
+            [3, -5, 7, 0, -30_000, 12, -1, 100],
+            [3, 9, -8, -1, 40_000, 12, 1, -100],
+            [1, 2, 3, 4, 5, 6, 7, 8],
+        );
+    }
+
+    #[test]
+    fn test_integer_lanes_wrap() {
+        let max = I32x4::splat(i32::MAX);
+        assert_eq!((max + I32x4::splat(1)).to_array(), [i32::MIN; 4]);
+        assert_eq!((max * I32x4::splat(2)).to_array(), [-2; 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_load_checks_length() {
+        F64x4::load(&[1.0, 2.0, 3.0]);
+    }
+}
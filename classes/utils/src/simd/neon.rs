@@ -0,0 +1,247 @@
+use super::{Mask, Simd};
+use std::arch::aarch64::*;
+use std::ops::{Add, Mul, Sub};
+
+// https://developer.arm.com/architectures/instruction-sets/intrinsics/
+//
+// NEON is part of every aarch64 CPU. Comparisons return unsigned lanes, masks keep them
+// reinterpreted as the vector type so that `Mask<V>` holds a `V` on every backend.
+
+#[derive(Clone, Copy, Debug)]
+pub struct F64x2(float64x2_t);
+
+#[derive(Clone, Copy, Debug)]
+pub struct I32x4(int32x4_t);
+
+// Arithmetic operators from intrinsics, `$op(a, b)`
+macro_rules! binary_ops {
+    ($type:ident { $(impl $trait:ident::$method:ident = $op:ident;)* }) => {
+        $(
+            impl $trait for $type {
+                type Output = Self;
+
+                #[inline(always)]
+                fn $method(self, other: Self) -> Self {
+                    // Safety: NEON is always present on aarch64
+                    Self(unsafe { $op(self.0, other.0) })
+                }
+            }
+        )*
+    };
+}
+
+binary_ops!(F64x2 {
+    impl Add::add = vaddq_f64;
+    impl Sub::sub = vsubq_f64;
+    impl Mul::mul = vmulq_f64;
+});
+
+binary_ops!(I32x4 {
+    impl Add::add = vaddq_s32;
+    impl Sub::sub = vsubq_s32;
+    impl Mul::mul = vmulq_s32;
+});
+
+impl F64x2 {
+    #[inline(always)]
+    pub fn from_array(values: [f64; 2]) -> Self {
+        Self::load(&values)
+    }
+
+    #[inline(always)]
+    pub fn to_array(self) -> [f64; 2] {
+        let mut values = [0.0; 2];
+        self.store(&mut values);
+        values
+    }
+}
+
+impl I32x4 {
+    #[inline(always)]
+    pub fn from_array(values: [i32; 4]) -> Self {
+        Self::load(&values)
+    }
+
+    #[inline(always)]
+    pub fn to_array(self) -> [i32; 4] {
+        let mut values = [0; 4];
+        self.store(&mut values);
+        values
+    }
+}
+
+// All intrinsics below only need NEON, which every aarch64 CPU has; loads and stores are
+// additionally covered by the slice length checks.
+
+impl Simd for F64x2 {
+    type Elem = f64;
+    const LANES: usize = 2;
+
+    #[inline(always)]
+    fn splat(value: f64) -> Self {
+        Self(unsafe { vdupq_n_f64(value) })
+    }
+
+    #[inline(always)]
+    fn load(slice: &[f64]) -> Self {
+        let slice = &slice[..Self::LANES];
+        Self(unsafe { vld1q_f64(slice.as_ptr()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [f64]) {
+        let slice = &mut slice[..Self::LANES];
+        unsafe { vst1q_f64(slice.as_mut_ptr(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        Self(unsafe { vfmaq_f64(c.0, self.0, b.0) })
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        Self(unsafe { vminq_f64(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        Self(unsafe { vmaxq_f64(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_f64_u64(vceqq_f64(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_f64_u64(vcltq_f64(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_f64_u64(vcleq_f64(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        let mask = unsafe { vreinterpretq_u64_f64(mask.0 .0) };
+        Self(unsafe { vbslq_f64(mask, if_true.0, if_false.0) })
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        let mask = unsafe { vreinterpretq_u64_f64(mask.0 .0) };
+        let (lo, hi) = unsafe { (vgetq_lane_u64::<0>(mask), vgetq_lane_u64::<1>(mask)) };
+        (lo & 1) as u32 | ((hi & 1) as u32) << 1
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> f64 {
+        unsafe { vaddvq_f64(self.0) }
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> f64 {
+        unsafe { vminvq_f64(self.0) }
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> f64 {
+        unsafe { vmaxvq_f64(self.0) }
+    }
+}
+
+impl Simd for I32x4 {
+    type Elem = i32;
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    fn splat(value: i32) -> Self {
+        Self(unsafe { vdupq_n_s32(value) })
+    }
+
+    #[inline(always)]
+    fn load(slice: &[i32]) -> Self {
+        let slice = &slice[..Self::LANES];
+        Self(unsafe { vld1q_s32(slice.as_ptr()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [i32]) {
+        let slice = &mut slice[..Self::LANES];
+        unsafe { vst1q_s32(slice.as_mut_ptr(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        Self(unsafe { vmlaq_s32(c.0, self.0, b.0) })
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        Self(unsafe { vminq_s32(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        Self(unsafe { vmaxq_s32(self.0, other.0) })
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_s32_u32(vceqq_s32(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_s32_u32(vcltq_s32(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        Mask(Self(unsafe {
+            vreinterpretq_s32_u32(vcleq_s32(self.0, other.0))
+        }))
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        let mask = unsafe { vreinterpretq_u32_s32(mask.0 .0) };
+        Self(unsafe { vbslq_s32(mask, if_true.0, if_false.0) })
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        // one bit per lane, weighted 1, 2, 4, 8, then summed across
+        let weights = unsafe { vld1q_u32([1, 2, 4, 8].as_ptr()) };
+        let mask = unsafe { vreinterpretq_u32_s32(mask.0 .0) };
+        unsafe { vaddvq_u32(vandq_u32(mask, weights)) }
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> i32 {
+        unsafe { vaddvq_s32(self.0) }
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> i32 {
+        unsafe { vminvq_s32(self.0) }
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> i32 {
+        unsafe { vmaxvq_s32(self.0) }
+    }
+}
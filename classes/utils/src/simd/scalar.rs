@@ -0,0 +1,217 @@
+use super::{Mask, Simd};
+use std::ops::{Add, Mul, Sub};
+
+// Arrays with one loop per operation, for targets without a vector backend here. The
+// loops have fixed trip counts, so LLVM unrolls them and vectorizes what it can.
+
+// What the loops need from a lane type
+trait Lane: Copy + Default + PartialOrd {
+    const SET: Self;
+    fn add(self, other: Self) -> Self;
+    fn sub(self, other: Self) -> Self;
+    fn mul(self, other: Self) -> Self;
+    fn mul_add(self, b: Self, c: Self) -> Self;
+    fn is_set(self) -> bool;
+}
+
+impl Lane for f64 {
+    // all bits set, a NaN
+    const SET: Self = f64::from_bits(u64::MAX);
+
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn mul(self, other: Self) -> Self {
+        self * other
+    }
+
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        // a software fma is far slower than the two operations
+        self * b + c
+    }
+
+    fn is_set(self) -> bool {
+        self.to_bits() != 0
+    }
+}
+
+impl Lane for i32 {
+    const SET: Self = -1;
+
+    fn add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self.wrapping_sub(other)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        self.wrapping_mul(other)
+    }
+
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        self.wrapping_mul(b).wrapping_add(c)
+    }
+
+    fn is_set(self) -> bool {
+        self != 0
+    }
+}
+
+macro_rules! array_simd {
+    ($($type:ident: $elem:ty, $lanes:literal);*) => {
+        $(
+            #[derive(Clone, Copy, Debug)]
+            pub struct $type([$elem; $lanes]);
+
+            impl $type {
+                #[inline(always)]
+                pub fn from_array(values: [$elem; $lanes]) -> Self {
+                    Self(values)
+                }
+
+                #[inline(always)]
+                pub fn to_array(self) -> [$elem; $lanes] {
+                    self.0
+                }
+
+                #[inline(always)]
+                fn map(self, other: Self, f: impl Fn($elem, $elem) -> $elem) -> Self {
+                    Self(std::array::from_fn(|i| f(self.0[i], other.0[i])))
+                }
+
+                #[inline(always)]
+                fn compare(self, other: Self, f: impl Fn($elem, $elem) -> bool) -> Mask<Self> {
+                    Mask(Self(std::array::from_fn(|i| {
+                        if f(self.0[i], other.0[i]) {
+                            <$elem as Lane>::SET
+                        } else {
+                            <$elem>::default()
+                        }
+                    })))
+                }
+
+                #[inline(always)]
+                fn fold(self, f: impl Fn($elem, $elem) -> $elem) -> $elem {
+                    self.0.into_iter().reduce(f).unwrap()
+                }
+            }
+
+            impl Simd for $type {
+                type Elem = $elem;
+                const LANES: usize = $lanes;
+
+                #[inline(always)]
+                fn splat(value: $elem) -> Self {
+                    Self([value; $lanes])
+                }
+
+                #[inline(always)]
+                fn load(slice: &[$elem]) -> Self {
+                    Self(slice[..$lanes].try_into().unwrap())
+                }
+
+                #[inline(always)]
+                fn store(self, slice: &mut [$elem]) {
+                    slice[..$lanes].copy_from_slice(&self.0);
+                }
+
+                #[inline(always)]
+                fn mul_add(self, b: Self, c: Self) -> Self {
+                    Self(std::array::from_fn(|i| Lane::mul_add(self.0[i], b.0[i], c.0[i])))
+                }
+
+                #[inline(always)]
+                fn min(self, other: Self) -> Self {
+                    self.map(other, |x, y| if y < x { y } else { x })
+                }
+
+                #[inline(always)]
+                fn max(self, other: Self) -> Self {
+                    self.map(other, |x, y| if y > x { y } else { x })
+                }
+
+                #[inline(always)]
+                fn simd_eq(self, other: Self) -> Mask<Self> {
+                    self.compare(other, |x, y| x == y)
+                }
+
+                #[inline(always)]
+                fn simd_lt(self, other: Self) -> Mask<Self> {
+                    self.compare(other, |x, y| x < y)
+                }
+
+                #[inline(always)]
+                fn simd_le(self, other: Self) -> Mask<Self> {
+                    self.compare(other, |x, y| x <= y)
+                }
+
+                #[inline(always)]
+                fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+                    Self(std::array::from_fn(|i| {
+                        if mask.0 .0[i].is_set() {
+                            if_true.0[i]
+                        } else {
+                            if_false.0[i]
+                        }
+                    }))
+                }
+
+                #[inline(always)]
+                fn bitmask(mask: Mask<Self>) -> u32 {
+                    (0..$lanes).fold(0, |bits, i| bits | (mask.0 .0[i].is_set() as u32) << i)
+                }
+
+                #[inline(always)]
+                fn reduce_sum(self) -> $elem {
+                    self.fold(Lane::add)
+                }
+
+                #[inline(always)]
+                fn reduce_min(self) -> $elem {
+                    self.fold(|x, y| if y < x { y } else { x })
+                }
+
+                #[inline(always)]
+                fn reduce_max(self) -> $elem {
+                    self.fold(|x, y| if y > x { y } else { x })
+                }
+            }
+
+            impl Add for $type {
+                type Output = Self;
+
+                #[inline(always)]
+                fn add(self, other: Self) -> Self {
+                    self.map(other, Lane::add)
+                }
+            }
+
+            impl Sub for $type {
+                type Output = Self;
+
+                #[inline(always)]
+                fn sub(self, other: Self) -> Self {
+                    self.map(other, Lane::sub)
+                }
+            }
+
+            impl Mul for $type {
+                type Output = Self;
+
+                #[inline(always)]
+                fn mul(self, other: Self) -> Self {
+                    self.map(other, Lane::mul)
+                }
+            }
+        )*
+    };
+}
+
+array_simd!(F64x2: f64, 2; F64x4: f64, 4; I32x4: i32, 4; I32x8: i32, 8);
@@ -0,0 +1,470 @@
+use super::{Mask, Simd};
+use std::arch::x86_64::*;
+use std::ops::{Add, Mul, Sub};
+
+// https://www.intel.com/content/www/us/en/docs/intrinsics-guide/index.html
+//
+// SSE2 is part of every x86_64 CPU. Everything above it is used only when the build
+// enables it, so none of these functions can meet an instruction the CPU lacks: that is
+// the safety argument for every `unsafe` below that isn't a load or store.
+
+#[derive(Clone, Copy, Debug)]
+pub struct F64x2(__m128d);
+
+#[derive(Clone, Copy, Debug)]
+pub struct I32x4(__m128i);
+
+#[cfg(target_feature = "avx2")]
+#[derive(Clone, Copy, Debug)]
+pub struct F64x4(__m256d);
+
+#[cfg(target_feature = "avx2")]
+#[derive(Clone, Copy, Debug)]
+pub struct I32x8(__m256i);
+
+// Arithmetic operators from intrinsics, `$op(a, b)`
+macro_rules! binary_ops {
+    ($type:ident { $(impl $trait:ident::$method:ident = $op:ident;)* }) => {
+        $(
+            impl $trait for $type {
+                type Output = Self;
+
+                #[inline(always)]
+                fn $method(self, other: Self) -> Self {
+                    Self(unsafe { $op(self.0, other.0) })
+                }
+            }
+        )*
+    };
+}
+
+// from_array, to_array and the reductions, which go through memory
+macro_rules! arrays {
+    ($type:ident, $elem:ty, $lanes:literal) => {
+        impl $type {
+            #[inline(always)]
+            pub fn from_array(values: [$elem; $lanes]) -> Self {
+                Self::load(&values)
+            }
+
+            #[inline(always)]
+            pub fn to_array(self) -> [$elem; $lanes] {
+                let mut values = [<$elem>::default(); $lanes];
+                self.store(&mut values);
+                values
+            }
+
+            #[inline(always)]
+            fn fold(self, f: impl Fn($elem, $elem) -> $elem) -> $elem {
+                self.to_array().into_iter().reduce(f).unwrap()
+            }
+        }
+    };
+}
+
+arrays!(F64x2, f64, 2);
+arrays!(I32x4, i32, 4);
+#[cfg(target_feature = "avx2")]
+arrays!(F64x4, f64, 4);
+#[cfg(target_feature = "avx2")]
+arrays!(I32x8, i32, 8);
+
+binary_ops!(F64x2 {
+    impl Add::add = _mm_add_pd;
+    impl Sub::sub = _mm_sub_pd;
+    impl Mul::mul = _mm_mul_pd;
+});
+
+impl Simd for F64x2 {
+    type Elem = f64;
+    const LANES: usize = 2;
+
+    #[inline(always)]
+    fn splat(value: f64) -> Self {
+        unsafe { Self(_mm_set1_pd(value)) }
+    }
+
+    #[inline(always)]
+    fn load(slice: &[f64]) -> Self {
+        let slice = &slice[..Self::LANES];
+        // Safety: two readable doubles, loadu has no alignment requirement
+        Self(unsafe { _mm_loadu_pd(slice.as_ptr()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [f64]) {
+        let slice = &mut slice[..Self::LANES];
+        // Safety: two writable doubles
+        unsafe { _mm_storeu_pd(slice.as_mut_ptr(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        #[cfg(target_feature = "fma")]
+        return unsafe { Self(_mm_fmadd_pd(self.0, b.0, c.0)) };
+        #[cfg(not(target_feature = "fma"))]
+        return self * b + c;
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        unsafe { Self(_mm_min_pd(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        unsafe { Self(_mm_max_pd(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm_cmpeq_pd(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm_cmplt_pd(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm_cmple_pd(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        unsafe {
+            let mask = mask.0 .0;
+            Self(_mm_or_pd(
+                _mm_and_pd(mask, if_true.0),
+                _mm_andnot_pd(mask, if_false.0),
+            ))
+        }
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        unsafe { _mm_movemask_pd(mask.0 .0) as u32 }
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> f64 {
+        self.fold(|x, y| x + y)
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> f64 {
+        self.fold(f64::min)
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> f64 {
+        self.fold(f64::max)
+    }
+}
+
+binary_ops!(I32x4 {
+    impl Add::add = _mm_add_epi32;
+    impl Sub::sub = _mm_sub_epi32;
+});
+
+impl Mul for I32x4 {
+    type Output = Self;
+
+    #[inline(always)]
+    fn mul(self, other: Self) -> Self {
+        Self(mullo_epi32(self.0, other.0))
+    }
+}
+
+// SSE2 only multiplies even lanes into 64-bit products: multiply evens and odds, keep the
+// low halves and interleave them back
+#[inline(always)]
+fn mullo_epi32(a: __m128i, b: __m128i) -> __m128i {
+    #[cfg(target_feature = "sse4.1")]
+    return unsafe { _mm_mullo_epi32(a, b) };
+    #[cfg(not(target_feature = "sse4.1"))]
+    unsafe {
+        let even = _mm_mul_epu32(a, b);
+        let odd = _mm_mul_epu32(_mm_srli_epi64::<32>(a), _mm_srli_epi64::<32>(b));
+        _mm_unpacklo_epi32(
+            _mm_shuffle_epi32::<0b00_00_10_00>(even),
+            _mm_shuffle_epi32::<0b00_00_10_00>(odd),
+        )
+    }
+}
+
+impl Simd for I32x4 {
+    type Elem = i32;
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    fn splat(value: i32) -> Self {
+        unsafe { Self(_mm_set1_epi32(value)) }
+    }
+
+    #[inline(always)]
+    fn load(slice: &[i32]) -> Self {
+        let slice = &slice[..Self::LANES];
+        // Safety: 16 readable bytes, loadu has no alignment requirement
+        Self(unsafe { _mm_loadu_si128(slice.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [i32]) {
+        let slice = &mut slice[..Self::LANES];
+        // Safety: 16 writable bytes
+        unsafe { _mm_storeu_si128(slice.as_mut_ptr().cast(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        self * b + c
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        #[cfg(target_feature = "sse4.1")]
+        return unsafe { Self(_mm_min_epi32(self.0, other.0)) };
+        #[cfg(not(target_feature = "sse4.1"))]
+        return self.simd_lt(other).select(self, other);
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        #[cfg(target_feature = "sse4.1")]
+        return unsafe { Self(_mm_max_epi32(self.0, other.0)) };
+        #[cfg(not(target_feature = "sse4.1"))]
+        return self.simd_gt(other).select(self, other);
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm_cmpeq_epi32(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm_cmplt_epi32(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        unsafe {
+            // not greater than
+            let gt = _mm_cmpgt_epi32(self.0, other.0);
+            Mask(Self(_mm_xor_si128(gt, _mm_set1_epi32(-1))))
+        }
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        unsafe {
+            let mask = mask.0 .0;
+            Self(_mm_or_si128(
+                _mm_and_si128(mask, if_true.0),
+                _mm_andnot_si128(mask, if_false.0),
+            ))
+        }
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        unsafe { _mm_movemask_ps(_mm_castsi128_ps(mask.0 .0)) as u32 }
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> i32 {
+        self.fold(i32::wrapping_add)
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> i32 {
+        self.fold(i32::min)
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> i32 {
+        self.fold(i32::max)
+    }
+}
+
+#[cfg(target_feature = "avx2")]
+binary_ops!(F64x4 {
+    impl Add::add = _mm256_add_pd;
+    impl Sub::sub = _mm256_sub_pd;
+    impl Mul::mul = _mm256_mul_pd;
+});
+
+#[cfg(target_feature = "avx2")]
+impl Simd for F64x4 {
+    type Elem = f64;
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    fn splat(value: f64) -> Self {
+        unsafe { Self(_mm256_set1_pd(value)) }
+    }
+
+    #[inline(always)]
+    fn load(slice: &[f64]) -> Self {
+        let slice = &slice[..Self::LANES];
+        // Safety: four readable doubles, loadu has no alignment requirement
+        Self(unsafe { _mm256_loadu_pd(slice.as_ptr()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [f64]) {
+        let slice = &mut slice[..Self::LANES];
+        // Safety: four writable doubles
+        unsafe { _mm256_storeu_pd(slice.as_mut_ptr(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        #[cfg(target_feature = "fma")]
+        return unsafe { Self(_mm256_fmadd_pd(self.0, b.0, c.0)) };
+        #[cfg(not(target_feature = "fma"))]
+        return self * b + c;
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        unsafe { Self(_mm256_min_pd(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        unsafe { Self(_mm256_max_pd(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm256_cmp_pd::<_CMP_EQ_OQ>(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm256_cmp_pd::<_CMP_LT_OQ>(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm256_cmp_pd::<_CMP_LE_OQ>(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        unsafe { Self(_mm256_blendv_pd(if_false.0, if_true.0, mask.0 .0)) }
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        unsafe { _mm256_movemask_pd(mask.0 .0) as u32 }
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> f64 {
+        self.fold(|x, y| x + y)
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> f64 {
+        self.fold(f64::min)
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> f64 {
+        self.fold(f64::max)
+    }
+}
+
+#[cfg(target_feature = "avx2")]
+binary_ops!(I32x8 {
+    impl Add::add = _mm256_add_epi32;
+    impl Sub::sub = _mm256_sub_epi32;
+    impl Mul::mul = _mm256_mullo_epi32;
+});
+
+#[cfg(target_feature = "avx2")]
+impl Simd for I32x8 {
+    type Elem = i32;
+    const LANES: usize = 8;
+
+    #[inline(always)]
+    fn splat(value: i32) -> Self {
+        unsafe { Self(_mm256_set1_epi32(value)) }
+    }
+
+    #[inline(always)]
+    fn load(slice: &[i32]) -> Self {
+        let slice = &slice[..Self::LANES];
+        // Safety: 32 readable bytes, loadu has no alignment requirement
+        Self(unsafe { _mm256_loadu_si256(slice.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, slice: &mut [i32]) {
+        let slice = &mut slice[..Self::LANES];
+        // Safety: 32 writable bytes
+        unsafe { _mm256_storeu_si256(slice.as_mut_ptr().cast(), self.0) }
+    }
+
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        self * b + c
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        unsafe { Self(_mm256_min_epi32(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        unsafe { Self(_mm256_max_epi32(self.0, other.0)) }
+    }
+
+    #[inline(always)]
+    fn simd_eq(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm256_cmpeq_epi32(self.0, other.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_lt(self, other: Self) -> Mask<Self> {
+        unsafe { Mask(Self(_mm256_cmpgt_epi32(other.0, self.0))) }
+    }
+
+    #[inline(always)]
+    fn simd_le(self, other: Self) -> Mask<Self> {
+        unsafe {
+            let gt = _mm256_cmpgt_epi32(self.0, other.0);
+            Mask(Self(_mm256_xor_si256(gt, _mm256_set1_epi32(-1))))
+        }
+    }
+
+    #[inline(always)]
+    fn select(mask: Mask<Self>, if_true: Self, if_false: Self) -> Self {
+        unsafe { Self(_mm256_blendv_epi8(if_false.0, if_true.0, mask.0 .0)) }
+    }
+
+    #[inline(always)]
+    fn bitmask(mask: Mask<Self>) -> u32 {
+        unsafe { _mm256_movemask_ps(_mm256_castsi256_ps(mask.0 .0)) as u32 }
+    }
+
+    #[inline(always)]
+    fn reduce_sum(self) -> i32 {
+        self.fold(i32::wrapping_add)
+    }
+
+    #[inline(always)]
+    fn reduce_min(self) -> i32 {
+        self.fold(i32::min)
+    }
+
+    #[inline(always)]
+    fn reduce_max(self) -> i32 {
+        self.fold(i32::max)
+    }
+}
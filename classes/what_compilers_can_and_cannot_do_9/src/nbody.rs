@@ -1,28 +1,33 @@
 use rand::Rng;
+use utils::simd::{F64x2, Simd};
 
+// Both coordinates in one 128-bit register
 #[derive(Debug, Clone, Copy)]
-struct Vec2 {
-    x: f64,
-    y: f64,
-}
+struct Vec2(F64x2);
 
 impl Vec2 {
+    fn new(x: f64, y: f64) -> Vec2 {
+        Vec2(F64x2::from_array([x, y]))
+    }
+
+    fn x(&self) -> f64 {
+        self.0.to_array()[0]
+    }
+
+    fn y(&self) -> f64 {
+        self.0.to_array()[1]
+    }
+
     fn add(&self, other: Vec2) -> Vec2 {
-        Vec2 {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
+        Vec2(self.0 + other.0)
     }
 
     fn scale(&self, scalar: f64) -> Vec2 {
-        Vec2 {
-            x: self.x * scalar,
-            y: self.y * scalar,
-        }
+        Vec2(self.0 * F64x2::splat(scalar))
     }
 
     fn length_squared(&self) -> f64 {
-        self.x * self.x + self.y * self.y
+        (self.0 * self.0).reduce_sum()
     }
 }
 
@@ -39,12 +44,9 @@ impl Default for Body {
         let mut rng = rand::thread_rng();
 
         Self {
-            position: Vec2 {
-                x: rng.gen_range(-10.0..10.0),
-                y: rng.gen_range(-10.0..10.0),
-            },
-            velocity: Vec2 { x: 0.0, y: 0.0 },
-            force: Vec2 { x: 0.0, y: 0.0 },
+            position: Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)),
+            velocity: Vec2::new(0.0, 0.0),
+            force: Vec2::new(0.0, 0.0),
             mass: rng.gen_range(1.0..100.0),
         }
     }
@@ -79,7 +81,9 @@ fn main() {
     for (i, body) in bodies.iter().enumerate() {
         println!(
             "Body {}: Position: ({:.2}, {:.2})",
-            i, body.position.x, body.position.y
+            i,
+            body.position.x(),
+            body.position.y()
         );
     }
 }